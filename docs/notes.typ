@@ -29,6 +29,7 @@ _Ahex_ is a minimalistic puzzle game with hexagonal movements. This document lis
 = Aesthetic
 - [x] I'd like to go for a soft pastel aesthetic, inspired by games such as #link("https://tunicgame.com/")[Tunic].
 - [x] The world has a baseline made of water, which is implemented using the `bevy_water` crate.
+- [x] Sound effects accompany moving, sliding, landing, moving tiles, collecting a _banana_, splashing into the water and completing a level, and each biome has its own ambient loop. All sounds are synthesized when the game starts, so that no sound files have to be shipped. The volumes are set in the `[audio]` section of the settings, where audio can also be disabled entirely for headless runs. The `--audio` and `--no-audio` options override that setting without rebuilding the game.
 - [x] Tiles are hexagonal with the tip pointing up. This is a stylistic design choice. The hexagon is defined through a circumcircle with unit radius, yielding the following geometry:
 
 #figure(
@@ -84,8 +85,12 @@ Due to Bevy, the _xz_-plane is the "flat" ground plane. The _y_ axis points up.
   - [ ] If a level has become unwinnable (due to the player or the _banana_ falling into the water), the game should hint to use this restart combination.
   - [ ] Players falling into water should despawn.
   - [ ] If no player remains but there is at least one _banana_ left, the game will know that the level cannot be won.
-- [ ] The player should be able to undo his last moves with `R` or similar. To do this, we need to keep the state of the entire level for each step.
-  - [ ] The player should be able to undo multiple moves as well.
+- [x] The player should be able to undo his last moves with `R` or similar. To do this, we need to keep the state of the entire level for each step.
+  - [x] The player should be able to undo multiple moves as well.
+- [x] A HUD in the top right corner shows the number of moves, the number of undos and the time spent on the level, next to the help text. Undoing a move takes it off the move count again.
+- [x] A level can set a maximum number of moves. Once that many moves have been made without collecting every _banana_, the level is lost and the last moves have to be undone, or the level restarted.
+- [x] A turn is played in fixed phases: the players move and slide, then the enemies, and then the environment, in which switches are pressed, tiles follow their paths and carry anything on them, conveyors push and the water moves. Anything left without support at the end of a phase falls before the next phase starts, so objects never fall through tiles that are still on their way. At the end of the environment, this goes for every object, including ones that stood still on a bridge that was hidden. Only tiles and resting objects hold something up, so a stack of objects without a tile below it falls as a whole. Goals and keys are collected, and players drown or are caught, as soon as they get there.
+- [x] Controls are bound to actions, which can be rebound in-game with `F1`. A key or button that is already in use swaps bindings with the action that had it, and the rebound controls are saved to `saves/input.toml`. Help texts write actions in braces, such as `{Undo}`, and show the key that is bound to them. Gamepads are supported as well; the stick direction maps to the nearest hex direction on screen.
 - [ ] The level is only completed if all _bananas_ are captured *and* all players have stopped moving *and* there is at least one player left.
   - This can make for some interesting levels where players must be sacrificed (but not all), and create traps where the _banana_ can be captuerd, but only at the expense of the last remaining player.s
 
@@ -103,6 +108,22 @@ Due to Bevy, the _xz_-plane is the "flat" ground plane. The _y_ axis points up.
         Of course this path _should_ return to the tile's original position, although this is not a strict requirement.
   - [x] If the player is on a tile that is moving, the player should move along with it.
     - [ ] This should keep in mind collisions, e.g. the player can be shoved off it it hits a wall along the way.
+  - [x] A moving tile does not take its step if it would move into a tile that stands still. It waits instead, and tries the next step of its path in the next turn.
+  - [x] Tiles can be _linked_ into a platform that shares one path and moves as a whole. Anything standing on any of its tiles moves along, and if one of its tiles is blocked, the entire platform waits.
+- [x] _Switches_ lie on top of tiles. A _pressure plate_ is on for as long as something stands on it, and a _toggle switch_ turns on or off each time something arrives on it. Switches are checked at the start of the level effects, before any tile moves.
+  - [x] Moving tiles can be _triggered_ by a switch. They only follow their path while it is on, and wait where they are otherwise.
+  - [x] _Bridges_ are tiles that are only there while a switch is on (or only while it is off). A hidden bridge cannot be walked on and does not block moving tiles.
+  - [ ] Plates are pressed by the player and the _banana_ for now. Once there are _crates_, they should press plates as well.
+  - [ ] Switches cannot turn off _lasers_ yet, since there are no lasers. This is left for when lasers are added (see below).
+- [x] _Teleporter pads_ come in pairs. An object that ends a step on one pad comes out of the other one, unless something is standing there. It keeps moving in the same direction, so that it slides on if the other pad is icy.
+- [x] _Conveyors_ push anything resting on them one hex per turn during the level effects, unless a higher tile is in the way. Objects pushed onto the next conveyor are pushed on in the next turn, objects pushed onto ice slide on, and objects pushed off an edge fall down.
+  - [ ] _Crates_ should be pushed by conveyors as well, since they will be carriable.
+- [x] _Walls_ stand on the edges of a tile. Nothing can move across such an edge at the height of the tile, whether by walking, sliding or being pushed by a conveyor. Walls move along with their tile.
+- [x] _One-way tiles_ can only be entered and left by moving in their direction. Objects that are higher up are not affected by walls or one-way tiles.
+- [x] _Keys_ lie on top of tiles and are collected by the player, who holds them in an inventory that is shown at the top of the screen. A _gate_ locks its tile until the player walks into it holding a key of the same color, which uses up the key and leaves the gate open for the rest of the level. The player also uses and collects keys while sliding or being pushed by a conveyor, but other objects never do, so they stop at locked gates. Undoing a turn puts back the keys and locks the gates again.
+- [x] The _water_ can take part in a level. Tiles below its height cannot be walked on, and a player that ends up below it drowns, after which the turn has to be undone or the level restarted. The water rises and falls during the level effects, together with the moving tiles, and can be triggered by a switch.
+  - [ ] Goals and crates under water should be lost as well.
+- [x] _Enemies_ move one hex per turn after the player, before the level effects. A _patrolling_ enemy follows a fixed route, skipping steps it cannot take, and a _chasing_ enemy steps towards the nearest player if that brings it closer. They walk by the same rules as the player without using keys, never step onto a hex where another enemy stands or is about to land, and slide, fall, get carried and get pushed like the player. A player that ends up on the same hex as an enemy is caught, after which the turn has to be undone or the level restarted.
 - [x] Tiles may be _slippery_. If the player moves on them, the player will continue to move until an end is reached (wall, or edge of the map).
 - [ ] Tiles may be _fragile_. After the player has stepped on it, it will crumble as soon as the player steps off it.
   - [ ] Some _fragile_ tiles might be rechargable.
@@ -111,6 +132,7 @@ Due to Bevy, the _xz_-plane is the "flat" ground plane. The _y_ axis points up.
 - [ ] The player is only strong enough to push one _crate_ at a time (I think). A series of crates are therefore not pushable in the direction that they form a series in.
 - [ ] _Lasers_ block the player from moving through them, much like walls. Lasers extend across the entire level, until blocked by something solid.
   - [ ] _Lasers_ may be blocked by the player pushing a _crate_ into its path.
+  - [ ] _Lasers_ may be turned off by a switch, in the same way as moving tiles are triggered and bridges are shown.
 - [ ] _Trampolines_ cause the player or any other solid object to jump one tile. This can be used to cross bridges.
   - [ ] _Trampolines_ could come in fixed or in _crate_-like variants (which can be moved). Note that for this, tile heights must be uniform!
   - [ ] If a player falls down flat on top of a _trampoline_, they can no longer move in any direction. This should trigger the restart hint.
@@ -120,6 +142,34 @@ Due to Bevy, the _xz_-plane is the "flat" ground plane. The _y_ axis points up.
 - [x] The format should be easily extendible. Everything should start in a section to allow for extension.
 - [x] Most levels will need only one layer of tiles (i.e. at most one tile at a given $y$ value). However, the format should allow for multiple layers in case a level will contain caves, stacked layers, and so on.
 - [x] The height map can be rectangular, and should map to corresponding $x z$-coordinates. The values of tiles can range from `0` to `9` by default. In practice tiles probably won't get higher than this.
+  - [x] Higher and lower tiles are written with letters: `a` to `z` are the heights $10$ to $35$, and `A` to `Z` are the heights $-1$ to $-26$. A `.` is a hex without a tile; any other character is an error.
+- [x] Instead of (or next to) the height map, a layer can list its tiles one by one with their axial coordinates. Here $q$ and $r$ are the $x$ and $z$ tile coordinates, where $(0, 0)$ is the middle of the height map. Any height is allowed, and TOML comments can be added to each tile:
+```toml
+[[layers.tiles]]
+name = "elevator" # optional, only to tell tiles apart
+q = 2
+r = -1
+height = 12
+player = false
+goal = false
+icy = false
+movement_map = [[0, 1, 0], [0, -1, 0]]
+```
+  - [x] The level editor writes tiles that don't fit in the height map in this form.
+- [x] Paths of moving tiles are defined by shape in a `[movements.<id>]` table. An id of a single lowercase letter can be written in a modifier map to make that tile move; listed tiles use `movement = "<id>"`.
+  - [x] Shapes: `ping_pong` (`direction`, `amplitude`) jumps back and forth, `line` (`direction`, `amplitude`) moves one hex per turn and back, `circle` (`radius`) goes around a hexagonal ring, `elevator` (`height`) goes up and down one level per turn, and `offsets` lists every step.
+  - [x] `phase` is the number of turns that have already passed when the level starts, and `period` is the number of turns before the path repeats; the tile waits at the end of its path for the remaining turns. Listed tiles can override both, so that tiles can share a path but start at different points.
+  - [x] With `linked = true`, all tiles that use the movement form a single platform. Linked tiles cannot override `phase` or `period`.
+  - [x] With `triggered_by = "<switch id>"`, the tiles only move while that switch is on.
+  - [x] The old `M` modifier with a list of `movement_maps`, matched in the order in which they appear, is still supported.
+- [x] `max_moves = <number>` in the `[metadata]` table limits the number of moves in which the level has to be won.
+- [x] Switches are defined in a `[switches.<id>]` table with a `kind` (`plate` or `toggle`) and whether they are `on` at the start. Listed tiles place a switch with `switch = "<id>"`, and become a bridge with `shown_by = "<id>"` or `hidden_by = "<id>"`. All switches with the same id work together.
+- [x] Listed tiles place a teleporter pad with `teleporter = "<id>"`. Exactly two pads share each id, and these are paired with each other. Pads cannot move or belong to a switch.
+- [x] Listed tiles place a conveyor with `conveyor = "<direction>"`, in the same notation as movement directions. Conveyors cannot move or belong to a switch either.
+- [x] A `[water]` table gives the `height` of the water at the start of the level. Without it, the water is only decoration. A `[water.movement]` table, in the same form as the movements of tiles, makes the water rise and fall; only vertical steps are allowed.
+- [x] Listed tiles get walls with `walls = ["<direction>", ...]`, naming the sides of the hex that have one, and become one-way tiles with `one_way = "<direction>"`.
+- [x] Listed tiles place a key with `key = "<color>"` and a gate with `gate = "<color>"`, where the color is `red`, `green`, `blue` or `yellow`. Keys and gates cannot move or belong to a switch, and a key cannot lie on a gate.
+- [x] Listed tiles place an enemy with `enemy = "patrol"` or `enemy = "chase"`. A patrolling enemy needs a `route = ["<direction>", ...]`, which it repeats from the start once it reaches the end.
 - [x] Tiles can be applied one or multiple sets of _modifiers_. Modifiers include:
   - [x] Has a player on top of it
   - [x] Has a goal on top of it
@@ -0,0 +1,79 @@
+//! Generates a level and prints it in the level file format.
+//!
+//! Usage: generate_level [--seed N] [--width N] [--height N] [--heights N] [--ice P]
+//!                       [--platforms N] [--min-moves N] [--output FILE]
+//!
+//! Options that are not given are taken from the `[generator]` section of the settings.
+
+use std::{env, fs, process::ExitCode};
+
+use ahex::{
+    generator::generate_level,
+    resources::{replay::ReplayStep, settings::Settings},
+};
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse<T: std::str::FromStr>(option: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or(format!("Missing value for {option}"))?;
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value for {option}: {value}"))
+}
+
+fn run() -> Result<(), String> {
+    let settings: Settings =
+        toml::from_str(include_str!("../settings.toml")).expect("Could not parse settings");
+    let mut generator = settings.generator;
+    let mut seed = 0;
+    let mut output = None;
+
+    let mut arguments = env::args().skip(1);
+    while let Some(option) = arguments.next() {
+        let value = arguments.next();
+
+        match option.as_str() {
+            "--seed" => seed = parse(&option, value)?,
+            "--width" => generator.width = parse(&option, value)?,
+            "--height" => generator.height = parse(&option, value)?,
+            "--heights" => generator.height_levels = parse(&option, value)?,
+            "--ice" => generator.ice_density = parse(&option, value)?,
+            "--platforms" => generator.moving_platforms = parse(&option, value)?,
+            "--min-moves" => generator.min_solution_length = parse(&option, value)?,
+            "--output" => output = Some(parse::<String>(&option, value)?),
+            _ => return Err(format!("Unknown option {option}")),
+        }
+    }
+
+    let generated = generate_level(seed, &generator)?;
+    let level = generated.level.to_toml();
+
+    match output {
+        Some(path) => {
+            fs::write(&path, level).map_err(|error| format!("Could not write {path}: {error}"))?
+        }
+        None => print!("{level}"),
+    }
+
+    let solution: Vec<&str> = generated
+        .solution
+        .into_iter()
+        .map(|direction| ReplayStep::Move(direction).to_token())
+        .collect();
+
+    eprintln!(
+        "Seed {seed}, solved in {} moves: {}",
+        solution.len(),
+        solution.join(" ")
+    );
+
+    Ok(())
+}
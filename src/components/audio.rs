@@ -0,0 +1,39 @@
+use bevy::ecs::{component::Component, event::Event};
+
+use crate::components::level::LevelMetadataBiome;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SoundEffect {
+    Move,
+    Slide,
+    Land,
+    PlatformMove,
+    GoalCollected,
+    Splash,
+    LevelComplete,
+}
+
+impl SoundEffect {
+    pub const ALL: [SoundEffect; 7] = [
+        SoundEffect::Move,
+        SoundEffect::Slide,
+        SoundEffect::Land,
+        SoundEffect::PlatformMove,
+        SoundEffect::GoalCollected,
+        SoundEffect::Splash,
+        SoundEffect::LevelComplete,
+    ];
+}
+
+/// Triggered by the game whenever something happens that can be heard. Without the audio
+/// systems, as in headless runs, nothing observes it.
+#[derive(Event)]
+pub struct PlaySoundEffect {
+    pub effect: SoundEffect,
+}
+
+/// The sound that loops in the background. It is kept between levels of the same biome.
+#[derive(Component)]
+pub struct AmbientLoop {
+    pub biome: LevelMetadataBiome,
+}
@@ -1,4 +1,4 @@
-use bevy::ecs::component::Component;
+use bevy::{ecs::component::Component, math::Vec3};
 
 #[derive(Component, Default)]
 pub struct CameraAngle {
@@ -6,9 +6,15 @@ pub struct CameraAngle {
     pub rotation_direction: Option<RotationDirection>,
     pub rotation_animation_percentage: Option<f32>,
     pub rotation_speed: f32,
+    /// Multiplier on the distance at which the whole level fits in view.
+    pub zoom: f32,
+    /// The angle between the ground plane and the line of sight, in radians.
+    pub pitch: f32,
+    /// The point that the camera looks at and rotates around.
+    pub focus: Vec3,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum RotationDirection {
     Clockwise,
     CounterClockwise,
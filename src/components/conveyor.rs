@@ -0,0 +1,16 @@
+use bevy::{ecs::component::Component, math::Vec3};
+
+use crate::components::tile_coordinates::MovementDirection;
+
+/// A conveyor belt lying on top of a tile. It pushes anything resting on it one hex per turn.
+#[derive(Component, Clone, Debug)]
+pub struct Conveyor {
+    pub direction: MovementDirection,
+}
+
+/// The arrow that shows which way a conveyor pushes. It moves along the conveyor over and over.
+#[derive(Component)]
+pub struct ConveyorArrow {
+    /// The direction of the conveyor in transform coordinates, with a length of one.
+    pub direction: Vec3,
+}
@@ -0,0 +1,5 @@
+use bevy::ecs::component::Component;
+
+/// Marks the explanation of the level editor controls.
+#[derive(Component)]
+pub struct EditorTextMarker;
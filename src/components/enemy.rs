@@ -0,0 +1,90 @@
+use bevy::ecs::{component::Component, event::Event};
+use serde::{Deserialize, Serialize};
+
+use crate::components::tile_coordinates::{MovementDirection, hex_distance};
+
+/// How an enemy is written in a level file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnemyKind {
+    Patrol,
+    Chase,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum EnemyBehaviour {
+    /// Takes the steps of the route one per turn, and starts over at the end.
+    /// A step that cannot be taken is skipped.
+    Patrol(Vec<MovementDirection>),
+    /// Takes one step per turn towards the nearest player, if that brings it closer.
+    Chase,
+}
+
+impl EnemyBehaviour {
+    /// The direction in which an enemy at the position moves this turn, if any.
+    /// A step is only taken if `can_move` allows it, following the same rules as for the players.
+    pub fn get_direction(
+        &self,
+        route_index: usize,
+        position: (isize, isize, isize),
+        players: impl Iterator<Item = (isize, isize, isize)>,
+        can_move: impl Fn(MovementDirection) -> bool,
+    ) -> Option<MovementDirection> {
+        match self {
+            EnemyBehaviour::Patrol(route) => {
+                let direction = *route.get(route_index % route.len().max(1))?;
+                can_move(direction).then_some(direction)
+            }
+            EnemyBehaviour::Chase => {
+                let distance_to = |from: (isize, isize), player: (isize, isize, isize)| {
+                    hex_distance(from, (player.0, player.2))
+                };
+                let target = players.min_by_key(|player| {
+                    (distance_to((position.0, position.2), *player), *player)
+                })?;
+                let distance = distance_to((position.0, position.2), target);
+
+                MovementDirection::ALL
+                    .into_iter()
+                    .filter(|direction| can_move(*direction))
+                    .map(|direction| {
+                        let offset = direction.get_tile_coordinate_offset();
+                        let next = (
+                            position.0 + offset.x as isize,
+                            position.2 + offset.z as isize,
+                        );
+                        (distance_to(next, target), direction)
+                    })
+                    .filter(|(next_distance, _)| *next_distance < distance)
+                    .min_by_key(|(next_distance, _)| *next_distance)
+                    .map(|(_, direction)| direction)
+            }
+        }
+    }
+
+    /// Where along its route an enemy is after a turn. Patrolling enemies move on to the next step
+    /// of their route, even if they could not take the current one.
+    pub fn next_route_index(&self, route_index: usize) -> usize {
+        match self {
+            EnemyBehaviour::Patrol(route) => (route_index + 1) % route.len().max(1),
+            EnemyBehaviour::Chase => route_index,
+        }
+    }
+}
+
+/// A creature that moves after the players, and defeats any player it ends up with on the same hex.
+#[derive(Component, Clone, Debug)]
+pub struct Enemy {
+    pub behaviour: EnemyBehaviour,
+    /// How far along its route a patrolling enemy is.
+    pub route_index: usize,
+}
+
+/// Marks a player that an enemy has caught. It cannot move until the turn is undone or the level
+/// is restarted.
+#[derive(Component)]
+pub struct Caught;
+
+/// Triggered once every enemy has finished its move and landed, after which the level effects start.
+#[derive(Event)]
+pub struct EnemiesFinishedMoving {}
@@ -0,0 +1,6 @@
+use bevy::ecs::component::Component;
+
+/// A translucent preview of where a moving tile, or an object carried
+/// by it, will be after the next turn.
+#[derive(Component)]
+pub struct PredictionGhost;
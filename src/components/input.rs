@@ -0,0 +1,10 @@
+use bevy::ecs::component::Component;
+
+/// Marks the text that prompts the player for a new binding.
+#[derive(Component)]
+pub struct RebindingTextMarker;
+
+/// A text that names the keys of actions, written in braces as in `{Undo}`. The names are filled in
+/// from the bindings, and again whenever the bindings change.
+#[derive(Component)]
+pub struct KeyHint(pub String);
@@ -0,0 +1,46 @@
+use bevy::{color::Color, ecs::component::Component};
+use serde::{Deserialize, Serialize};
+
+/// The color of a key, which opens the gates of the same color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyColor {
+    Red,
+    Green,
+    Blue,
+    Yellow,
+}
+
+impl KeyColor {
+    pub const ALL: [KeyColor; 4] = [
+        KeyColor::Red,
+        KeyColor::Green,
+        KeyColor::Blue,
+        KeyColor::Yellow,
+    ];
+
+    pub fn color(&self) -> Color {
+        let hue = match self {
+            KeyColor::Red => 0.0,
+            KeyColor::Green => 120.0,
+            KeyColor::Blue => 220.0,
+            KeyColor::Yellow => 55.0,
+        };
+
+        Color::hsl(hue, 0.9, 0.55)
+    }
+}
+
+/// A key lying on top of a tile. It is collected when a player passes over it.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Key {
+    pub color: KeyColor,
+}
+
+/// The mesh of a locked gate, a child of its tile.
+#[derive(Component)]
+pub struct GateMesh;
+
+/// Marks the row of held keys at the top of the screen.
+#[derive(Component)]
+pub struct KeyHudMarker;
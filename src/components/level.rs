@@ -1,8 +1,10 @@
 use bevy::{
     asset::AssetServer,
+    camera::visibility::Visibility,
     color::{Color, palettes::css::YELLOW},
     ecs::{
         component::Component,
+        event::Event,
         system::{Commands, Res},
     },
     light::{NotShadowCaster, PointLight},
@@ -12,19 +14,35 @@ use bevy::{
     text::TextFont,
     transform::components::Transform,
     ui::{
-        Node, PositionType, px,
+        AlignItems, FlexDirection, JustifyContent, Node, PositionType, UiRect, Val, percent, px,
         widget::{Text, TextShadow},
     },
     utils::default,
 };
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
 use bevy_gltf::GltfAssetLabel;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::components::{
-    goal::Goal,
-    player::Player,
-    tile::{Carriable, HasGravity, IcyTile, MovementMap, ShouldRenderMovementMapPolylines, Tile},
-    tile_coordinates::TileCoordinates,
+use crate::{
+    components::{
+        conveyor::Conveyor,
+        enemy::{Enemy, EnemyBehaviour, EnemyKind},
+        goal::Goal,
+        input::KeyHint,
+        key::{Key, KeyColor},
+        player::Player,
+        switch::{Bridge, Switch, SwitchKind},
+        teleporter::{Teleporter, get_teleporter_exits},
+        tile::{Carriable, HasGravity, IcyTile, MovementMap, Tile, TileEdges},
+        tile_coordinates::{
+            MovementDirection, TileCoordinates, tile_coordinates_to_transform_coordinates,
+        },
+    },
+    resources::{
+        camera::CameraFraming, keys::KeyInventory, stats::LevelStats, switches::SwitchStates,
+        water::Water,
+    },
 };
 
 #[derive(Component)]
@@ -33,60 +51,715 @@ pub struct LevelEntityMarker;
 #[derive(Component)]
 pub struct HelpTextMarker;
 
+/// The column in the top right corner that holds the move counter and the held keys.
+#[derive(Component)]
+pub struct HudMarker;
+
+#[derive(Component)]
+pub struct HudTextMarker;
+
+/// The message in the HUD that explains why the level was lost.
+#[derive(Component)]
+pub struct LoseTextMarker;
+
 #[derive(Component)]
 pub struct LevelCompleteTextMarker;
 
+/// Triggered whenever a level is (re)built from its level file.
+#[derive(Event)]
+pub struct LevelStarted {
+    pub biome: LevelMetadataBiome,
+}
+
 #[derive(Component, Deserialize, Debug)]
 pub struct Level {
     pub metadata: LevelMetadata,
+    /// The paths of moving tiles by their id. Ids of a single lowercase letter
+    /// can be used in modifier maps to make the tile in that hex move along the path.
+    #[serde(default)]
+    pub movements: BTreeMap<String, LevelMovement>,
+    /// The switches by their id. Switches are placed on listed tiles.
+    #[serde(default)]
+    pub switches: BTreeMap<String, LevelSwitch>,
+    /// Without it, the water is only decoration.
+    pub water: Option<LevelWater>,
     pub layers: Vec<LevelLayer>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct LevelMetadata {
-    #[allow(unused)]
     pub name: String,
-    #[allow(unused)]
     pub biome: LevelMetadataBiome,
     pub help_text: String,
+    #[serde(default)]
+    pub camera: LevelMetadataCamera,
+    /// If given, the level is lost when this many moves have been made without winning it.
+    pub max_moves: Option<usize>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum LevelMetadataBiome {
     DAYLIGHT,
     DUSK,
     NIGHT,
 }
 
+impl LevelMetadataBiome {
+    pub const ALL: [LevelMetadataBiome; 3] = [
+        LevelMetadataBiome::DAYLIGHT,
+        LevelMetadataBiome::DUSK,
+        LevelMetadataBiome::NIGHT,
+    ];
+}
+
+/// How the camera frames the level. Large levels can opt in to having the
+/// camera follow the players around instead of showing the whole level at once.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LevelMetadataCamera {
+    #[default]
+    Fixed,
+    Follow,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct LevelLayer {
     pub pillars: Option<bool>,
+    #[serde(default)]
     pub height_map: String,
+    #[serde(default)]
     pub modifiers: Vec<String>,
     pub movement_maps: Option<Vec<Vec<(isize, isize, isize)>>>,
+    /// Tiles given by their coordinates instead of by their place in the height map.
+    #[serde(default)]
+    pub tiles: Vec<LevelTile>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LevelMovement {
+    #[serde(flatten)]
+    pub shape: MovementShape,
+    /// The number of turns of the movement that have already passed when the level starts.
+    #[serde(default)]
+    pub phase: usize,
+    /// The number of turns after which the movement repeats. The tile waits at
+    /// the end of its path for the turns that are left. Defaults to the length of the path.
+    pub period: Option<usize>,
+    /// Whether all tiles with this movement are linked together into a single platform.
+    #[serde(default)]
+    pub linked: bool,
+    /// The id of a switch. If given, the tiles only move while the switch is on.
+    pub triggered_by: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LevelSwitch {
+    pub kind: SwitchKind,
+    /// Whether the switch is on when the level starts. Plates are only on while something stands on them.
+    #[serde(default)]
+    pub on: bool,
+}
+
+/// Water that floods the tiles below its height, and can rise and fall during the level.
+#[derive(Deserialize, Debug, Clone)]
+pub struct LevelWater {
+    /// The height of the water when the level starts. Tiles lower than this are under water.
+    #[serde(default)]
+    pub height: isize,
+    /// How the water rises and falls, in the same form as the movements of tiles.
+    /// Only vertical steps are allowed, and the water cannot be linked.
+    pub movement: Option<LevelMovement>,
+}
+
+impl LevelWater {
+    pub fn get_water(&self) -> Result<Water, String> {
+        let Some(movement) = &self.movement else {
+            return Ok(Water {
+                height: Some(self.height),
+                ..default()
+            });
+        };
+
+        let map = movement.get_movement_map()?;
+        if map.iter().any(|offset| offset.0 != 0 || offset.2 != 0) {
+            return Err("The water can only move up and down".to_owned());
+        }
+
+        if movement.linked {
+            return Err("The water cannot be linked".to_owned());
+        }
+
+        Ok(Water {
+            height: Some(self.height),
+            movement_map: MovementMap {
+                map,
+                switch: movement.triggered_by.clone(),
+                ..default()
+            },
+        })
+    }
+}
+
+/// The shape of the path of a moving tile, which starts where the tile is placed.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "shape", rename_all = "snake_case")]
+pub enum MovementShape {
+    /// Jumps back and forth between two hexes.
+    PingPong {
+        direction: MovementDirection,
+        amplitude: isize,
+    },
+    /// Moves one hex at a time along a line, and back.
+    Line {
+        direction: MovementDirection,
+        amplitude: isize,
+    },
+    /// Moves one hex at a time around a hexagonal ring, clockwise.
+    Circle {
+        radius: usize,
+    },
+    /// Moves up one level at a time and back down, or down first if the height is negative.
+    Elevator {
+        height: isize,
+    },
+    Offsets {
+        offsets: Vec<(isize, isize, isize)>,
+    },
+}
+
+impl LevelMovement {
+    /// The offset of every turn, including the turns in which the tile waits.
+    pub fn get_movement_map(&self) -> Result<Vec<(isize, isize, isize)>, String> {
+        let get_offset = |direction: &MovementDirection, amplitude: isize| {
+            let offset = direction.get_tile_coordinate_offset();
+            (
+                offset.x as isize * amplitude,
+                0,
+                offset.z as isize * amplitude,
+            )
+        };
+
+        let mut movement_map: Vec<(isize, isize, isize)> = match &self.shape {
+            MovementShape::PingPong {
+                direction,
+                amplitude,
+            } => vec![
+                get_offset(direction, *amplitude),
+                get_offset(direction, -*amplitude),
+            ],
+            MovementShape::Line {
+                direction,
+                amplitude,
+            } => {
+                let step = get_offset(direction, amplitude.signum());
+                let back = (-step.0, 0, -step.2);
+                [step, back]
+                    .into_iter()
+                    .flat_map(|offset| std::iter::repeat_n(offset, amplitude.unsigned_abs()))
+                    .collect()
+            }
+            MovementShape::Circle { radius } => MovementDirection::ALL
+                .iter()
+                .flat_map(|direction| std::iter::repeat_n(get_offset(direction, 1), *radius))
+                .collect(),
+            MovementShape::Elevator { height } => {
+                [(0, height.signum(), 0), (0, -height.signum(), 0)]
+                    .into_iter()
+                    .flat_map(|offset| std::iter::repeat_n(offset, height.unsigned_abs()))
+                    .collect()
+            }
+            MovementShape::Offsets { offsets } => offsets.clone(),
+        };
+
+        if let Some(period) = self.period {
+            if period < movement_map.len() {
+                return Err(format!(
+                    "The period of {period} turns is shorter than the path of {} steps",
+                    movement_map.len()
+                ));
+            }
+
+            movement_map.resize(period, (0, 0, 0));
+        }
+
+        if !movement_map.is_empty() {
+            let phase = self.phase % movement_map.len();
+            movement_map.rotate_left(phase);
+        }
+
+        Ok(movement_map)
+    }
+}
+
+/// A single tile of a layer, at the axial coordinates (`q`, `r`). These are the same as
+/// the tile coordinates (x, z), so `q` increases to the east and `r` to the north-east,
+/// and (0, 0) is the middle of the height maps.
+#[derive(Deserialize, Debug, Clone)]
+pub struct LevelTile {
+    /// Only used to tell tiles apart in the level file.
+    pub name: Option<String>,
+    pub q: isize,
+    pub r: isize,
+    pub height: isize,
+    #[serde(default)]
+    pub player: bool,
+    #[serde(default)]
+    pub goal: bool,
+    #[serde(default)]
+    pub icy: bool,
+    #[serde(default)]
+    pub movement_map: Vec<(isize, isize, isize)>,
+    /// The id of the movement of the tile, used instead of `movement_map`.
+    pub movement: Option<String>,
+    /// Replaces the phase of the movement for this tile only.
+    pub phase: Option<usize>,
+    /// Replaces the period of the movement for this tile only.
+    pub period: Option<usize>,
+    /// The id of a switch lying on top of the tile.
+    pub switch: Option<String>,
+    /// The id of a switch. The tile is only there while that switch is on.
+    pub shown_by: Option<String>,
+    /// The id of a switch. The tile is only there while that switch is off.
+    pub hidden_by: Option<String>,
+    /// The id of a teleporter pad lying on top of the tile. Exactly two tiles share each id.
+    pub teleporter: Option<String>,
+    /// The direction in which a conveyor belt on top of the tile pushes.
+    pub conveyor: Option<MovementDirection>,
+    /// The sides of the tile that have a wall.
+    #[serde(default)]
+    pub walls: Vec<MovementDirection>,
+    /// If given, the tile can only be entered and left by moving in this direction.
+    pub one_way: Option<MovementDirection>,
+    /// The color of a key lying on top of the tile.
+    pub key: Option<KeyColor>,
+    /// The color of a locked gate on the tile, which is opened with a key of the same color.
+    pub gate: Option<KeyColor>,
+    /// An enemy standing on top of the tile.
+    pub enemy: Option<EnemyKind>,
+    /// The steps of a patrolling enemy, which it takes one per turn and then repeats.
+    #[serde(default)]
+    pub route: Vec<MovementDirection>,
+}
+
+impl LevelTile {
+    pub fn get_edges(&self) -> TileEdges {
+        TileEdges {
+            walls: self.walls.clone(),
+            one_way: self.one_way,
+            gate: self.gate,
+        }
+    }
+
+    pub fn get_enemy(&self) -> Result<Option<EnemyBehaviour>, String> {
+        match (self.enemy, self.route.is_empty()) {
+            (None, true) => Ok(None),
+            (Some(EnemyKind::Patrol), false) => {
+                Ok(Some(EnemyBehaviour::Patrol(self.route.clone())))
+            }
+            (Some(EnemyKind::Patrol), true) => Err("A patrolling enemy needs a route".to_owned()),
+            (Some(EnemyKind::Chase), true) => Ok(Some(EnemyBehaviour::Chase)),
+            (_, false) => Err("Only patrolling enemies have a route".to_owned()),
+        }
+    }
+
+    /// The bridge that this tile is part of, if it is shown or hidden by a switch.
+    pub fn get_bridge(&self) -> Result<Option<Bridge>, String> {
+        match (&self.shown_by, &self.hidden_by) {
+            (None, None) => Ok(None),
+            (Some(switch), None) => Ok(Some(Bridge {
+                switch: switch.clone(),
+                is_shown_when_on: true,
+            })),
+            (None, Some(switch)) => Ok(Some(Bridge {
+                switch: switch.clone(),
+                is_shown_when_on: false,
+            })),
+            (Some(_), Some(_)) => {
+                Err("A tile cannot be both shown and hidden by a switch".to_owned())
+            }
+        }
+    }
+}
+
+/// What lies on a tile and how the tile is placed, which limits what else can be on it.
+/// Tiles of both the level files and the editor are checked with it.
+pub struct TileContents {
+    pub is_moving: bool,
+    pub is_bridge: bool,
+    pub has_teleporter: bool,
+    pub has_conveyor: bool,
+    pub has_key: bool,
+    pub has_gate: bool,
+}
+
+impl TileContents {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.is_bridge && self.is_moving {
+            return Err("Tiles that are shown or hidden by a switch cannot move".to_owned());
+        }
+
+        let is_fixed = !self.is_bridge && !self.is_moving;
+        if self.has_teleporter && !is_fixed {
+            return Err("Teleporter pads cannot move or be shown or hidden by a switch".to_owned());
+        }
+
+        if self.has_conveyor && !is_fixed {
+            return Err("Conveyors cannot move or be shown or hidden by a switch".to_owned());
+        }
+
+        if (self.has_key || self.has_gate) && !is_fixed {
+            return Err("Keys and gates cannot move or be shown or hidden by a switch".to_owned());
+        }
+
+        if self.has_key && self.has_gate {
+            return Err("A key cannot lie on a gate".to_owned());
+        }
+
+        Ok(())
+    }
+}
+
+pub const MIN_TILE_HEIGHT: isize = -26;
+pub const MAX_TILE_HEIGHT: isize = 35;
+
+/// Reads a single character of a height map: `.` is a hex without a tile, `0`-`9` are the
+/// heights 0 to 9, `a`-`z` continue with 10 to 35, and `A`-`Z` are the heights -1 to -26.
+pub fn parse_height(char: char) -> Result<Option<isize>, String> {
+    match char {
+        '.' => Ok(None),
+        '0'..='9' => Ok(Some(char as isize - '0' as isize)),
+        'a'..='z' => Ok(Some(char as isize - 'a' as isize + 10)),
+        'A'..='Z' => Ok(Some('A' as isize - char as isize - 1)),
+        _ => Err(format!("Invalid height '{char}' in height map")),
+    }
+}
+
+/// The character for a height in a height map, or `None` if the height is out of range.
+pub fn get_height_char(height: isize) -> Option<char> {
+    let char = match height {
+        0..=9 => b'0' + height as u8,
+        10..=MAX_TILE_HEIGHT => b'a' + (height - 10) as u8,
+        MIN_TILE_HEIGHT..=-1 => b'A' + (-height - 1) as u8,
+        _ => return None,
+    };
+
+    Some(char as char)
 }
 
 impl Level {
-    pub fn render_level(&self, commands: &mut Commands, asset_server: &Res<AssetServer>) {
-        if !self.metadata.help_text.is_empty() {
-            commands.spawn((
-                HelpTextMarker,
+    /// The movement map of a tile that moves along the movement with the given id.
+    pub fn get_movement_map(&self, id: &str) -> Result<MovementMap, String> {
+        let movement = self.get_movement(id)?;
+
+        Ok(MovementMap {
+            map: movement
+                .get_movement_map()
+                .map_err(|error| format!("Movement '{id}': {error}"))?,
+            platform: movement.linked.then(|| id.to_owned()),
+            switch: movement.triggered_by.clone(),
+            ..default()
+        })
+    }
+
+    /// The water of the level, which is only decoration if the level has none.
+    pub fn get_water(&self) -> Result<Water, String> {
+        let Some(water) = &self.water else {
+            return Ok(Water::default());
+        };
+
+        water.get_water().map_err(|error| format!("Water: {error}"))
+    }
+
+    pub fn get_movement(&self, id: &str) -> Result<&LevelMovement, String> {
+        self.movements
+            .get(id)
+            .ok_or_else(|| format!("Movement '{id}' is used, but not defined"))
+    }
+
+    /// The movement map of a listed tile, with its own phase and period if it has any.
+    /// The movement of a listed tile with the phase and period of the tile, or `None` if the tile
+    /// gives its movement map instead.
+    pub fn get_tile_movement(&self, tile: &LevelTile) -> Result<Option<LevelMovement>, String> {
+        let Some(id) = &tile.movement else {
+            return Ok(None);
+        };
+
+        let mut movement = self.get_movement(id)?.clone();
+        if movement.linked && (tile.phase.is_some() || tile.period.is_some()) {
+            return Err(format!(
+                "Movement '{id}' is linked, so its tiles cannot have their own phase or period"
+            ));
+        }
+
+        movement.phase = tile.phase.unwrap_or(movement.phase);
+        movement.period = tile.period.or(movement.period);
+
+        Ok(Some(movement))
+    }
+
+    pub fn get_tile_movement_map(&self, tile: &LevelTile) -> Result<MovementMap, String> {
+        let (Some(id), Some(movement)) = (&tile.movement, self.get_tile_movement(tile)?) else {
+            return Ok(MovementMap {
+                map: tile.movement_map.clone(),
+                ..default()
+            });
+        };
+
+        Ok(MovementMap {
+            map: movement
+                .get_movement_map()
+                .map_err(|error| format!("Movement '{id}': {error}"))?,
+            platform: movement.linked.then(|| id.clone()),
+            switch: movement.triggered_by.clone(),
+            ..default()
+        })
+    }
+
+    pub fn get_switch(&self, id: &str) -> Result<&LevelSwitch, String> {
+        self.switches
+            .get(id)
+            .ok_or_else(|| format!("Switch '{id}' is used, but not defined"))
+    }
+
+    /// The ids of the switches that are on when the level starts.
+    pub fn get_switches_on(&self) -> BTreeSet<String> {
+        self.switches
+            .iter()
+            .filter(|(_, switch)| switch.on)
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// The positions of the players and goals when the level starts, in the same places as
+    /// `render_level` spawns them.
+    pub fn get_carriable_positions(&self) -> HashSet<(isize, isize, isize)> {
+        let (level_width, level_height) = self.get_level_dimensions();
+        let (x_offset, z_offset) = (level_width as isize / 2, level_height as isize / 2);
+        let mut positions = HashSet::new();
+
+        for layer in &self.layers {
+            let heights = layer.height_map.trim().split('\n');
+
+            for (row_index, row) in heights.enumerate() {
+                for (col_index, char) in row.chars().enumerate() {
+                    let is_carriable = layer.modifiers.iter().any(|modifier_map| {
+                        modifier_map
+                            .trim()
+                            .split('\n')
+                            .nth(row_index)
+                            .and_then(|line| line.chars().nth(col_index))
+                            .is_some_and(|modifier| modifier == 'P' || modifier == 'G')
+                    });
+
+                    if is_carriable {
+                        positions.insert((
+                            col_index as isize - x_offset,
+                            parse_height(char).ok().flatten().unwrap_or(0),
+                            z_offset - row_index as isize,
+                        ));
+                    }
+                }
+            }
+
+            positions.extend(
+                layer
+                    .tiles
+                    .iter()
+                    .filter(|tile| tile.player || tile.goal)
+                    .map(|tile| (tile.q, tile.height, tile.r)),
+            );
+        }
+
+        positions
+    }
+
+    /// Checks everything that `render_level` relies on, so that a broken level file is reported
+    /// before any of it is spawned.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.layers.is_empty() {
+            return Err("A level needs at least one layer".to_owned());
+        }
+
+        for id in self.movements.keys() {
+            if let Some(switch) = &self.get_movement_map(id)?.switch {
+                self.get_switch(switch)?;
+            }
+        }
+
+        if let Some(switch) = &self.get_water()?.movement_map.switch {
+            self.get_switch(switch)?;
+        }
+
+        for layer in &self.layers {
+            let heights: Vec<Vec<char>> = layer
+                .height_map
+                .trim()
+                .split('\n')
+                .map(|line| line.chars().collect())
+                .collect();
+
+            for char in heights.iter().flatten() {
+                parse_height(*char)?;
+            }
+
+            let mut num_movement_maps = 0;
+            for modifier_map in &layer.modifiers {
+                let modifier_rows: Vec<Vec<char>> = modifier_map
+                    .trim()
+                    .split('\n')
+                    .map(|line| line.chars().collect())
+                    .collect();
+
+                for (row_index, row) in heights.iter().enumerate() {
+                    let Some(modifiers) = modifier_rows
+                        .get(row_index)
+                        .and_then(|modifiers| modifiers.get(..row.len()))
+                    else {
+                        return Err("A modifier map is smaller than its height map".to_owned());
+                    };
+
+                    for modifier in modifiers {
+                        match modifier {
+                            'M' => num_movement_maps += 1,
+                            'a'..='z' => {
+                                self.get_movement(&modifier.to_string())?;
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+
+            if num_movement_maps > layer.movement_maps.as_ref().map_or(0, Vec::len) {
+                return Err("A layer has fewer movement maps than 'M' modifiers".to_owned());
+            }
+
+            for tile in &layer.tiles {
+                self.validate_tile(tile)
+                    .map_err(|error| format!("Tile at ({}, {}): {error}", tile.q, tile.r))?;
+            }
+        }
+
+        get_teleporter_exits(self.layers.iter().flat_map(|layer| {
+            layer.tiles.iter().filter_map(|tile| {
+                Some((tile.teleporter.as_deref()?, (tile.q, tile.height, tile.r)))
+            })
+        }))?;
+
+        Ok(())
+    }
+
+    fn validate_tile(&self, tile: &LevelTile) -> Result<(), String> {
+        let movement_map = self.get_tile_movement_map(tile)?;
+        let bridge = tile.get_bridge()?;
+        let is_moving = !movement_map.map.is_empty();
+
+        for switch in [
+            &tile.switch,
+            &movement_map.switch,
+            &bridge.as_ref().map(|bridge| bridge.switch.clone()),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            self.get_switch(switch)?;
+        }
+
+        TileContents {
+            is_moving,
+            is_bridge: bridge.is_some(),
+            has_teleporter: tile.teleporter.is_some(),
+            has_conveyor: tile.conveyor.is_some(),
+            has_key: tile.key.is_some(),
+            has_gate: tile.gate.is_some(),
+        }
+        .validate()?;
+
+        tile.get_enemy()?;
+
+        Ok(())
+    }
+
+    /// Spawns the level, after checking it with `validate`. Nothing is spawned for a broken level.
+    pub fn render_level(
+        &self,
+        commands: &mut Commands,
+        asset_server: &Res<AssetServer>,
+    ) -> Result<(), String> {
+        self.validate()?;
+
+        commands.trigger(LevelStarted {
+            biome: self.metadata.biome,
+        });
+
+        // The help text and the HUD share the top of the screen, so that a long help text wraps
+        // before it reaches the HUD instead of running underneath it.
+        commands
+            .spawn((
                 LevelEntityMarker,
-                Text::new(self.metadata.help_text.as_str()),
-                TextFont {
-                    font: asset_server.load("fonts/main.ttf"),
-                    font_size: 48.0,
-                    ..default()
-                },
-                TextShadow::default(),
                 Node {
                     position_type: PositionType::Absolute,
-                    top: px(20),
-                    left: px(20),
+                    width: percent(100),
+                    padding: UiRect::all(px(20)),
+                    column_gap: px(20),
+                    justify_content: JustifyContent::SpaceBetween,
+                    align_items: AlignItems::Start,
                     ..default()
                 },
-            ));
-        }
+            ))
+            .with_children(|builder| {
+                if !self.metadata.help_text.is_empty() {
+                    builder.spawn((
+                        HelpTextMarker,
+                        LevelEntityMarker,
+                        KeyHint(self.metadata.help_text.clone()),
+                        Text::default(),
+                        TextFont {
+                            font: asset_server.load("fonts/main.ttf"),
+                            font_size: 48.0,
+                            ..default()
+                        },
+                        TextShadow::default(),
+                        Node {
+                            flex_shrink: 1.0,
+                            ..default()
+                        },
+                    ));
+                }
+
+                builder
+                    .spawn((
+                        HudMarker,
+                        LevelEntityMarker,
+                        Node {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::End,
+                            row_gap: px(8),
+                            flex_shrink: 0.0,
+                            margin: UiRect::left(Val::Auto),
+                            ..default()
+                        },
+                    ))
+                    .with_child((
+                        HudTextMarker,
+                        LevelEntityMarker,
+                        Text::default(),
+                        TextFont {
+                            font: asset_server.load("fonts/main.ttf"),
+                            font_size: 28.0,
+                            ..default()
+                        },
+                        TextShadow::default(),
+                    ));
+            });
+
+        commands.insert_resource(self.get_camera_framing());
+        commands.insert_resource(SwitchStates::from_level(self)?);
+        commands.insert_resource(self.get_water()?);
+        commands.insert_resource(KeyInventory::default());
+        commands.insert_resource(LevelStats {
+            max_moves: self.metadata.max_moves,
+            ..default()
+        });
 
         let (x_offset, z_offset) = self.get_level_xz_offsets();
 
@@ -114,23 +787,23 @@ impl Level {
 
             for (row_index, row) in heights.iter().enumerate() {
                 for (col_index, char) in row.iter().enumerate() {
+                    let height = parse_height(*char)?;
                     let tile_xyz = (
                         col_index as isize - x_offset,
-                        char.to_digit(10).unwrap_or(0) as isize,
+                        height.unwrap_or(0),
                         z_offset - row_index as isize,
                     );
 
-                    let mut movement_map: Vec<(isize, isize, isize)> = vec![];
+                    let mut movement_map = MovementMap::default();
                     let mut is_icy: bool = false;
 
                     for modifier_map in &modifier_maps {
-                        let modifier = modifier_map
-                            .iter()
-                            .nth(row_index)
-                            .expect("Modifier map has fewer rows than height map")
-                            .iter()
-                            .nth(col_index)
-                            .expect("Modifier map has fewer columns than height map");
+                        let Some(modifier) = modifier_map
+                            .get(row_index)
+                            .and_then(|row| row.get(col_index))
+                        else {
+                            continue;
+                        };
 
                         match modifier {
                             'P' => {
@@ -152,25 +825,27 @@ impl Level {
                                 );
                             }
                             'M' => {
-                                movement_map =
-                                    layer.movement_maps
-                                        .clone()
-                                        .expect("Movement map modifier found, but no movement maps specified")
-                                        .iter()
-                                        .nth(num_movement_maps_applied)
-                                        .expect("Too few movement maps specified")
-                                        .clone();
+                                movement_map.map = layer
+                                    .movement_maps
+                                    .iter()
+                                    .flatten()
+                                    .nth(num_movement_maps_applied)
+                                    .cloned()
+                                    .unwrap_or_default();
 
                                 num_movement_maps_applied += 1;
                             }
                             'I' => {
                                 is_icy = true;
                             }
+                            'a'..='z' => {
+                                movement_map = self.get_movement_map(&modifier.to_string())?;
+                            }
                             _ => continue,
                         };
                     }
 
-                    if *char != '.' {
+                    if height.is_some() {
                         self.get_tile_entity(
                             tile_xyz.0,
                             tile_xyz.1,
@@ -179,16 +854,68 @@ impl Level {
                             layer.pillars.unwrap_or(false),
                             is_icy,
                             movement_map,
+                            None,
+                            TileEdges::default(),
                             commands,
                             asset_server,
                         );
                     }
                 }
             }
+
+            for tile in &layer.tiles {
+                if tile.player {
+                    self.get_player_entity(tile.q, tile.height, tile.r, commands, asset_server);
+                }
+
+                if tile.goal {
+                    self.get_goal_entity(tile.q, tile.height, tile.r, commands, asset_server);
+                }
+
+                if let Some(id) = &tile.switch {
+                    let kind = self.get_switch(id)?.kind;
+                    self.get_switch_entity(tile.q, tile.height, tile.r, id, kind, commands);
+                }
+
+                let movement_map = self.get_tile_movement_map(tile)?;
+                let bridge = tile.get_bridge()?;
+
+                if let Some(id) = &tile.teleporter {
+                    self.get_teleporter_entity(tile.q, tile.height, tile.r, id, commands);
+                }
+
+                if let Some(direction) = tile.conveyor {
+                    self.get_conveyor_entity(tile.q, tile.height, tile.r, direction, commands);
+                }
+
+                if let Some(color) = tile.key {
+                    self.get_key_entity(tile.q, tile.height, tile.r, color, commands);
+                }
+
+                if let Some(behaviour) = tile.get_enemy()? {
+                    self.get_enemy_entity(tile.q, tile.height, tile.r, behaviour, commands);
+                }
+
+                self.get_tile_entity(
+                    tile.q,
+                    tile.height,
+                    tile.r,
+                    true,
+                    layer.pillars.unwrap_or(false),
+                    tile.icy,
+                    movement_map,
+                    bridge,
+                    tile.get_edges(),
+                    commands,
+                    asset_server,
+                );
+            }
         }
+
+        Ok(())
     }
 
-    fn get_level_xz_offsets(&self) -> (isize, isize) {
+    fn get_level_dimensions(&self) -> (usize, usize) {
         let level_width = self
             .layers
             .iter()
@@ -218,11 +945,75 @@ impl Level {
             .max()
             .unwrap();
 
+        (level_width, level_height)
+    }
+
+    fn get_level_xz_offsets(&self) -> (isize, isize) {
+        let (level_width, level_height) = self.get_level_dimensions();
+
         info!("Level width is {level_width} and level height is {level_height}");
 
         (level_width as isize / 2, level_height as isize / 2)
     }
 
+    /// Computes the circle (in world coordinates) that encloses the entire level,
+    /// so that the camera can frame it.
+    pub fn get_camera_framing(&self) -> CameraFraming {
+        let (level_width, level_height) = self.get_level_dimensions();
+        let (x_offset, z_offset) = (level_width as isize / 2, level_height as isize / 2);
+
+        // The rows of the height map are skewed in world space, so the level is a
+        // parallelogram. Its four corner tiles determine the bounds of the level.
+        let min_x = -x_offset as f32;
+        let max_x = (level_width as isize - 1 - x_offset) as f32;
+        let min_z = (z_offset - (level_height as isize - 1)) as f32;
+        let max_z = z_offset as f32;
+
+        let grid_corners = if level_width > 0 {
+            vec![
+                Vec3::new(min_x, 0.0, min_z),
+                Vec3::new(max_x, 0.0, min_z),
+                Vec3::new(min_x, 0.0, max_z),
+                Vec3::new(max_x, 0.0, max_z),
+            ]
+        } else {
+            vec![]
+        };
+
+        // Tiles outside of the height maps are included as corners as well.
+        let corners: Vec<Vec3> = grid_corners
+            .into_iter()
+            .chain(self.layers.iter().flat_map(|layer| {
+                layer
+                    .tiles
+                    .iter()
+                    .map(|tile| Vec3::new(tile.q as f32, 0.0, tile.r as f32))
+            }))
+            .map(|corner| tile_coordinates_to_transform_coordinates(&corner))
+            .collect();
+
+        if corners.is_empty() {
+            return CameraFraming {
+                center: Vec3::ZERO,
+                radius: 1.0,
+                mode: self.metadata.camera,
+            };
+        }
+
+        let center = corners.iter().sum::<Vec3>() / corners.len() as f32;
+        let radius = corners
+            .iter()
+            .map(|corner| corner.distance(center))
+            .fold(0.0, f32::max);
+
+        // Add the circumradius of a single tile, since the corners are tile centers.
+        CameraFraming {
+            center,
+            radius: radius + 1.0,
+            mode: self.metadata.camera,
+        }
+    }
+
     fn get_player_entity(
         &self,
         x: isize,
@@ -251,7 +1042,7 @@ impl Level {
         ));
     }
 
-    fn get_goal_entity(
+    pub fn get_goal_entity(
         &self,
         x: isize,
         y: isize,
@@ -292,7 +1083,9 @@ impl Level {
         is_on_top: bool,
         is_pillar: bool,
         is_icy: bool,
-        movement_map: Vec<(isize, isize, isize)>,
+        movement_map: MovementMap,
+        bridge: Option<Bridge>,
+        edges: TileEdges,
         commands: &mut Commands,
         asset_server: &Res<AssetServer>,
     ) {
@@ -301,11 +1094,10 @@ impl Level {
             asset_server.load(GltfAssetLabel::Scene(0).from_asset("tile_below.glb"));
         let icy_tile_asset = asset_server.load(GltfAssetLabel::Scene(0).from_asset("ice.glb"));
 
-        commands.spawn((
+        let mut tile = commands.spawn((
             Tile {
                 color: Color::hsla(90.0, 0.8, (0.4 + 0.1 * y as f32).clamp(0.05, 1.0), 1.0),
             },
-            ShouldRenderMovementMapPolylines,
             LevelEntityMarker,
             TileCoordinates {
                 x: x,
@@ -320,15 +1112,21 @@ impl Level {
                 tile_below_asset.clone()
             }),
             Transform::default(),
-            MovementMap {
-                map: movement_map.clone(),
-                index: 0,
-            },
+            movement_map.clone(),
         ));
 
+        if let Some(bridge) = &bridge {
+            tile.insert(bridge.clone());
+        }
+
+        if !edges.is_empty() {
+            tile.insert(edges);
+        }
+
         if is_icy {
-            commands.spawn((
+            let mut icy_tile = commands.spawn((
                 IcyTile,
+                LevelEntityMarker,
                 TileCoordinates {
                     x: x,
                     y: y,
@@ -339,6 +1137,11 @@ impl Level {
                 NotShadowCaster,
                 SceneRoot(icy_tile_asset.clone()),
             ));
+
+            // The ice disappears together with the tile.
+            if let Some(bridge) = &bridge {
+                icy_tile.insert(bridge.clone());
+            }
         }
 
         if y > 0 && is_pillar {
@@ -350,9 +1153,138 @@ impl Level {
                 is_pillar,
                 false,
                 movement_map,
+                bridge,
+                TileEdges::default(),
                 commands,
                 asset_server,
             );
         }
     }
+
+    /// The switch itself is invisible. Its mesh is added by `add_switch_meshes`.
+    fn get_switch_entity(
+        &self,
+        x: isize,
+        y: isize,
+        z: isize,
+        id: &str,
+        kind: SwitchKind,
+        commands: &mut Commands,
+    ) {
+        commands.spawn((
+            Switch {
+                id: id.to_owned(),
+                kind,
+            },
+            LevelEntityMarker,
+            TileCoordinates {
+                x,
+                y,
+                z,
+                ..default()
+            },
+            Transform::default(),
+            Visibility::default(),
+        ));
+    }
+
+    /// The pad itself is invisible. Its mesh is added by `add_teleporter_meshes`.
+    fn get_teleporter_entity(
+        &self,
+        x: isize,
+        y: isize,
+        z: isize,
+        id: &str,
+        commands: &mut Commands,
+    ) {
+        commands.spawn((
+            Teleporter { id: id.to_owned() },
+            LevelEntityMarker,
+            TileCoordinates {
+                x,
+                y,
+                z,
+                ..default()
+            },
+            Transform::default(),
+            Visibility::default(),
+        ));
+    }
+
+    /// The conveyor itself is invisible. Its arrow is added by `add_conveyor_arrows`.
+    fn get_conveyor_entity(
+        &self,
+        x: isize,
+        y: isize,
+        z: isize,
+        direction: MovementDirection,
+        commands: &mut Commands,
+    ) {
+        commands.spawn((
+            Conveyor { direction },
+            LevelEntityMarker,
+            TileCoordinates {
+                x,
+                y,
+                z,
+                ..default()
+            },
+            Transform::default(),
+            Visibility::default(),
+        ));
+    }
+
+    /// The enemy itself is invisible. Its mesh is added by `add_enemy_meshes`.
+    fn get_enemy_entity(
+        &self,
+        x: isize,
+        y: isize,
+        z: isize,
+        behaviour: EnemyBehaviour,
+        commands: &mut Commands,
+    ) {
+        commands.spawn((
+            Enemy {
+                behaviour,
+                route_index: 0,
+            },
+            Carriable,
+            HasGravity,
+            LevelEntityMarker,
+            TileCoordinates {
+                x,
+                y,
+                z,
+                movement_speed: 5.0,
+                visual_offset: Vec3::new(0.0, 0.25, 0.0),
+                ..default()
+            },
+            Transform::default(),
+            Visibility::default(),
+        ));
+    }
+
+    /// The key itself is invisible. Its mesh is added by `add_key_meshes`.
+    pub fn get_key_entity(
+        &self,
+        x: isize,
+        y: isize,
+        z: isize,
+        color: KeyColor,
+        commands: &mut Commands,
+    ) {
+        commands.spawn((
+            Key { color },
+            LevelEntityMarker,
+            TileCoordinates {
+                x,
+                y,
+                z,
+                visual_offset: Vec3::new(0.0, 0.4, 0.0),
+                ..default()
+            },
+            Transform::default(),
+            Visibility::default(),
+        ));
+    }
 }
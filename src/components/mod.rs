@@ -1,8 +1,18 @@
+pub mod audio;
 pub mod camera;
+pub mod conveyor;
+pub mod editor;
+pub mod enemy;
+pub mod ghost;
 pub mod goal;
+pub mod input;
+pub mod key;
 pub mod level;
 pub mod lighting;
 pub mod movement;
 pub mod player;
+pub mod replay;
+pub mod switch;
+pub mod teleporter;
 pub mod tile;
 pub mod tile_coordinates;
@@ -10,4 +10,6 @@ pub struct Movement {
     pub offset: Vec3,
     pub movement_speed: f32,
     pub animation_percentage: f32,
+    /// Whether the object has slid on after its first step, so that a slide is only heard once.
+    pub is_sliding: bool,
 }
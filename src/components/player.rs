@@ -1,10 +1,18 @@
 use bevy::ecs::{component::Component, event::Event};
 
+use crate::components::tile_coordinates::MovementDirection;
+
 #[derive(Component)]
 pub struct Player;
 
+/// Marks a player that ended up under water. It cannot move until the turn is undone or the level is restarted.
+#[derive(Component)]
+pub struct Drowned;
+
 #[derive(Event)]
-pub struct PlayerStartedMoving {}
+pub struct PlayerStartedMoving {
+    pub direction: MovementDirection,
+}
 
 #[derive(Event)]
 pub struct PlayerFinishedMoving {}
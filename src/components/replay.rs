@@ -0,0 +1,5 @@
+use bevy::ecs::component::Component;
+
+/// Marks a message about the state of a replay.
+#[derive(Component)]
+pub struct ReplayTextMarker;
@@ -0,0 +1,37 @@
+use bevy::ecs::component::Component;
+use serde::{Deserialize, Serialize};
+
+use crate::resources::switches::SwitchStates;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum SwitchKind {
+    /// On for as long as something is standing on it.
+    Plate,
+    /// Turns on or off each time something arrives on it.
+    Toggle,
+}
+
+/// A pressure plate or toggle switch lying on top of a tile. All switches with the same id work together.
+#[derive(Component, Clone, Debug)]
+pub struct Switch {
+    pub id: String,
+    pub kind: SwitchKind,
+}
+
+/// The mesh of a switch, a child of the switch, which is colored by whether the switch is on.
+#[derive(Component)]
+pub struct SwitchMesh;
+
+/// A tile that is only there while its switch is on, or only while it is off.
+#[derive(Component, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Bridge {
+    pub switch: String,
+    pub is_shown_when_on: bool,
+}
+
+impl Bridge {
+    pub fn is_shown(&self, switch_states: &SwitchStates) -> bool {
+        switch_states.is_on(&self.switch) == self.is_shown_when_on
+    }
+}
@@ -0,0 +1,45 @@
+use std::collections::HashMap;
+
+use bevy::ecs::{component::Component, entity::Entity, event::Event};
+
+type Position = (isize, isize, isize);
+
+/// A teleporter pad lying on top of a tile. It is paired with the one other pad with the same id.
+#[derive(Component, Clone, Debug)]
+pub struct Teleporter {
+    pub id: String,
+}
+
+/// Triggered when an object has been transported from one pad to the other.
+#[derive(Event)]
+pub struct ObjectTeleported {
+    pub entity: Entity,
+    pub from: Position,
+    pub to: Position,
+}
+
+/// For the position of each pad, the position of the pad that it is paired with.
+/// Returns an error if an id is not used by exactly two pads.
+pub fn get_teleporter_exits<'a>(
+    pads: impl Iterator<Item = (&'a str, Position)>,
+) -> Result<HashMap<Position, Position>, String> {
+    let mut pads_by_id: HashMap<&str, Vec<Position>> = HashMap::new();
+    for (id, position) in pads {
+        pads_by_id.entry(id).or_default().push(position);
+    }
+
+    let mut exits = HashMap::new();
+    for (id, positions) in pads_by_id {
+        let [first, second] = positions[..] else {
+            return Err(format!(
+                "Teleporter '{id}' needs exactly two pads, found {}",
+                positions.len()
+            ));
+        };
+
+        exits.insert(first, second);
+        exits.insert(second, first);
+    }
+
+    Ok(exits)
+}
@@ -1,5 +1,12 @@
+use std::collections::HashMap;
+
 use bevy::{color::Color, ecs::component::Component};
 
+use crate::{
+    components::{key::KeyColor, tile_coordinates::MovementDirection},
+    resources::switches::SwitchStates,
+};
+
 #[derive(Component)]
 pub struct Tile {
     pub color: Color,
@@ -8,17 +15,107 @@ pub struct Tile {
 #[derive(Component)]
 pub struct IcyTile;
 
+/// A tile that is not there at the moment, because its switch is in the wrong state.
+#[derive(Component)]
+pub struct HiddenTile;
+
 #[derive(Component)]
 pub struct HasGravity;
 
+/// Marks an object with gravity that finished a step during the current phase of the turn, or any
+/// object with gravity at the end of the environment phase. It falls at the end of the phase if
+/// nothing holds it up, in `apply_gravity`.
 #[derive(Component)]
-pub struct Carriable;
+pub struct Settling;
 
 #[derive(Component)]
+pub struct Carriable;
+
+#[derive(Component, Clone, Default)]
 pub struct MovementMap {
     pub map: Vec<(isize, isize, isize)>,
     pub index: usize,
+    /// The id of the platform that this tile is part of. All tiles of a platform move
+    /// together, and none of them move if one of them is blocked.
+    pub platform: Option<String>,
+    /// The id of the switch that must be on for this tile to move. Otherwise it waits where it is.
+    pub switch: Option<String>,
+}
+
+impl MovementMap {
+    /// The offset this tile will move by when the level effects of the next turn are applied.
+    pub fn next_offset(&self) -> Option<(isize, isize, isize)> {
+        if self.map.is_empty() {
+            return None;
+        }
+
+        Some(self.map[self.index % self.map.len()])
+    }
+
+    /// Whether the tile follows its movement map this turn, rather than waiting for its switch.
+    pub fn is_running(&self, switch_states: &SwitchStates) -> bool {
+        self.switch
+            .as_ref()
+            .is_none_or(|switch| switch_states.is_on(switch))
+    }
+}
+
+/// Walls on the edges of a tile, the direction of a one-way tile, and a locked gate. These only
+/// stop objects that move across an edge at the height of the tile, so objects that are higher up
+/// pass over them.
+#[derive(Component, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TileEdges {
+    /// The sides of the hex that have a wall.
+    pub walls: Vec<MovementDirection>,
+    /// If given, objects can only enter and leave the tile by moving in this direction.
+    pub one_way: Option<MovementDirection>,
+    /// A locked gate that can only be entered by a player holding a key of its color.
+    /// The key is used up, and the gate stays open afterwards.
+    pub gate: Option<KeyColor>,
+}
+
+impl TileEdges {
+    pub fn is_empty(&self) -> bool {
+        self.walls.is_empty() && self.one_way.is_none() && self.gate.is_none()
+    }
+
+    /// Whether an object standing on the tile can leave it in the direction.
+    pub fn can_exit(&self, direction: MovementDirection) -> bool {
+        !self.walls.contains(&direction) && self.one_way.is_none_or(|one_way| one_way == direction)
+    }
+
+    /// Whether an object moving in the direction, that holds the given keys, can enter the tile.
+    pub fn can_enter(&self, direction: MovementDirection, keys: &[KeyColor]) -> bool {
+        !self.walls.contains(&direction.rotate_y(3))
+            && self.one_way.is_none_or(|one_way| one_way == direction)
+            && self.gate.is_none_or(|gate| keys.contains(&gate))
+    }
+}
+
+/// Whether an object at the position is stopped by a wall, one-way tile or locked gate when it
+/// moves in the direction. Only players hold keys; anything else is stopped by every locked gate.
+/// The tiles with edges are found by their position.
+pub fn is_edge_blocked(
+    edges: &HashMap<(isize, isize, isize), TileEdges>,
+    from: (isize, isize, isize),
+    direction: MovementDirection,
+    keys: &[KeyColor],
+) -> bool {
+    let offset = direction.get_tile_coordinate_offset();
+    let to = (
+        from.0 + offset.x as isize,
+        from.1,
+        from.2 + offset.z as isize,
+    );
+
+    edges
+        .get(&from)
+        .is_some_and(|edges| !edges.can_exit(direction))
+        || edges
+            .get(&to)
+            .is_some_and(|edges| !edges.can_enter(direction, keys))
 }
 
+/// Marks a polyline drawn by the movement map overlay.
 #[derive(Component)]
-pub struct ShouldRenderMovementMapPolylines;
+pub struct MovementMapPolyline;
@@ -1,4 +1,5 @@
 use bevy::{ecs::component::Component, math::Vec3};
+use serde::{Deserialize, Serialize};
 
 #[derive(Component, Debug)]
 pub struct TileCoordinates {
@@ -27,17 +28,33 @@ impl Default for TileCoordinates {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+/// In level files, directions are written as `NW`, `NE`, `E`, `SE`, `SW` and `W`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum MovementDirection {
+    #[serde(rename = "NW")]
     NorthWest,
+    #[serde(rename = "NE")]
     NorthEast,
+    #[serde(rename = "E")]
     East,
+    #[serde(rename = "SE")]
     SouthEast,
+    #[serde(rename = "SW")]
     SouthWest,
+    #[serde(rename = "W")]
     West,
 }
 
 impl MovementDirection {
+    pub const ALL: [MovementDirection; 6] = [
+        Self::East,
+        Self::SouthEast,
+        Self::SouthWest,
+        Self::West,
+        Self::NorthWest,
+        Self::NorthEast,
+    ];
+
     pub fn get_tile_coordinate_offset(&self) -> Vec3 {
         match self {
             MovementDirection::East => Vec3::new(1., 0., 0.),
@@ -49,6 +66,14 @@ impl MovementDirection {
         }
     }
 
+    /// The direction of a horizontal offset to an adjacent hex. Vertical offsets have no direction.
+    pub fn from_offset(offset: Vec3) -> Option<Self> {
+        Self::ALL.into_iter().find(|direction| {
+            let direction_offset = direction.get_tile_coordinate_offset();
+            direction_offset.x == offset.x && direction_offset.z == offset.z
+        })
+    }
+
     pub fn rotate_y(&self, num_rotations: isize) -> Self {
         let sorted_directions = [
             Self::East,
@@ -60,16 +85,21 @@ impl MovementDirection {
         ];
 
         match sorted_directions.iter().position(|item| self == item) {
-            None => self,
+            None => *self,
             Some(value) => {
                 let new_index = (value as isize + num_rotations).rem_euclid(6);
-                &sorted_directions[new_index as usize]
+                sorted_directions[new_index as usize]
             }
         }
-        .clone()
     }
 }
 
+/// The number of steps between two hexes, given by their (x, z) tile coordinates.
+pub fn hex_distance(a: (isize, isize), b: (isize, isize)) -> isize {
+    let (dx, dz) = (a.0 - b.0, a.1 - b.1);
+    (dx.abs() + dz.abs() + (dx + dz).abs()) / 2
+}
+
 pub fn tile_coordinates_to_transform_coordinates(tile_coordinates: &Vec3) -> Vec3 {
     Vec3::new(
         3f32.sqrt() * (tile_coordinates.x + tile_coordinates.z / 2.),
@@ -77,3 +107,170 @@ pub fn tile_coordinates_to_transform_coordinates(tile_coordinates: &Vec3) -> Vec
         -1.5 * tile_coordinates.z,
     )
 }
+
+/// The (x, z) tile coordinates of the hex column that contains the given point.
+pub fn transform_coordinates_to_nearest_column(transform_coordinates: &Vec3) -> (isize, isize) {
+    let z = -transform_coordinates.z / 1.5;
+    let x = transform_coordinates.x / 3f32.sqrt() - z / 2.;
+
+    // Round in cube coordinates, where the third coordinate is -x - z,
+    // and fix up the coordinate with the largest rounding error.
+    let (rounded_x, rounded_z, rounded_s) = (x.round(), z.round(), (-x - z).round());
+    let (error_x, error_z, error_s) = (
+        (rounded_x - x).abs(),
+        (rounded_z - z).abs(),
+        (rounded_s + x + z).abs(),
+    );
+
+    if error_x > error_z && error_x > error_s {
+        ((-rounded_z - rounded_s) as isize, rounded_z as isize)
+    } else if error_z > error_s {
+        (rounded_x as isize, (-rounded_x - rounded_s) as isize)
+    } else {
+        (rounded_x as isize, rounded_z as isize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotate_y_cycles_through_all_directions() {
+        for (index, direction) in MovementDirection::ALL.iter().enumerate() {
+            assert_eq!(
+                direction.rotate_y(1),
+                MovementDirection::ALL[(index + 1) % 6]
+            );
+            assert_eq!(
+                direction.rotate_y(-1),
+                MovementDirection::ALL[(index + 5) % 6]
+            );
+            assert_eq!(
+                direction.rotate_y(3),
+                MovementDirection::ALL[(index + 3) % 6]
+            );
+            assert_eq!(direction.rotate_y(6), *direction);
+            assert_eq!(direction.rotate_y(-13), direction.rotate_y(-1));
+        }
+
+        assert_eq!(
+            MovementDirection::East.rotate_y(1),
+            MovementDirection::SouthEast
+        );
+        assert_eq!(
+            MovementDirection::East.rotate_y(-1),
+            MovementDirection::NorthEast
+        );
+    }
+
+    #[test]
+    fn opposite_directions_have_opposite_offsets() {
+        for direction in MovementDirection::ALL {
+            assert_eq!(
+                direction.get_tile_coordinate_offset()
+                    + direction.rotate_y(3).get_tile_coordinate_offset(),
+                Vec3::ZERO
+            );
+        }
+    }
+
+    #[test]
+    fn directions_are_found_from_their_offsets() {
+        for direction in MovementDirection::ALL {
+            assert_eq!(
+                MovementDirection::from_offset(direction.get_tile_coordinate_offset()),
+                Some(direction)
+            );
+        }
+
+        assert_eq!(MovementDirection::from_offset(Vec3::new(0., -1., 0.)), None);
+    }
+
+    #[test]
+    fn adjacent_hexes_are_one_step_apart() {
+        for direction in MovementDirection::ALL {
+            let offset = direction.get_tile_coordinate_offset();
+            let (x, z) = (offset.x as isize, offset.z as isize);
+
+            assert_eq!(hex_distance((0, 0), (x, z)), 1);
+            assert_eq!(hex_distance((3, -2), (3 + 2 * x, -2 + 2 * z)), 2);
+        }
+
+        assert_eq!(hex_distance((0, 0), (2, 1)), 3);
+    }
+
+    #[test]
+    fn offsets_point_to_adjacent_hexes() {
+        let sqrt3 = 3f32.sqrt();
+
+        for direction in MovementDirection::ALL {
+            let offset =
+                tile_coordinates_to_transform_coordinates(&direction.get_tile_coordinate_offset());
+
+            assert_eq!(offset.y, 0.);
+            assert!((offset.length() - sqrt3).abs() < 1e-5);
+        }
+
+        // Consecutive directions should be 60 degrees apart, so that rotating the camera
+        // by a sixth of a turn maps the controls onto the next direction.
+        for direction in MovementDirection::ALL {
+            let offset =
+                tile_coordinates_to_transform_coordinates(&direction.get_tile_coordinate_offset());
+            let next_offset = tile_coordinates_to_transform_coordinates(
+                &direction.rotate_y(1).get_tile_coordinate_offset(),
+            );
+
+            assert!(
+                (offset.dot(next_offset) - 1.5).abs() < 1e-5,
+                "{direction:?} and the direction after it are not adjacent"
+            );
+        }
+    }
+
+    #[test]
+    fn tile_coordinates_to_transform_coordinates_uses_hex_spacing() {
+        let sqrt3 = 3f32.sqrt();
+
+        assert_eq!(
+            tile_coordinates_to_transform_coordinates(&Vec3::ZERO),
+            Vec3::ZERO
+        );
+        assert_eq!(
+            tile_coordinates_to_transform_coordinates(&Vec3::new(1., 0., 0.)),
+            Vec3::new(sqrt3, 0., 0.)
+        );
+        assert_eq!(
+            tile_coordinates_to_transform_coordinates(&Vec3::new(0., 1., 0.)),
+            Vec3::new(0., 0.8, 0.)
+        );
+        assert_eq!(
+            tile_coordinates_to_transform_coordinates(&Vec3::new(0., 0., 2.)),
+            Vec3::new(sqrt3, 0., -3.)
+        );
+    }
+
+    #[test]
+    fn nearest_column_is_the_inverse_of_the_transform() {
+        for x in -5..=5 {
+            for z in -5..=5 {
+                let center =
+                    tile_coordinates_to_transform_coordinates(&Vec3::new(x as f32, 3., z as f32));
+
+                assert_eq!(transform_coordinates_to_nearest_column(&center), (x, z));
+
+                // Any point within the inner circle of the hex belongs to the same column.
+                for direction in MovementDirection::ALL {
+                    let offset = tile_coordinates_to_transform_coordinates(
+                        &direction.get_tile_coordinate_offset(),
+                    );
+
+                    assert_eq!(
+                        transform_coordinates_to_nearest_column(&(center + offset * 0.45)),
+                        (x, z)
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,258 @@
+//! Generates new levels from a seed. Only levels that can be solved are kept,
+//! which is checked by searching the turn rules in `simulation`.
+
+use crate::{
+    components::{
+        level::{LevelMetadata, LevelMetadataBiome, LevelMetadataCamera},
+        tile_coordinates::MovementDirection,
+    },
+    resources::{
+        editor::{EditableCell, EditableLayer, EditableLevel},
+        settings::GeneratorSettings,
+    },
+    simulation::Simulation,
+};
+
+/// The part of the grid that is covered by the island.
+const ISLAND_FILL: f32 = 0.55;
+
+/// The most states that are searched when solving a generated level.
+const MAX_SEARCH_STATES: usize = 200_000;
+
+pub struct GeneratedLevel {
+    pub level: EditableLevel,
+    /// The shortest solution of the level.
+    pub solution: Vec<MovementDirection>,
+}
+
+/// A small random number generator (SplitMix64), so that a seed always gives the same level
+/// on every platform.
+struct Random(u64);
+
+impl Random {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut value = self.0;
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d049bb133111eb);
+        value ^ (value >> 31)
+    }
+
+    /// A number from 0 up to, but not including, `bound`.
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound.max(1) as u64) as usize
+    }
+
+    fn chance(&mut self, probability: f32) -> bool {
+        ((self.next_u64() >> 40) as f32 / (1u64 << 24) as f32) < probability
+    }
+}
+
+/// The seed of the first puzzle of the given day, counted in days since the Unix epoch.
+/// The next puzzles of the day use the following seeds.
+pub fn get_daily_seed(day: u64) -> u64 {
+    day << 32
+}
+
+/// The (row, column) offset in the level grid of a step in the given direction.
+fn get_grid_offset(direction: MovementDirection) -> (isize, isize) {
+    let offset = direction.get_tile_coordinate_offset();
+    (-offset.z as isize, offset.x as isize)
+}
+
+pub fn generate_level(seed: u64, settings: &GeneratorSettings) -> Result<GeneratedLevel, String> {
+    let mut random = Random(seed);
+
+    for _ in 0..settings.max_attempts {
+        let level = generate_candidate(&mut random, settings, seed);
+
+        let Some(solution) = Simulation::from_editable_level(&level)?.solve(MAX_SEARCH_STATES)
+        else {
+            continue;
+        };
+
+        if solution.len() >= settings.min_solution_length {
+            return Ok(GeneratedLevel { level, solution });
+        }
+    }
+
+    Err(format!(
+        "Could not generate a solvable level from seed {seed} in {} attempts",
+        settings.max_attempts
+    ))
+}
+
+fn generate_candidate(
+    random: &mut Random,
+    settings: &GeneratorSettings,
+    seed: u64,
+) -> EditableLevel {
+    let (width, height) = (settings.width.max(2), settings.height.max(2));
+    let height_levels = settings.height_levels.max(1);
+    let mut ground = vec![vec![EditableCell::default(); width]; height];
+    let mut platforms = vec![vec![EditableCell::default(); width]; height];
+
+    // Grow an island from the middle, where neighbouring tiles differ by at most one in height.
+    let start = (height / 2, width / 2);
+    ground[start.0][start.1].height = Some(random.below(height_levels) as isize);
+
+    let mut island = vec![start];
+    let island_size = ((width * height) as f32 * ISLAND_FILL) as usize;
+
+    while island.len() < island_size {
+        let (row, col) = island[random.below(island.len())];
+        let (row_offset, col_offset) =
+            get_grid_offset(MovementDirection::ALL[random.below(MovementDirection::ALL.len())]);
+        let (new_row, new_col) = (row as isize + row_offset, col as isize + col_offset);
+
+        if new_row < 0 || new_col < 0 || new_row >= height as isize || new_col >= width as isize {
+            continue;
+        }
+
+        let (new_row, new_col) = (new_row as usize, new_col as usize);
+        if ground[new_row][new_col].height.is_some() {
+            continue;
+        }
+
+        let parent_height = ground[row][col].height.unwrap_or(0);
+        let new_height =
+            (parent_height + [-1, 0, 0, 1][random.below(4)]).clamp(0, height_levels as isize - 1);
+
+        ground[new_row][new_col].height = Some(new_height);
+        island.push((new_row, new_col));
+    }
+
+    for (row, col) in &island {
+        if random.chance(settings.ice_density) {
+            ground[*row][*col].modifiers.push('I');
+        }
+    }
+
+    // The player and the goal are never placed on ice.
+    let mut free_tiles: Vec<(usize, usize)> = island
+        .iter()
+        .copied()
+        .filter(|(row, col)| ground[*row][*col].modifiers.is_empty())
+        .collect();
+
+    if !free_tiles.is_empty() {
+        let (row, col) = free_tiles.swap_remove(random.below(free_tiles.len()));
+        ground[row][col].modifiers.push('P');
+    }
+
+    // Moving platforms go back and forth between two hexes next to the island,
+    // or move up and down like an elevator.
+    for _ in 0..settings.moving_platforms {
+        for _ in 0..20 {
+            let (row, col) = island[random.below(island.len())];
+            let direction = MovementDirection::ALL[random.below(MovementDirection::ALL.len())];
+            let (row_offset, col_offset) = get_grid_offset(direction);
+            let is_elevator = height_levels > 1 && random.chance(0.3);
+            let amplitude = if is_elevator {
+                1
+            } else {
+                1 + random.below(2) as isize
+            };
+
+            let start = (row as isize + row_offset, col as isize + col_offset);
+            let end = if is_elevator {
+                start
+            } else {
+                (
+                    start.0 + row_offset * amplitude,
+                    start.1 + col_offset * amplitude,
+                )
+            };
+
+            let is_free = |(row, col): (isize, isize)| {
+                row >= 0
+                    && col >= 0
+                    && row < height as isize
+                    && col < width as isize
+                    && ground[row as usize][col as usize].height.is_none()
+                    && platforms[row as usize][col as usize].height.is_none()
+                    && platforms[row as usize][col as usize].modifiers.is_empty()
+            };
+
+            if !is_free(start) || !is_free(end) {
+                continue;
+            }
+
+            let platform_height = random.below(height_levels - is_elevator as usize) as isize;
+            let offset = direction.get_tile_coordinate_offset();
+            let step = if is_elevator {
+                (0, amplitude, 0)
+            } else {
+                (
+                    offset.x as isize * amplitude,
+                    0,
+                    offset.z as isize * amplitude,
+                )
+            };
+
+            platforms[start.0 as usize][start.1 as usize] = EditableCell {
+                height: Some(platform_height),
+                modifiers: vec!['M'],
+                movement_map: vec![step, (-step.0, -step.1, -step.2)],
+                ..Default::default()
+            };
+
+            // Keep the other end of the path free for this platform.
+            platforms[end.0 as usize][end.1 as usize]
+                .modifiers
+                .push('.');
+            break;
+        }
+    }
+
+    for cell in platforms.iter_mut().flatten() {
+        cell.modifiers.retain(|modifier| *modifier != '.');
+    }
+
+    let mut level = EditableLevel {
+        metadata: LevelMetadata {
+            name: format!("Puzzle {seed}"),
+            biome: LevelMetadataBiome::DAYLIGHT,
+            help_text: String::new(),
+            camera: LevelMetadataCamera::Fixed,
+            max_moves: None,
+        },
+        switches: Default::default(),
+        water: Default::default(),
+        layers: vec![
+            EditableLayer {
+                pillars: true,
+                cells: ground,
+            },
+            EditableLayer {
+                pillars: false,
+                cells: platforms,
+            },
+        ],
+    };
+
+    place_goal(&mut level, &free_tiles);
+    level
+}
+
+/// Places the goal on the tile that takes the most moves to reach,
+/// so that levels are not won by walking straight to the goal.
+fn place_goal(level: &mut EditableLevel, free_tiles: &[(usize, usize)]) {
+    let Ok(simulation) = Simulation::from_editable_level(level) else {
+        return;
+    };
+    let move_counts = simulation.get_move_counts(MAX_SEARCH_STATES);
+
+    let furthest_tile = free_tiles
+        .iter()
+        .filter_map(|(row, col)| {
+            let (x, z) = level.get_tile_coordinates(*row, *col);
+            let y = level.layers[0].cells[*row][*col].height?;
+            Some((move_counts.get(&(x, y, z))?, (*row, *col)))
+        })
+        .max();
+
+    if let Some((_, (row, col))) = furthest_tile {
+        level.layers[0].cells[row][col].modifiers.push('G');
+    }
+}
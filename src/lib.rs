@@ -0,0 +1,9 @@
+pub mod components;
+pub mod generator;
+pub mod resources;
+pub mod rules;
+pub mod simulation;
+pub mod systems;
+
+#[cfg(test)]
+mod tests;
@@ -1,77 +1,161 @@
-use bevy::{prelude::*, window::WindowResolution};
+use std::env;
+
+use bevy::{
+    audio::{AddAudioSource, AudioPlugin, Volume},
+    input::InputSystems,
+    prelude::*,
+    window::WindowResolution,
+};
 use bevy_hanabi::HanabiPlugin;
 use bevy_polyline::PolylinePlugin;
 use bevy_water::{WaterPlugin, WaterSettings};
 
-use crate::{
-    resources::{levels::LevelResource, settings::Settings},
+use ahex::{
+    resources::{
+        audio::{Sounds, SynthesizedSound},
+        camera::CameraFraming,
+        daily_puzzle::DailyPuzzleGeneration,
+        input::{InputBuffer, InputRebinding},
+        levels::LevelResource,
+        overlays::MovementMapOverlay,
+        replay::ReplayPlayback,
+        settings::Settings,
+        water::WATER_SURFACE_HEIGHT,
+    },
     systems::{
+        audio::{play_ambient_loop, play_sound_effect},
         camera::move_camera,
+        conveyors::{add_conveyor_arrows, animate_conveyor_arrows},
+        daily_puzzle::{
+            finish_daily_puzzle, go_to_next_daily_puzzle, is_playing_daily_puzzle,
+            toggle_daily_puzzle,
+        },
+        edges::add_tile_edges,
+        editor::{
+            edit_level, is_editing, on_editor_clicked, render_edited_level, toggle_editor,
+            update_editor_text,
+        },
+        enemies::{add_enemy_meshes, catch_players},
+        ghost::update_prediction_ghosts,
         goal::{add_goal_bloom, rotate_goal, vary_goal_intensity},
+        hud::{count_elapsed_time, show_lose_text, update_hud_text},
+        input::{buffer_input_actions, read_input_actions, rebind_inputs, show_key_hints},
+        keys::{add_key_meshes, open_gates, rotate_keys, update_gate_meshes, update_key_hud},
         level::{
-            build_level, go_to_next_level, restart_level, show_level_complete,
+            build_level, go_to_next_level, restart_level, show_level_complete, undo_turn,
             update_level_complete_color,
         },
         lighting::{create_the_sun, update_the_sun},
-        player::{add_player_bloom, collect_goals, player_controls},
-        setup::{enable_water_shadows, setup, setup_effects},
+        pathfinding::on_tile_clicked,
+        player::{add_player_bloom, player_controls},
+        replay::{export_replay, play_replay, save_completed_replay, start_replay},
+        setup::{
+            enable_water_shadows, setup, setup_conveyor_arrows, setup_effects, setup_enemies,
+            setup_keys, setup_prediction_ghosts, setup_switches, setup_tile_edges,
+        },
+        switches::{add_switch_meshes, colorize_switches},
+        teleporters::{add_teleporter_meshes, on_object_teleported},
         tiles::{
-            apply_movement, colorize_tiles, draw_moving_tiles_polylines, on_player_started_moving,
-            on_players_finished_moving, patch_icy_tile_texture,
-            set_transform_based_on_tile_coordinates,
+            apply_movement, colorize_tiles, draw_moving_tiles_polylines, patch_icy_tile_texture,
+            set_transform_based_on_tile_coordinates, toggle_movement_map_overlay,
         },
+        turn::TurnPlugin,
+        water::{drown_players, update_water_height},
     },
 };
 
-use crate::resources::effects::GlobalEffects;
-
-mod components;
-mod resources;
-mod systems;
+use ahex::resources::effects::GlobalEffects;
 
 fn main() {
-    let settings: Settings =
+    let mut settings: Settings =
         toml::from_str(include_str!("settings.toml")).expect("Could not parse settings");
 
+    // The settings are built into the game, so audio can be switched on or off when starting it.
+    for argument in env::args().skip(1) {
+        match argument.as_str() {
+            "--audio" => settings.audio.enabled = true,
+            "--no-audio" => settings.audio.enabled = false,
+            _ => warn!("Unknown option {argument}"),
+        }
+    }
+
+    if let Err(error) = settings.input.load_saved_bindings() {
+        warn!("{error}");
+    }
+
     info!("{:#?}", settings);
 
-    App::new()
-        .insert_resource(GlobalEffects::default())
+    // Without audio, the audio device is not opened at all, so that the game can run headless.
+    let mut default_plugins = DefaultPlugins.set(WindowPlugin {
+        primary_window: Some(Window {
+            title: "Ahex".into(),
+            resizable: false,
+            resolution: WindowResolution::new(settings.display.width, settings.display.height),
+            canvas: Some("#bevy".to_owned()),
+            ..default()
+        }),
+        ..default()
+    });
+    if !settings.audio.enabled {
+        default_plugins = default_plugins.disable::<AudioPlugin>();
+    }
+
+    let mut app = App::new();
+    app.insert_resource(GlobalEffects::default())
+        .insert_resource(CameraFraming::default())
+        .insert_resource(InputRebinding::default())
+        .insert_resource(InputBuffer::default())
+        .insert_resource(ReplayPlayback::default())
+        .insert_resource(DailyPuzzleGeneration::default())
+        .insert_resource(settings.input.clone())
         .insert_resource(settings.clone())
         .insert_resource(ClearColor(Color::hsl(200.0, 0.0, 0.3)))
         .insert_resource(LevelResource {
             current_level_number: settings.initial_level_number,
             ..default()
         })
+        .insert_resource(MovementMapOverlay {
+            enabled: settings.display.movement_map_overlay.enabled_by_default,
+        })
         .insert_resource(WaterSettings {
-            height: 0.3,
+            height: WATER_SURFACE_HEIGHT,
             amplitude: 1.5,
             alpha_mode: AlphaMode::Add,
             water_quality: bevy_water::WaterQuality::Basic, // High or better for shadows
             deep_color: Color::hsla(180.0, 1.0, 0.6, 1.0),
             ..default()
         })
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                title: "Ahex".into(),
-                resizable: false,
-                resolution: WindowResolution::new(settings.display.width, settings.display.height),
-                canvas: Some("#bevy".to_owned()),
-                ..default()
-            }),
-            ..default()
-        }))
+        .add_plugins(default_plugins)
         .add_plugins(WaterPlugin)
         .add_plugins(HanabiPlugin)
         .add_plugins(PolylinePlugin)
+        .add_plugins(MeshPickingPlugin)
+        .add_plugins(TurnPlugin)
         .add_systems(
             Startup,
             (
                 setup,
                 setup_effects,
+                setup_prediction_ghosts,
+                setup_conveyor_arrows,
+                setup_tile_edges,
+                setup_keys,
+                setup_enemies,
+                setup_switches,
                 (build_level, create_the_sun).after(setup),
             ),
         )
+        .add_systems(
+            PreUpdate,
+            (
+                read_input_actions,
+                buffer_input_actions,
+                play_replay,
+                rebind_inputs,
+            )
+                .chain()
+                .after(InputSystems),
+        )
         .add_systems(
             PostStartup,
             enable_water_shadows.run_if(move || settings.display.water.shadows_enabled),
@@ -86,19 +170,78 @@ fn main() {
                 rotate_goal,
                 colorize_tiles,
                 patch_icy_tile_texture,
-                draw_moving_tiles_polylines.run_if(|| false),
-                collect_goals,
-                apply_movement,
-                restart_level,
-                go_to_next_level,
-                show_level_complete,
+                toggle_movement_map_overlay,
+                draw_moving_tiles_polylines
+                    .after(toggle_movement_map_overlay)
+                    .after(apply_movement),
+                update_prediction_ghosts.after(apply_movement),
+                restart_level.run_if(not(is_editing)),
+                undo_turn.run_if(not(is_editing)),
+                go_to_next_level
+                    .run_if(not(is_editing))
+                    .run_if(not(is_playing_daily_puzzle)),
+                go_to_next_daily_puzzle.run_if(is_playing_daily_puzzle),
+                show_level_complete.run_if(not(is_editing)),
                 update_level_complete_color,
+                show_key_hints,
                 update_the_sun,
-                player_controls.after(apply_movement),
                 set_transform_based_on_tile_coordinates,
             ),
         )
-        .add_observer(on_players_finished_moving)
-        .add_observer(on_player_started_moving)
-        .run();
+        .add_systems(
+            Update,
+            (
+                add_switch_meshes,
+                colorize_switches,
+                add_teleporter_meshes,
+                add_conveyor_arrows,
+                animate_conveyor_arrows,
+                add_tile_edges,
+                add_key_meshes,
+                rotate_keys,
+                update_gate_meshes.after(open_gates).after(undo_turn),
+                update_key_hud.after(open_gates).after(undo_turn),
+                count_elapsed_time,
+                update_hud_text.after(count_elapsed_time).after(undo_turn),
+                show_lose_text
+                    .after(player_controls)
+                    .after(drown_players)
+                    .after(catch_players)
+                    .after(undo_turn),
+                update_water_height,
+                add_enemy_meshes,
+            ),
+        )
+        .add_systems(
+            Update,
+            (start_replay, save_completed_replay, export_replay)
+                .run_if(not(is_editing))
+                .run_if(not(is_playing_daily_puzzle)),
+        )
+        .add_systems(
+            Update,
+            (
+                toggle_editor,
+                toggle_daily_puzzle.run_if(not(is_editing)),
+                finish_daily_puzzle.run_if(not(is_editing)),
+                edit_level.after(toggle_editor).run_if(is_editing),
+                render_edited_level.after(edit_level),
+                update_editor_text.after(edit_level),
+            ),
+        )
+        .add_observer(on_tile_clicked)
+        .add_observer(on_object_teleported)
+        .add_observer(on_editor_clicked);
+
+    if settings.audio.enabled {
+        app.insert_resource(GlobalVolume::new(Volume::Linear(
+            settings.audio.master_volume,
+        )))
+        .add_audio_source::<SynthesizedSound>()
+        .init_resource::<Sounds>()
+        .add_observer(play_sound_effect)
+        .add_observer(play_ambient_loop);
+    }
+
+    app.run();
 }
@@ -0,0 +1,224 @@
+use std::{
+    collections::HashMap,
+    f32::consts::{PI, TAU},
+    sync::Arc,
+    time::Duration,
+};
+
+use bevy::{
+    asset::{Asset, Assets, Handle},
+    audio::{Decodable, Source},
+    ecs::{
+        resource::Resource,
+        world::{FromWorld, World},
+    },
+    reflect::TypePath,
+};
+
+use crate::components::{audio::SoundEffect, level::LevelMetadataBiome};
+
+const SAMPLE_RATE: u32 = 44100;
+
+/// How long an ambient loop lasts before it repeats. Every frequency in the loops is a multiple
+/// of 1/8 Hz, so that a whole number of periods fits in it and the loop repeats without a click.
+const AMBIENT_LOOP_SECONDS: f32 = 8.0;
+
+/// The handles of all sounds, which are synthesized once when the game starts.
+#[derive(Resource)]
+pub struct Sounds {
+    pub effects: HashMap<SoundEffect, Handle<SynthesizedSound>>,
+    pub ambient_loops: HashMap<LevelMetadataBiome, Handle<SynthesizedSound>>,
+}
+
+impl FromWorld for Sounds {
+    fn from_world(world: &mut World) -> Self {
+        let mut sounds = world.resource_mut::<Assets<SynthesizedSound>>();
+
+        Self {
+            effects: SoundEffect::ALL
+                .into_iter()
+                .map(|effect| (effect, sounds.add(SynthesizedSound::effect(effect))))
+                .collect(),
+            ambient_loops: LevelMetadataBiome::ALL
+                .into_iter()
+                .map(|biome| (biome, sounds.add(SynthesizedSound::ambient_loop(biome))))
+                .collect(),
+        }
+    }
+}
+
+/// A mono sound that is computed sample by sample, so that the game does not need sound files.
+#[derive(Asset, TypePath, Clone, Debug)]
+pub struct SynthesizedSound {
+    samples: Arc<[f32]>,
+}
+
+impl SynthesizedSound {
+    /// Samples the wave, given as a function of the time in seconds, for the given duration.
+    fn new(seconds: f32, wave: impl Fn(f32) -> f32) -> Self {
+        let length = (seconds * SAMPLE_RATE as f32) as usize;
+        Self {
+            samples: (0..length)
+                .map(|index| wave(index as f32 / SAMPLE_RATE as f32).clamp(-1.0, 1.0))
+                .collect(),
+        }
+    }
+
+    pub fn effect(effect: SoundEffect) -> Self {
+        match effect {
+            // A short, soft knock.
+            SoundEffect::Move => {
+                Self::new(0.1, |t| sine(190.0, t) * attack(t) * decay(t, 40.0) * 0.6)
+            }
+            // Noise that swells and fades again, like a gust of wind.
+            SoundEffect::Slide => Self::new(0.3, |t| noise(t, 4) * swell(t, 0.3) * 0.4),
+            // A dull thump that drops in pitch.
+            SoundEffect::Land => Self::new(0.25, |t| {
+                (sweep(150.0, 50.0, 0.25, t) + noise(t, 8) * 0.3) * attack(t) * decay(t, 18.0) * 0.8
+            }),
+            // A low rumble for as long as the tiles take to move.
+            SoundEffect::PlatformMove => Self::new(0.5, |t| {
+                (sine(55.0, t) * 0.6 + noise(t, 32) * 0.4) * swell(t, 0.5) * 0.5
+            }),
+            SoundEffect::GoalCollected => Self::arpeggio(&[659.25, 880.0, 1318.5], 0.07, 0.4),
+            // A burst of noise that washes out slowly.
+            SoundEffect::Splash => {
+                Self::new(0.7, |t| noise(t, 2) * attack(t) * decay(t, 6.0) * 0.6)
+            }
+            SoundEffect::LevelComplete => {
+                Self::arpeggio(&[523.25, 659.25, 783.99, 1046.5], 0.14, 0.9)
+            }
+        }
+    }
+
+    /// A chord of which every note swells and fades at its own slow pace.
+    pub fn ambient_loop(biome: LevelMetadataBiome) -> Self {
+        let chord: &[f32] = match biome {
+            // A major
+            LevelMetadataBiome::DAYLIGHT => &[220.0, 277.125, 329.625, 440.0],
+            // A minor
+            LevelMetadataBiome::DUSK => &[220.0, 261.625, 329.625],
+            // An open fifth, low down
+            LevelMetadataBiome::NIGHT => &[110.0, 164.875, 220.0],
+        };
+
+        Self::new(AMBIENT_LOOP_SECONDS, |t| {
+            chord
+                .iter()
+                .enumerate()
+                .map(|(index, frequency)| {
+                    let tremolo = 0.6 + 0.4 * sine(0.125 * (index + 1) as f32, t);
+                    sine(*frequency, t) * tremolo
+                })
+                .sum::<f32>()
+                * 0.5
+                / chord.len() as f32
+        })
+    }
+
+    /// Plucks the notes one after another, each ringing on until the end.
+    fn arpeggio(notes: &[f32], note_seconds: f32, ring_seconds: f32) -> Self {
+        Self::new(notes.len() as f32 * note_seconds + ring_seconds, |t| {
+            notes
+                .iter()
+                .enumerate()
+                .map(|(index, frequency)| (frequency, t - index as f32 * note_seconds))
+                .filter(|(_, t)| *t >= 0.0)
+                .map(|(frequency, t)| sine(*frequency, t) * attack(t) * decay(t, 6.0))
+                .sum::<f32>()
+                * 0.3
+        })
+    }
+}
+
+fn sine(frequency: f32, t: f32) -> f32 {
+    // Taking the fraction first keeps the phase precise at the end of long loops.
+    (TAU * (frequency * t).fract()).sin()
+}
+
+/// A sine wave whose frequency goes linearly from `from` to `to` over the given duration.
+fn sweep(from: f32, to: f32, seconds: f32, t: f32) -> f32 {
+    (TAU * (from * t + (to - from) * t * t / (2.0 * seconds)).fract()).sin()
+}
+
+/// Fades the sound in over a few milliseconds, which prevents a click at its start.
+fn attack(t: f32) -> f32 {
+    1.0 - (-t * 400.0).exp()
+}
+
+fn decay(t: f32, rate: f32) -> f32 {
+    (-t * rate).exp()
+}
+
+/// Rises from silence and falls back to it over the given duration.
+fn swell(t: f32, seconds: f32) -> f32 {
+    (PI * t / seconds).sin().max(0.0)
+}
+
+/// White noise, averaged over `width` samples to take away its highest frequencies.
+fn noise(t: f32, width: u32) -> f32 {
+    let index = (t * SAMPLE_RATE as f32) as u32;
+    (0..width)
+        .map(|offset| white_noise(index.wrapping_sub(offset)))
+        .sum::<f32>()
+        / (width as f32).sqrt()
+}
+
+/// A pseudo-random value between -1 and 1 for each sample, so that every sound is the same each
+/// time the game starts.
+fn white_noise(index: u32) -> f32 {
+    let mut hash = index.wrapping_mul(0x9E37_79B9) ^ 0x85EB_CA6B;
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x7FEB_352D);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x846C_A68B);
+    hash ^= hash >> 16;
+    hash as f32 / u32::MAX as f32 * 2.0 - 1.0
+}
+
+impl Decodable for SynthesizedSound {
+    type DecoderItem = f32;
+    type Decoder = SynthesizedSoundDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        SynthesizedSoundDecoder {
+            samples: self.samples.clone(),
+            index: 0,
+        }
+    }
+}
+
+pub struct SynthesizedSoundDecoder {
+    samples: Arc<[f32]>,
+    index: usize,
+}
+
+impl Iterator for SynthesizedSoundDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.samples.get(self.index).copied();
+        self.index += 1;
+        sample
+    }
+}
+
+impl Source for SynthesizedSoundDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        Some(self.samples.len().saturating_sub(self.index))
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f32(
+            self.samples.len() as f32 / SAMPLE_RATE as f32,
+        ))
+    }
+}
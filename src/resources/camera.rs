@@ -0,0 +1,11 @@
+use bevy::{ecs::resource::Resource, math::Vec3};
+
+use crate::components::level::LevelMetadataCamera;
+
+/// The area of the current level that the camera should keep in view.
+#[derive(Resource, Default, Debug)]
+pub struct CameraFraming {
+    pub center: Vec3,
+    pub radius: f32,
+    pub mode: LevelMetadataCamera,
+}
@@ -0,0 +1,10 @@
+use bevy::{ecs::resource::Resource, tasks::Task};
+
+use crate::generator::GeneratedLevel;
+
+/// The daily puzzle that is being generated in the background, with its seed. The current level
+/// stays playable until it is done.
+#[derive(Resource, Default)]
+pub struct DailyPuzzleGeneration {
+    pub task: Option<(u64, Task<Result<GeneratedLevel, String>>)>,
+}
@@ -0,0 +1,740 @@
+use std::collections::BTreeMap;
+
+use bevy::ecs::resource::Resource;
+
+use crate::{
+    components::{
+        enemy::{EnemyBehaviour, EnemyKind},
+        key::KeyColor,
+        level::{Level, LevelMetadata, LevelMovement, LevelSwitch, get_height_char, parse_height},
+        switch::Bridge,
+        tile::TileEdges,
+        tile_coordinates::MovementDirection,
+    },
+    resources::water::Water,
+};
+
+/// What clicking a hex does in the level editor.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EditorTool {
+    #[default]
+    Height,
+    Player,
+    Goal,
+    Ice,
+    Path,
+}
+
+impl EditorTool {
+    pub const ALL: [EditorTool; 5] = [
+        EditorTool::Height,
+        EditorTool::Player,
+        EditorTool::Goal,
+        EditorTool::Ice,
+        EditorTool::Path,
+    ];
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|tool| *tool == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// The modifier that this tool toggles in the modifier grid, if any.
+    pub fn modifier(self) -> Option<char> {
+        match self {
+            EditorTool::Player => Some('P'),
+            EditorTool::Goal => Some('G'),
+            EditorTool::Ice => Some('I'),
+            EditorTool::Height | EditorTool::Path => None,
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            EditorTool::Height => "Height: left click raises a tile, right click lowers it",
+            EditorTool::Player => "Player: left click places a player, right click removes it",
+            EditorTool::Goal => "Goal: left click places a goal, right click removes it",
+            EditorTool::Ice => "Ice: left click makes a tile icy, right click thaws it",
+            EditorTool::Path => {
+                "Path: left click selects a tile, move keys and PageUp/PageDown add steps, \
+                R removes a step, Enter finishes the path, right click removes it"
+            }
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct LevelEditor {
+    pub enabled: bool,
+    pub tool: EditorTool,
+    pub layer_index: usize,
+    pub level: Option<EditableLevel>,
+    /// The grid cell (row, column) of the current layer whose movement map is being drawn.
+    pub path_cell: Option<(usize, usize)>,
+    /// Whether the level has been edited since it was last rendered.
+    pub has_unrendered_changes: bool,
+    /// Feedback about the last action, e.g. where the level was saved.
+    pub message: String,
+}
+
+/// A level in the same grid form as the level file, but with the height,
+/// modifiers and movement map of every cell together so that it is easy to edit.
+/// All layers have the same dimensions, so that a row and column mean the same hex in each layer.
+/// Tiles that are listed by their coordinates in the level file are placed in the grid as well.
+pub struct EditableLevel {
+    pub metadata: LevelMetadata,
+    pub switches: BTreeMap<String, LevelSwitch>,
+    pub water: Water,
+    pub layers: Vec<EditableLayer>,
+}
+
+pub struct EditableLayer {
+    pub pillars: bool,
+    pub cells: Vec<Vec<EditableCell>>,
+}
+
+#[derive(Clone, Default)]
+pub struct EditableCell {
+    pub height: Option<isize>,
+    pub modifiers: Vec<char>,
+    pub movement_map: Vec<(isize, isize, isize)>,
+    /// The movement that the movement map was read from, which is saved by its shape for as long
+    /// as it still gives the same movement map.
+    pub movement: Option<LevelMovement>,
+    /// The platform that the tile is linked to, which moves as a whole.
+    pub platform: Option<String>,
+    /// The switch that must be on for the tile to move.
+    pub triggered_by: Option<String>,
+    /// The switch lying on top of the tile.
+    pub switch: Option<String>,
+    pub bridge: Option<Bridge>,
+    /// The teleporter pad lying on top of the tile.
+    pub teleporter: Option<String>,
+    /// The direction of the conveyor lying on top of the tile.
+    pub conveyor: Option<MovementDirection>,
+    /// The walls, one-way direction and gate of the tile.
+    pub edges: TileEdges,
+    /// The color of the key lying on top of the tile.
+    pub key: Option<KeyColor>,
+    /// The enemy standing on top of the tile.
+    pub enemy: Option<EnemyBehaviour>,
+}
+
+impl EditableCell {
+    pub fn is_empty(&self) -> bool {
+        self.height.is_none() && self.modifiers.is_empty()
+    }
+
+    fn has_same_movement(&self, other: &EditableCell) -> bool {
+        self.movement_map == other.movement_map
+            && self.platform == other.platform
+            && self.triggered_by == other.triggered_by
+    }
+
+    /// Whether the cell can be written in a height map. Tiles that are too high or too low,
+    /// or that have a switch, teleporter, conveyor, key, enemy, walls or a gate on them or belong
+    /// to a switch, are written in the list of tiles of the layer instead.
+    fn fits_in_height_map(&self) -> bool {
+        self.height
+            .is_none_or(|height| get_height_char(height).is_some())
+            && self.switch.is_none()
+            && self.bridge.is_none()
+            && self.teleporter.is_none()
+            && self.conveyor.is_none()
+            && self.edges.is_empty()
+            && self.key.is_none()
+            && self.enemy.is_none()
+    }
+}
+
+impl EditableLayer {
+    fn is_empty(&self) -> bool {
+        self.cells.iter().flatten().all(EditableCell::is_empty)
+    }
+}
+
+impl EditableLevel {
+    /// Reads the level the same way as `Level::render_level` does.
+    pub fn from_level(level: &Level) -> Result<Self, String> {
+        let mut layers: Vec<EditableLayer> = level
+            .layers
+            .iter()
+            .map(|layer| {
+                let mut movement_maps = layer.movement_maps.clone().unwrap_or_default().into_iter();
+
+                let mut cells: Vec<Vec<EditableCell>> = layer
+                    .height_map
+                    .trim()
+                    .split('\n')
+                    .map(|line| {
+                        line.chars()
+                            .map(|char| {
+                                Ok(EditableCell {
+                                    height: parse_height(char)?,
+                                    ..Default::default()
+                                })
+                            })
+                            .collect::<Result<_, String>>()
+                    })
+                    .collect::<Result<_, String>>()?;
+
+                let modifier_maps: Vec<Vec<Vec<char>>> = layer
+                    .modifiers
+                    .iter()
+                    .map(|modifier_map| {
+                        modifier_map
+                            .trim()
+                            .split('\n')
+                            .map(|line| line.chars().collect())
+                            .collect()
+                    })
+                    .collect();
+
+                for (row_index, row) in cells.iter_mut().enumerate() {
+                    for (col_index, cell) in row.iter_mut().enumerate() {
+                        for modifier_map in &modifier_maps {
+                            let Some(modifier) = modifier_map
+                                .get(row_index)
+                                .and_then(|row| row.get(col_index))
+                            else {
+                                continue;
+                            };
+
+                            // Tiles that move along a named movement are edited like any other moving tile.
+                            let modifier = match modifier {
+                                'P' | 'G' | 'I' => *modifier,
+                                'M' => {
+                                    cell.movement_map = movement_maps.next().unwrap_or_default();
+                                    'M'
+                                }
+                                'a'..='z' => {
+                                    let movement_map =
+                                        level.get_movement_map(&modifier.to_string())?;
+                                    cell.movement =
+                                        Some(level.get_movement(&modifier.to_string())?.clone());
+                                    cell.movement_map = movement_map.map;
+                                    cell.platform = movement_map.platform;
+                                    cell.triggered_by = movement_map.switch;
+                                    'M'
+                                }
+                                _ => continue,
+                            };
+
+                            if !cell.modifiers.contains(&modifier) {
+                                cell.modifiers.push(modifier);
+                            }
+                        }
+                    }
+                }
+
+                Ok(EditableLayer {
+                    pillars: layer.pillars.unwrap_or(false),
+                    cells,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+
+        // Pad all layers to the same dimensions.
+        let width = layers
+            .iter()
+            .flat_map(|layer| layer.cells.iter().map(Vec::len))
+            .max()
+            .unwrap_or(0);
+        let height = layers
+            .iter()
+            .map(|layer| layer.cells.len())
+            .max()
+            .unwrap_or(0);
+
+        for layer in &mut layers {
+            layer.cells.resize(height, vec![]);
+            for row in &mut layer.cells {
+                row.resize(width, EditableCell::default());
+            }
+        }
+
+        let mut editable_level = Self {
+            metadata: level.metadata.clone(),
+            switches: level.switches.clone(),
+            water: level.get_water()?,
+            layers,
+        };
+
+        // The grid positions of listed tiles are found before the grid grows to include them,
+        // since growing the grid shifts all positions.
+        let listed_tiles: Vec<(usize, isize, isize, EditableCell)> = level
+            .layers
+            .iter()
+            .enumerate()
+            .flat_map(|(layer_index, layer)| {
+                layer.tiles.iter().map(move |tile| (layer_index, tile))
+            })
+            .map(|(layer_index, tile)| {
+                let (row, col) = editable_level.get_grid_position(tile.q, tile.r);
+                let movement_map = level.get_tile_movement_map(tile)?;
+                let modifiers = [
+                    (tile.player, 'P'),
+                    (tile.goal, 'G'),
+                    (tile.icy, 'I'),
+                    (!movement_map.map.is_empty(), 'M'),
+                ]
+                .into_iter()
+                .filter_map(|(has_modifier, modifier)| has_modifier.then_some(modifier))
+                .collect();
+
+                let cell = EditableCell {
+                    height: Some(tile.height),
+                    modifiers,
+                    movement_map: movement_map.map,
+                    movement: level.get_tile_movement(tile)?,
+                    platform: movement_map.platform,
+                    triggered_by: movement_map.switch,
+                    switch: tile.switch.clone(),
+                    bridge: tile.get_bridge()?,
+                    teleporter: tile.teleporter.clone(),
+                    conveyor: tile.conveyor,
+                    edges: tile.get_edges(),
+                    key: tile.key,
+                    enemy: tile.get_enemy()?,
+                };
+
+                Ok((layer_index, row, col, cell))
+            })
+            .collect::<Result<_, String>>()?;
+
+        let mut shift = (0, 0);
+        // For each layer of the level, the layers that its listed tiles are placed in.
+        let mut target_layers: Vec<Vec<usize>> = (0..editable_level.layers.len())
+            .map(|index| vec![index])
+            .collect();
+
+        for (layer_index, row, col, cell) in listed_tiles {
+            let ((row, col), (rows_added, cols_added)) =
+                editable_level.grow_to_include(row + shift.0, col + shift.1);
+            shift = (shift.0 + rows_added as isize, shift.1 + cols_added as isize);
+
+            // A hex that is already taken in the layer gets the tile in an extra layer.
+            let target_layer = match target_layers[layer_index]
+                .iter()
+                .find(|index| editable_level.layers[**index].cells[row][col].is_empty())
+            {
+                Some(index) => *index,
+                None => {
+                    let (width, height) = editable_level.get_dimensions();
+                    editable_level.layers.push(EditableLayer {
+                        pillars: editable_level.layers[layer_index].pillars,
+                        cells: vec![vec![EditableCell::default(); width]; height],
+                    });
+                    target_layers[layer_index].push(editable_level.layers.len() - 1);
+                    editable_level.layers.len() - 1
+                }
+            };
+
+            editable_level.layers[target_layer].cells[row][col] = cell;
+        }
+
+        Ok(editable_level)
+    }
+
+    pub fn get_switch(&self, id: &str) -> Result<&LevelSwitch, String> {
+        self.switches
+            .get(id)
+            .ok_or_else(|| format!("Switch '{id}' is used, but not defined"))
+    }
+
+    pub fn get_dimensions(&self) -> (usize, usize) {
+        let cells = &self.layers[0].cells;
+        (cells.first().map(Vec::len).unwrap_or(0), cells.len())
+    }
+
+    /// The (row, column) in the grid of the hex with the given tile coordinates.
+    /// The grid is centered the same way as in `Level::get_level_xz_offsets`.
+    pub fn get_grid_position(&self, x: isize, z: isize) -> (isize, isize) {
+        let (width, height) = self.get_dimensions();
+        (height as isize / 2 - z, x + width as isize / 2)
+    }
+
+    /// The tile coordinates (x, z) of the hex at the given row and column of the grid.
+    pub fn get_tile_coordinates(&self, row: usize, col: usize) -> (isize, isize) {
+        let (width, height) = self.get_dimensions();
+        (
+            col as isize - width as isize / 2,
+            height as isize / 2 - row as isize,
+        )
+    }
+
+    /// Makes sure that the grid contains the given position, by adding rows and columns to all layers.
+    /// Returns the position in the grid, and by how many rows and columns existing cells have shifted.
+    /// The grid grows by as much on both sides, so that the middle of the grid and therefore the tile
+    /// coordinates of the existing cells stay the same.
+    pub fn grow_to_include(&mut self, row: isize, col: isize) -> ((usize, usize), (usize, usize)) {
+        let (width, height) = self.get_dimensions();
+
+        let rows_before = (-row).max(row + 1 - height as isize).max(0) as usize;
+        let rows_after = rows_before;
+        let cols_before = (-col).max(col + 1 - width as isize).max(0) as usize;
+        let cols_after = cols_before;
+        let new_width = width + cols_before + cols_after;
+
+        for layer in &mut self.layers {
+            for row in &mut layer.cells {
+                row.splice(0..0, vec![EditableCell::default(); cols_before]);
+                row.resize(new_width, EditableCell::default());
+            }
+
+            let empty_row = vec![EditableCell::default(); new_width];
+            layer
+                .cells
+                .splice(0..0, vec![empty_row.clone(); rows_before]);
+            layer
+                .cells
+                .resize(height + rows_before + rows_after, empty_row);
+        }
+
+        (
+            (
+                (row + rows_before as isize) as usize,
+                (col + cols_before as isize) as usize,
+            ),
+            (rows_before, cols_before),
+        )
+    }
+
+    /// Adds an empty layer on top, unless the top layer is still empty.
+    /// Returns the index of the top layer.
+    pub fn add_layer(&mut self) -> usize {
+        if !self.layers.last().is_some_and(EditableLayer::is_empty) {
+            let (width, height) = self.get_dimensions();
+            self.layers.push(EditableLayer {
+                pillars: false,
+                cells: vec![vec![EditableCell::default(); width]; height],
+            });
+        }
+
+        self.layers.len() - 1
+    }
+
+    /// Writes the level in the same format as the level files. Empty layers are left out,
+    /// and tiles that are too high or too low for a height map are listed by their coordinates.
+    pub fn to_toml(&self) -> String {
+        let mut toml = format!(
+            "[metadata]\nname = {}\nbiome = {}\nhelp_text = {}\n",
+            toml::Value::from(self.metadata.name.as_str()),
+            toml::Value::try_from(self.metadata.biome).expect("Could not serialize biome"),
+            toml::Value::from(self.metadata.help_text.as_str()),
+        );
+
+        if self.metadata.camera != Default::default() {
+            toml += &format!(
+                "camera = {}\n",
+                toml::Value::try_from(self.metadata.camera).expect("Could not serialize camera")
+            );
+        }
+
+        if let Some(max_moves) = self.metadata.max_moves {
+            toml += &format!("max_moves = {max_moves}\n");
+        }
+
+        for (id, switch) in &self.switches {
+            toml += &format!(
+                "\n[switches.{id}]\nkind = {}\n",
+                toml::Value::try_from(switch.kind).expect("Could not serialize switch kind")
+            );
+
+            if switch.on {
+                toml += "on = true\n";
+            }
+        }
+
+        if let Some(height) = self.water.height {
+            toml += &format!("\n[water]\nheight = {height}\n");
+
+            let movement_map = &self.water.movement_map;
+            if !movement_map.map.is_empty() {
+                toml += &format!(
+                    "\n[water.movement]\nshape = \"offsets\"\noffsets = {}\n",
+                    format_offsets(&movement_map.map)
+                );
+
+                if let Some(switch) = &movement_map.switch {
+                    toml += &format!("triggered_by = {}\n", toml::Value::from(switch.as_str()));
+                }
+            }
+        }
+
+        // Every different movement map is written as a movement, whose id is placed in the
+        // modifier map or given to the listed tile. Only when the ids run out the `M` modifier
+        // or the movement map of the listed tile is used. Tiles of the same platform share a linked movement.
+        let mut movements: Vec<&EditableCell> = vec![];
+        for cell in self
+            .layers
+            .iter()
+            .flat_map(|layer| layer.cells.iter().flatten())
+        {
+            if cell.modifiers.contains(&'M')
+                && !movements
+                    .iter()
+                    .any(|movement| movement.has_same_movement(cell))
+            {
+                movements.push(cell);
+            }
+        }
+
+        let movement_ids: Vec<char> = ('a'..='z').take(movements.len()).collect();
+        let get_movement_id = |cell: &EditableCell| {
+            let index = movements
+                .iter()
+                .position(|movement| movement.has_same_movement(cell))?;
+            movement_ids.get(index).copied()
+        };
+
+        for (id, movement) in movement_ids.iter().zip(&movements) {
+            toml += &format!("\n[movements.{id}]\n");
+
+            match movement.movement.as_ref().filter(|original| {
+                original.get_movement_map().as_ref() == Ok(&movement.movement_map)
+            }) {
+                Some(original) => {
+                    toml += &toml::to_string(&original.shape).expect("Could not serialize shape");
+
+                    if original.phase != 0 {
+                        toml += &format!("phase = {}\n", original.phase);
+                    }
+
+                    if let Some(period) = original.period {
+                        toml += &format!("period = {period}\n");
+                    }
+                }
+                None => {
+                    toml += &format!(
+                        "shape = \"offsets\"\noffsets = {}\n",
+                        format_offsets(&movement.movement_map)
+                    )
+                }
+            }
+
+            if movement.platform.is_some() {
+                toml += "linked = true\n";
+            }
+
+            if let Some(switch) = &movement.triggered_by {
+                toml += &format!("triggered_by = {}\n", toml::Value::from(switch.as_str()));
+            }
+        }
+
+        for layer in self.layers.iter().filter(|layer| !layer.is_empty()) {
+            let height_map: Vec<String> = layer
+                .cells
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|cell| {
+                            cell.height
+                                .filter(|_| cell.fits_in_height_map())
+                                .and_then(get_height_char)
+                                .unwrap_or('.')
+                        })
+                        .collect()
+                })
+                .collect();
+
+            // Each kind of modifier goes in the first modifier map where it fits,
+            // so that a cell with several modifiers gets one in each map.
+            let (width, height) = self.get_dimensions();
+            let mut modifier_grids: Vec<Vec<Vec<char>>> = vec![];
+
+            for modifier in ['P', 'G', 'I', 'M'] {
+                let positions: Vec<(usize, usize, char)> = layer
+                    .cells
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(row_index, row)| {
+                        row.iter()
+                            .enumerate()
+                            .filter(|(_, cell)| {
+                                cell.fits_in_height_map() && cell.modifiers.contains(&modifier)
+                            })
+                            .map(move |(col_index, cell)| {
+                                let char = match modifier {
+                                    'M' => get_movement_id(cell).unwrap_or('M'),
+                                    _ => modifier,
+                                };
+                                (row_index, col_index, char)
+                            })
+                    })
+                    .collect();
+
+                if positions.is_empty() {
+                    continue;
+                }
+
+                let index = match modifier_grids.iter().position(|grid| {
+                    positions
+                        .iter()
+                        .all(|(row, col, _)| grid[*row][*col] == '.')
+                }) {
+                    Some(index) => index,
+                    None => {
+                        modifier_grids.push(vec![vec!['.'; width]; height]);
+                        modifier_grids.len() - 1
+                    }
+                };
+
+                for (row, col, char) in positions {
+                    modifier_grids[index][row][col] = char;
+                }
+            }
+
+            if modifier_grids.is_empty() {
+                modifier_grids.push(vec![vec!['.'; width]; height]);
+            }
+
+            let modifier_maps: Vec<String> = modifier_grids
+                .iter()
+                .map(|grid| {
+                    let rows: Vec<String> = grid.iter().map(|row| row.iter().collect()).collect();
+                    format!("\"\"\"\n{}\n\"\"\"", rows.join("\n"))
+                })
+                .collect();
+
+            toml += &format!(
+                "\n[[layers]]\npillars = {}\nheight_map = \"\"\"\n{}\n\"\"\"\nmodifiers = [{}]\n",
+                layer.pillars,
+                height_map.join("\n"),
+                modifier_maps.join(", "),
+            );
+
+            // Movement maps are matched to the `M` modifiers in the order in which they appear.
+            let unnamed_movement_maps: Vec<String> = layer
+                .cells
+                .iter()
+                .flatten()
+                .filter(|cell| {
+                    cell.fits_in_height_map()
+                        && cell.modifiers.contains(&'M')
+                        && get_movement_id(cell).is_none()
+                })
+                .map(|cell| format!("    {},\n", format_offsets(&cell.movement_map)))
+                .collect();
+
+            if !unnamed_movement_maps.is_empty() {
+                toml += &format!("movement_maps = [\n{}]\n", unnamed_movement_maps.concat());
+            }
+
+            for (row_index, row) in layer.cells.iter().enumerate() {
+                for (col_index, cell) in row.iter().enumerate() {
+                    let Some(height) = cell.height.filter(|_| !cell.fits_in_height_map()) else {
+                        continue;
+                    };
+
+                    let (q, r) = self.get_tile_coordinates(row_index, col_index);
+                    toml += &format!("\n[[layers.tiles]]\nq = {q}\nr = {r}\nheight = {height}\n");
+
+                    for (modifier, key) in [('P', "player"), ('G', "goal"), ('I', "icy")] {
+                        if cell.modifiers.contains(&modifier) {
+                            toml += &format!("{key} = true\n");
+                        }
+                    }
+
+                    if cell.modifiers.contains(&'M') && !cell.movement_map.is_empty() {
+                        match get_movement_id(cell) {
+                            Some(id) => toml += &format!("movement = \"{id}\"\n"),
+                            None => {
+                                toml += &format!(
+                                    "movement_map = {}\n",
+                                    format_offsets(&cell.movement_map)
+                                )
+                            }
+                        }
+                    }
+
+                    if let Some(switch) = &cell.switch {
+                        toml += &format!("switch = {}\n", toml::Value::from(switch.as_str()));
+                    }
+
+                    if let Some(bridge) = &cell.bridge {
+                        let key = if bridge.is_shown_when_on {
+                            "shown_by"
+                        } else {
+                            "hidden_by"
+                        };
+                        toml += &format!("{key} = {}\n", toml::Value::from(bridge.switch.as_str()));
+                    }
+
+                    if let Some(teleporter) = &cell.teleporter {
+                        toml +=
+                            &format!("teleporter = {}\n", toml::Value::from(teleporter.as_str()));
+                    }
+
+                    if let Some(direction) = cell.conveyor {
+                        toml += &format!(
+                            "conveyor = {}\n",
+                            toml::Value::try_from(direction)
+                                .expect("Could not serialize direction")
+                        );
+                    }
+
+                    if !cell.edges.walls.is_empty() {
+                        toml += &format!(
+                            "walls = {}\n",
+                            toml::Value::try_from(&cell.edges.walls)
+                                .expect("Could not serialize walls")
+                        );
+                    }
+
+                    if let Some(direction) = cell.edges.one_way {
+                        toml += &format!(
+                            "one_way = {}\n",
+                            toml::Value::try_from(direction)
+                                .expect("Could not serialize direction")
+                        );
+                    }
+
+                    if let Some(color) = cell.key {
+                        toml += &format!(
+                            "key = {}\n",
+                            toml::Value::try_from(color).expect("Could not serialize key")
+                        );
+                    }
+
+                    match &cell.enemy {
+                        Some(EnemyBehaviour::Patrol(route)) => {
+                            toml += &format!(
+                                "enemy = {}\nroute = {}\n",
+                                toml::Value::try_from(EnemyKind::Patrol)
+                                    .expect("Could not serialize enemy"),
+                                toml::Value::try_from(route).expect("Could not serialize route")
+                            );
+                        }
+                        Some(EnemyBehaviour::Chase) => {
+                            toml += &format!(
+                                "enemy = {}\n",
+                                toml::Value::try_from(EnemyKind::Chase)
+                                    .expect("Could not serialize enemy")
+                            );
+                        }
+                        None => {}
+                    }
+
+                    if let Some(color) = cell.edges.gate {
+                        toml += &format!(
+                            "gate = {}\n",
+                            toml::Value::try_from(color).expect("Could not serialize gate")
+                        );
+                    }
+                }
+            }
+        }
+
+        toml
+    }
+}
+
+fn format_offsets(offsets: &[(isize, isize, isize)]) -> String {
+    let offsets: Vec<String> = offsets
+        .iter()
+        .map(|(x, y, z)| format!("[{x}, {y}, {z}]"))
+        .collect();
+
+    format!("[{}]", offsets.join(", "))
+}
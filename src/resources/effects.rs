@@ -1,7 +1,31 @@
-use bevy::{asset::Handle, ecs::resource::Resource};
+use std::collections::HashMap;
+
+use bevy::{asset::Handle, ecs::resource::Resource, mesh::Mesh, pbr::StandardMaterial};
 use bevy_hanabi::EffectAsset;
 
+use crate::components::{key::KeyColor, switch::SwitchKind};
+
 #[derive(Resource, Default)]
 pub struct GlobalEffects {
     pub goal_explosion_effect: Option<Handle<EffectAsset>>,
+    pub teleport_effect: Option<Handle<EffectAsset>>,
+    pub prediction_ghost_tile_mesh: Option<Handle<Mesh>>,
+    pub prediction_ghost_object_mesh: Option<Handle<Mesh>>,
+    pub prediction_ghost_material: Option<Handle<StandardMaterial>>,
+    pub conveyor_arrow_mesh: Option<Handle<Mesh>>,
+    pub conveyor_arrow_material: Option<Handle<StandardMaterial>>,
+    pub wall_mesh: Option<Handle<Mesh>>,
+    pub wall_material: Option<Handle<StandardMaterial>>,
+    pub one_way_arrow_material: Option<Handle<StandardMaterial>>,
+    pub key_mesh: Option<Handle<Mesh>>,
+    pub key_materials: HashMap<KeyColor, Handle<StandardMaterial>>,
+    pub gate_mesh: Option<Handle<Mesh>>,
+    pub gate_materials: HashMap<KeyColor, Handle<StandardMaterial>>,
+    pub enemy_mesh: Option<Handle<Mesh>>,
+    pub enemy_material: Option<Handle<StandardMaterial>>,
+    pub switch_mesh: Option<Handle<Mesh>>,
+    /// By the kind of switch and whether it is on.
+    pub switch_materials: HashMap<(SwitchKind, bool), Handle<StandardMaterial>>,
+    pub teleporter_mesh: Option<Handle<Mesh>>,
+    pub teleporter_material: Option<Handle<StandardMaterial>>,
 }
@@ -0,0 +1,293 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fs,
+    io::ErrorKind,
+    path::Path,
+};
+
+use bevy::{
+    ecs::resource::Resource,
+    input::{gamepad::GamepadButton, keyboard::KeyCode},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::components::tile_coordinates::MovementDirection;
+
+/// Everything the player can do, independent of the key or button that triggers it.
+/// Movement directions are relative to the screen, not to the level.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InputAction {
+    MoveNW,
+    MoveNE,
+    MoveE,
+    MoveSE,
+    MoveSW,
+    MoveW,
+    RotateCW,
+    RotateCCW,
+    Restart,
+    Undo,
+    Confirm,
+    ZoomIn,
+    ZoomOut,
+    TiltUp,
+    TiltDown,
+    ToggleMovementMapOverlay,
+    PlayReplay,
+    ExportReplay,
+    ReplayFaster,
+    ReplaySlower,
+    ToggleEditor,
+    EditorNextTool,
+    EditorNextLayer,
+    EditorTogglePillars,
+    EditorPathUp,
+    EditorPathDown,
+    EditorSave,
+    PlayDailyPuzzle,
+}
+
+impl InputAction {
+    /// All actions, in the order in which they are offered for rebinding.
+    pub const ALL: [InputAction; 28] = [
+        InputAction::MoveNW,
+        InputAction::MoveNE,
+        InputAction::MoveE,
+        InputAction::MoveSE,
+        InputAction::MoveSW,
+        InputAction::MoveW,
+        InputAction::RotateCW,
+        InputAction::RotateCCW,
+        InputAction::Restart,
+        InputAction::Undo,
+        InputAction::Confirm,
+        InputAction::ZoomIn,
+        InputAction::ZoomOut,
+        InputAction::TiltUp,
+        InputAction::TiltDown,
+        InputAction::ToggleMovementMapOverlay,
+        InputAction::PlayReplay,
+        InputAction::ExportReplay,
+        InputAction::ReplayFaster,
+        InputAction::ReplaySlower,
+        InputAction::ToggleEditor,
+        InputAction::EditorNextTool,
+        InputAction::EditorNextLayer,
+        InputAction::EditorTogglePillars,
+        InputAction::EditorPathUp,
+        InputAction::EditorPathDown,
+        InputAction::EditorSave,
+        InputAction::PlayDailyPuzzle,
+    ];
+
+    /// Whether this action should wait for ongoing animations to finish, rather than be ignored.
+    pub fn is_bufferable(&self) -> bool {
+        self.movement_direction().is_some()
+            || matches!(
+                self,
+                InputAction::RotateCW
+                    | InputAction::RotateCCW
+                    | InputAction::Undo
+                    | InputAction::Restart
+            )
+    }
+
+    pub fn movement_direction(&self) -> Option<MovementDirection> {
+        match self {
+            InputAction::MoveNW => Some(MovementDirection::NorthWest),
+            InputAction::MoveNE => Some(MovementDirection::NorthEast),
+            InputAction::MoveE => Some(MovementDirection::East),
+            InputAction::MoveSE => Some(MovementDirection::SouthEast),
+            InputAction::MoveSW => Some(MovementDirection::SouthWest),
+            InputAction::MoveW => Some(MovementDirection::West),
+            _ => None,
+        }
+    }
+
+    /// The movement action whose on-screen direction is closest to the given angle,
+    /// in radians counter-clockwise from the right of the screen.
+    pub fn from_screen_angle(angle: f32) -> Self {
+        let sixth = (angle.to_degrees() / 60.0).round().rem_euclid(6.0) as usize;
+
+        [
+            InputAction::MoveE,
+            InputAction::MoveNE,
+            InputAction::MoveNW,
+            InputAction::MoveW,
+            InputAction::MoveSW,
+            InputAction::MoveSE,
+        ][sixth]
+    }
+}
+
+#[derive(Resource, Deserialize, Clone, Debug)]
+pub struct InputBindings {
+    pub keyboard: HashMap<InputAction, Vec<KeyCode>>,
+    pub gamepad: HashMap<InputAction, Vec<GamepadButton>>,
+    /// How far the stick must be pushed before it counts as a movement.
+    pub gamepad_stick_threshold: f32,
+    /// How far a touch must travel, in logical pixels, before it counts as a swipe.
+    pub swipe_threshold: f32,
+    /// How many moves, rotations, undos and restarts are remembered while the level or camera is
+    /// still animating.
+    pub buffer_length: usize,
+    /// Starts rebinding all actions one by one. This key itself cannot be rebound,
+    /// so that a bad set of bindings can always be fixed.
+    pub rebind_key: KeyCode,
+    /// Where the rebound keys and buttons are stored, so that they are kept between runs.
+    pub saved_bindings: String,
+}
+
+/// The part of the bindings that the player can change, as stored in `saved_bindings`.
+#[derive(Serialize, Deserialize, Default)]
+struct SavedBindings {
+    keyboard: HashMap<InputAction, Vec<KeyCode>>,
+    gamepad: HashMap<InputAction, Vec<GamepadButton>>,
+}
+
+/// Binds the input to the action instead of its current inputs. An action that the input was bound
+/// to gets those inputs instead, so that no input triggers two actions.
+fn bind<T: Copy + PartialEq>(
+    bindings: &mut HashMap<InputAction, Vec<T>>,
+    action: InputAction,
+    input: T,
+) {
+    let previous = bindings.insert(action, vec![input]).unwrap_or_default();
+
+    for (other_action, inputs) in bindings.iter_mut() {
+        if *other_action != action && inputs.contains(&input) {
+            inputs.retain(|other_input| *other_input != input);
+            inputs.extend(previous.iter().filter(|other_input| **other_input != input));
+        }
+    }
+}
+
+/// The saved bindings replace the built-in bindings of the same actions.
+fn load<T: Copy + PartialEq>(
+    bindings: &mut HashMap<InputAction, Vec<T>>,
+    saved: HashMap<InputAction, Vec<T>>,
+) {
+    for (action, inputs) in saved {
+        for input in &inputs {
+            bindings
+                .values_mut()
+                .for_each(|other_inputs| other_inputs.retain(|other_input| other_input != input));
+        }
+
+        bindings.insert(action, inputs);
+    }
+}
+
+impl InputBindings {
+    /// Makes the key the only one of the action. An action that already used the key gets the
+    /// previous keys of this action instead.
+    pub fn bind_key(&mut self, action: InputAction, key_code: KeyCode) {
+        bind(&mut self.keyboard, action, key_code);
+    }
+
+    /// Makes the button the only one of the action, the same way as `bind_key`.
+    pub fn bind_button(&mut self, action: InputAction, button: GamepadButton) {
+        bind(&mut self.gamepad, action, button);
+    }
+
+    /// Applies the bindings that were saved by an earlier run, if there are any.
+    pub fn load_saved_bindings(&mut self) -> Result<(), String> {
+        let contents = match fs::read_to_string(&self.saved_bindings) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(error) => return Err(format!("Could not read {}: {error}", self.saved_bindings)),
+        };
+        let saved: SavedBindings = toml::from_str(&contents)
+            .map_err(|error| format!("Could not parse {}: {error}", self.saved_bindings))?;
+
+        load(&mut self.keyboard, saved.keyboard);
+        load(&mut self.gamepad, saved.gamepad);
+
+        Ok(())
+    }
+
+    /// Writes all bindings to the save file, so that they are kept for the next run.
+    pub fn save_bindings(&self) -> Result<(), String> {
+        let contents = toml::to_string(&SavedBindings {
+            keyboard: self.keyboard.clone(),
+            gamepad: self.gamepad.clone(),
+        })
+        .map_err(|error| format!("Could not serialize the bindings: {error}"))?;
+        let path = Path::new(&self.saved_bindings);
+
+        fs::create_dir_all(path.parent().unwrap_or(path))
+            .and_then(|_| fs::write(path, contents))
+            .map_err(|error| format!("Could not save the bindings to {}: {error}", path.display()))
+    }
+
+    /// The name of the first key bound to the action, for on-screen hints. Falls back to the first
+    /// gamepad button if the action has no key.
+    pub fn name(&self, action: InputAction) -> String {
+        if let Some(key_code) = self.keyboard.get(&action).and_then(|keys| keys.first()) {
+            let name = format!("{key_code:?}");
+
+            return match name.strip_prefix("Key").or(name.strip_prefix("Digit")) {
+                Some(character) => character.to_owned(),
+                None => name,
+            };
+        }
+
+        match self
+            .gamepad
+            .get(&action)
+            .and_then(|buttons| buttons.first())
+        {
+            Some(button) => format!("{button:?}"),
+            None => "unbound".to_owned(),
+        }
+    }
+
+    /// Replaces every action written in braces, such as `{Undo}`, with the name of its key.
+    pub fn fill_in_names(&self, text: &str) -> String {
+        InputAction::ALL
+            .iter()
+            .fold(text.to_owned(), |text, action| {
+                text.replace(&format!("{{{action:?}}}"), &self.name(*action))
+            })
+    }
+}
+
+/// The actions that were triggered during the current frame.
+#[derive(Resource, Default)]
+pub struct ActionInput {
+    /// In the order in which they were pressed.
+    pub just_pressed: Vec<InputAction>,
+    pub pressed: HashSet<InputAction>,
+    /// Whether the gamepad stick was pushed past the threshold during the previous frame.
+    /// A stick only triggers a movement when it is first pushed.
+    pub stick_engaged: bool,
+}
+
+impl ActionInput {
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    pub fn press(&mut self, action: InputAction) {
+        if !self.just_pressed(action) {
+            self.just_pressed.push(action);
+        }
+    }
+
+    pub fn pressed(&self, action: InputAction) -> bool {
+        self.pressed.contains(&action)
+    }
+}
+
+/// Moves, rotations, undos and restarts that are waiting for the animations before them to finish,
+/// in the order they were pressed.
+#[derive(Resource, Default)]
+pub struct InputBuffer {
+    pub actions: VecDeque<InputAction>,
+}
+
+/// While rebinding, the next key or button that is pressed gets bound to `action`.
+#[derive(Resource, Default)]
+pub struct InputRebinding {
+    pub action: Option<InputAction>,
+}
@@ -0,0 +1,27 @@
+use bevy::ecs::resource::Resource;
+
+use crate::components::key::KeyColor;
+
+/// The keys that the players are holding. Each key opens a single gate.
+#[derive(Resource, Clone, Default, Debug, PartialEq, Eq, Hash)]
+pub struct KeyInventory {
+    /// Sorted, so that the same keys collected in a different order compare equal.
+    pub keys: Vec<KeyColor>,
+}
+
+impl KeyInventory {
+    pub fn add(&mut self, color: KeyColor) {
+        let index = self.keys.partition_point(|key| *key < color);
+        self.keys.insert(index, color);
+    }
+
+    /// Uses up a key of the color. Returns whether there was one.
+    pub fn take(&mut self, color: KeyColor) -> bool {
+        let Some(index) = self.keys.iter().position(|key| *key == color) else {
+            return false;
+        };
+
+        self.keys.remove(index);
+        true
+    }
+}
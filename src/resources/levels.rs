@@ -1,33 +1,119 @@
-use bevy::ecs::resource::Resource;
+use bevy::ecs::{entity::Entity, resource::Resource, schedule::SystemSet};
 use include_dir::{Dir, include_dir};
 use serde::Deserialize;
 
-use crate::components::level::Level;
+use crate::{
+    components::{key::KeyColor, level::Level},
+    resources::{keys::KeyInventory, switches::SwitchStates, water::Water},
+};
 
 static LEVEL_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/levels");
 
-#[derive(Deserialize, Default, Debug)]
+/// The phases of a turn, in the order in which they are played. Each phase in which objects move
+/// is followed by one in which anything they left without support falls, so that objects only
+/// fall once every tile has arrived where it is going. `advance_turn` moves on to the next phase
+/// once nothing is moving anymore.
+#[derive(Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LevelState {
     #[default]
     WaitingForPlayerInput,
-    ProcessingPlayerInput,
-    ProcessingLevelEffects,
+    /// The players take their step, and slide over ice.
+    PlayerMoving,
+    PlayerFalling,
+    /// The enemies take their step after the players have landed.
+    EnemiesMoving,
+    EnemiesFalling,
+    /// Switches are pressed, tiles follow their movement maps and carry anything standing on
+    /// them, conveyors push, and the water rises or falls.
+    EnvironmentMoving,
+    EnvironmentFalling,
+}
+
+impl LevelState {
+    pub fn is_falling(&self) -> bool {
+        matches!(
+            self,
+            LevelState::PlayerFalling | LevelState::EnemiesFalling | LevelState::EnvironmentFalling
+        )
+    }
+}
+
+/// The order of the turn systems within a frame. Objects first finish their steps and the turn
+/// moves on to its next phase, then goals and keys are collected and players drown or are caught,
+/// and only then is new input read.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TurnSet {
+    Movement,
+    Resolution,
+    Input,
 }
 
 #[derive(Resource, Default)]
 pub struct LevelResource {
     pub current_level_number: isize,
     pub level_state: LevelState,
+    /// The contents of the current level as changed in the level editor.
+    /// These are played instead of the level file, until another level is loaded.
+    pub edited_level: Option<String>,
+    /// The seed of the generated level that is played in the daily puzzle mode.
+    pub daily_puzzle_seed: Option<u64>,
 }
 
 impl LevelResource {
-    pub fn get_level(&self) -> Level {
-        let level = LEVEL_DIR
+    /// Reads the level file. It is checked when it is spawned, by `Level::render_level`.
+    pub fn get_level(&self) -> Result<Level, String> {
+        toml::from_str(self.get_level_contents()?).map_err(|error| error.to_string())
+    }
+
+    /// Fails after the last level, as there is no level file to read.
+    pub fn get_level_contents(&self) -> Result<&str, String> {
+        if let Some(edited_level) = &self.edited_level {
+            return Ok(edited_level);
+        }
+
+        LEVEL_DIR
             .get_file(format!("{}.toml", self.current_level_number).as_str())
-            .expect("Level number not found")
+            .ok_or_else(|| format!("There is no level {}", self.current_level_number))?
             .contents_utf8()
-            .unwrap();
+            .ok_or_else(|| format!("Level {} is not UTF-8", self.current_level_number))
+    }
 
-        toml::from_str(level).unwrap()
+    /// A hash of the level file, used to detect whether a replay still matches the level.
+    /// This is FNV-1a, because unlike the standard library hasher its output is stable.
+    pub fn get_level_hash(&self) -> Result<u64, String> {
+        Ok(self
+            .get_level_contents()?
+            .bytes()
+            .fold(0xcbf29ce484222325, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+            }))
     }
 }
+
+/// The state of the level at the start of each turn, so that turns can be undone.
+#[derive(Resource, Default)]
+pub struct UndoHistory {
+    pub snapshots: Vec<LevelSnapshot>,
+}
+
+pub struct LevelSnapshot {
+    pub objects: Vec<ObjectSnapshot>,
+    /// Goals are despawned when collected, so they are stored by position and respawned.
+    pub goals: Vec<(isize, isize, isize)>,
+    pub switch_states: SwitchStates,
+    pub water: Water,
+    /// Keys are despawned when collected as well.
+    pub keys: Vec<((isize, isize, isize), KeyColor)>,
+    pub inventory: KeyInventory,
+    /// The tiles with a gate that was still locked. Gates are never locked again during a level.
+    pub gates: Vec<(Entity, KeyColor)>,
+}
+
+/// The position of a player, enemy or moving tile, and how far along its movement map or route
+/// it is.
+pub struct ObjectSnapshot {
+    pub entity: Entity,
+    pub coordinates: (isize, isize, isize),
+    pub movement_map_index: Option<usize>,
+    pub route_index: Option<usize>,
+}
@@ -1,3 +1,15 @@
+pub mod audio;
+pub mod camera;
+pub mod daily_puzzle;
+pub mod editor;
 pub mod effects;
+pub mod input;
+pub mod keys;
 pub mod levels;
+pub mod overlays;
+pub mod pathfinding;
+pub mod replay;
 pub mod settings;
+pub mod stats;
+pub mod switches;
+pub mod water;
@@ -0,0 +1,6 @@
+use bevy::ecs::resource::Resource;
+
+#[derive(Resource, Default)]
+pub struct MovementMapOverlay {
+    pub enabled: bool,
+}
@@ -0,0 +1,12 @@
+use std::collections::VecDeque;
+
+use bevy::ecs::resource::Resource;
+
+use crate::components::tile_coordinates::MovementDirection;
+
+/// Moves that were planned ahead, e.g. by clicking a distant tile. These are
+/// carried out one per turn, and are relative to the level rather than the screen.
+#[derive(Resource, Default)]
+pub struct PlannedMoves {
+    pub directions: VecDeque<MovementDirection>,
+}
@@ -0,0 +1,113 @@
+use std::collections::VecDeque;
+
+use bevy::ecs::resource::Resource;
+use serde::{Deserialize, Serialize};
+
+use crate::components::{camera::RotationDirection, tile_coordinates::MovementDirection};
+
+/// A single step of a replay. Moves are stored relative to the level, so
+/// rotating the camera only matters for what the replay looks like.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReplayStep {
+    Move(MovementDirection),
+    Rotate(RotationDirection),
+}
+
+impl ReplayStep {
+    pub fn to_token(self) -> &'static str {
+        match self {
+            ReplayStep::Move(MovementDirection::NorthWest) => "NW",
+            ReplayStep::Move(MovementDirection::NorthEast) => "NE",
+            ReplayStep::Move(MovementDirection::East) => "E",
+            ReplayStep::Move(MovementDirection::SouthEast) => "SE",
+            ReplayStep::Move(MovementDirection::SouthWest) => "SW",
+            ReplayStep::Move(MovementDirection::West) => "W",
+            ReplayStep::Rotate(RotationDirection::Clockwise) => "CW",
+            ReplayStep::Rotate(RotationDirection::CounterClockwise) => "CCW",
+        }
+    }
+
+    pub fn from_token(token: &str) -> Option<Self> {
+        Some(match token {
+            "NW" => ReplayStep::Move(MovementDirection::NorthWest),
+            "NE" => ReplayStep::Move(MovementDirection::NorthEast),
+            "E" => ReplayStep::Move(MovementDirection::East),
+            "SE" => ReplayStep::Move(MovementDirection::SouthEast),
+            "SW" => ReplayStep::Move(MovementDirection::SouthWest),
+            "W" => ReplayStep::Move(MovementDirection::West),
+            "CW" => ReplayStep::Rotate(RotationDirection::Clockwise),
+            "CCW" => ReplayStep::Rotate(RotationDirection::CounterClockwise),
+            _ => return None,
+        })
+    }
+}
+
+/// The plain text form of a replay, as it is saved to and loaded from disk.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReplayFile {
+    pub level: isize,
+    /// The hash of the level file at the time of recording, see `LevelResource::get_level_hash`.
+    pub hash: String,
+    /// The steps as whitespace separated tokens, e.g. `"NE E CW W"`.
+    pub steps: String,
+}
+
+impl ReplayFile {
+    pub fn new(level: isize, hash: u64, steps: &[ReplayStep]) -> Self {
+        Self {
+            level,
+            hash: format!("{hash:016x}"),
+            steps: steps
+                .iter()
+                .map(|step| step.to_token())
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+
+    /// Checks that the replay belongs to the given level, and parses its steps.
+    pub fn parse_steps(&self, level: isize, hash: u64) -> Result<Vec<ReplayStep>, String> {
+        if self.level != level {
+            return Err(format!(
+                "This replay was recorded for level {}, not level {level}",
+                self.level
+            ));
+        }
+
+        if self.hash != format!("{hash:016x}") {
+            return Err(format!(
+                "Level {level} has changed since this replay was recorded"
+            ));
+        }
+
+        self.steps
+            .split_whitespace()
+            .map(|token| {
+                ReplayStep::from_token(token).ok_or(format!("Unknown replay step `{token}`"))
+            })
+            .collect()
+    }
+}
+
+/// The steps taken during the current attempt at the level.
+#[derive(Resource, Default)]
+pub struct ReplayRecorder {
+    pub steps: Vec<ReplayStep>,
+    pub is_saved: bool,
+}
+
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    /// The steps that still have to be played back. Empty when no replay is playing.
+    pub steps: VecDeque<ReplayStep>,
+    pub speed: f32,
+}
+
+impl Default for ReplayPlayback {
+    fn default() -> Self {
+        Self {
+            steps: VecDeque::new(),
+            speed: 1.0,
+        }
+    }
+}
@@ -1,11 +1,26 @@
 use bevy::ecs::resource::Resource;
 use serde::Deserialize;
 
+use crate::resources::input::InputBindings;
+
 #[derive(Resource, Deserialize, Clone, Debug)]
 pub struct Settings {
     pub initial_level_number: isize,
+    pub difficulty: Difficulty,
     pub display: DisplaySettings,
+    pub audio: AudioSettings,
     pub camera: CameraSettings,
+    pub replays: ReplaySettings,
+    pub editor: EditorSettings,
+    pub generator: GeneratorSettings,
+    pub input: InputBindings,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -15,6 +30,8 @@ pub struct DisplaySettings {
     pub water: WaterDisplaySettings,
     pub sun: SunDisplaySettings,
     pub level_complete: LevelCompleteDisplaySettings,
+    pub movement_map_overlay: MovementMapOverlayDisplaySettings,
+    pub prediction_ghost: PredictionGhostDisplaySettings,
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -38,12 +55,55 @@ pub struct LevelCompleteDisplaySettings {
     pub font_size: f32,
 }
 
+#[derive(Deserialize, Clone, Debug)]
+pub struct MovementMapOverlayDisplaySettings {
+    pub enabled_by_default: bool,
+    pub line_width: f32,
+    pub next_step_line_width: f32,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct PredictionGhostDisplaySettings {
+    /// The difficulties at which the next turn of moving tiles is previewed.
+    pub difficulties: Vec<Difficulty>,
+    pub alpha: f32,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct AudioSettings {
+    /// Without audio, the game does not open an audio device, as in headless runs.
+    pub enabled: bool,
+    /// The volumes go from 0 for silence to 1 for full volume. The others are scaled by this one.
+    pub master_volume: f32,
+    pub effects_volume: f32,
+    pub ambient_volume: f32,
+}
+
 #[derive(Deserialize, Clone, Debug)]
 pub struct CameraSettings {
     pub rotation_speed: f32,
+    pub zoom_speed: f32,
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+    /// The initial pitch of the camera, in degrees.
+    pub pitch: f32,
+    pub min_pitch: f32,
+    pub max_pitch: f32,
+    /// The speed at which the pitch changes, in degrees per second.
+    pub pitch_speed: f32,
+    pub follow: CameraFollowSettings,
     pub bloom: CameraBloomSettings,
 }
 
+#[derive(Deserialize, Clone, Debug)]
+pub struct CameraFollowSettings {
+    /// The radius around the followed point that is kept in view.
+    pub view_radius: f32,
+    /// How far the players can move away from the camera focus before the camera follows.
+    pub dead_zone: f32,
+    pub speed: f32,
+}
+
 #[derive(Deserialize, Clone, Debug)]
 pub struct CameraBloomSettings {
     pub intensity: f32,
@@ -51,3 +111,33 @@ pub struct CameraBloomSettings {
     pub low_frequency_boost_curvature: f32,
     pub high_pass_frequency: f32,
 }
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct ReplaySettings {
+    /// Where the replay of the last completion of each level is stored.
+    pub directory: String,
+    pub min_speed: f32,
+    pub max_speed: f32,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct EditorSettings {
+    /// Where levels are saved by the level editor. This is not the `levels` directory, so that
+    /// saving never overwrites a level of the game.
+    pub directory: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct GeneratorSettings {
+    pub width: usize,
+    pub height: usize,
+    /// The number of different heights that tiles can have.
+    pub height_levels: usize,
+    /// The chance for each tile to be icy.
+    pub ice_density: f32,
+    pub moving_platforms: usize,
+    /// Levels that can be solved in fewer moves than this are rejected.
+    pub min_solution_length: usize,
+    /// How many levels are tried for a single seed before giving up.
+    pub max_attempts: usize,
+}
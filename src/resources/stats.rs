@@ -0,0 +1,25 @@
+use bevy::ecs::resource::Resource;
+
+use crate::resources::levels::LevelState;
+
+/// What the player has done so far in the current level, shown in the HUD.
+#[derive(Resource, Clone, Default, Debug)]
+pub struct LevelStats {
+    /// The number of moves that have been made and not undone.
+    pub moves: usize,
+    pub undos: usize,
+    pub elapsed_secs: f32,
+    /// The level is lost once this many moves have been made without collecting every goal.
+    pub max_moves: Option<usize>,
+}
+
+impl LevelStats {
+    /// Whether the move limit has been reached. A move only counts once its turn has resolved,
+    /// so that the move that collects the last goal does not lose the level while it is played.
+    pub fn is_out_of_moves(&self, level_state: LevelState) -> bool {
+        level_state == LevelState::WaitingForPlayerInput
+            && self
+                .max_moves
+                .is_some_and(|max_moves| self.moves >= max_moves)
+    }
+}
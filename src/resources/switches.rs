@@ -0,0 +1,116 @@
+use std::collections::{BTreeSet, HashSet};
+
+use bevy::ecs::resource::Resource;
+
+use crate::components::{level::Level, switch::SwitchKind};
+
+type Position = (isize, isize, isize);
+
+/// Which switches are on, by id. This is updated at the start of the level effects of every turn.
+#[derive(Resource, Clone, Default, Debug, PartialEq, Eq, Hash)]
+pub struct SwitchStates {
+    pub on: BTreeSet<String>,
+    /// The positions of the switches that something was standing on after the last turn,
+    /// so that toggle switches only flip when something arrives on them.
+    pub pressed: BTreeSet<Position>,
+}
+
+impl SwitchStates {
+    /// The states at the start of the level, given the switches that are on and where the players
+    /// and goals start. Plates that something starts on are pressed right away.
+    pub fn new(
+        on: BTreeSet<String>,
+        switches: &[(&str, SwitchKind, Position)],
+        occupied: &HashSet<Position>,
+    ) -> Self {
+        let mut switch_states = Self {
+            on,
+            // Nothing arrives on a switch before the first turn, so no toggle switch flips.
+            pressed: switches
+                .iter()
+                .filter(|(_, _, position)| occupied.contains(position))
+                .map(|(_, _, position)| *position)
+                .collect(),
+        };
+        switch_states.update_plates(switches);
+
+        switch_states
+    }
+
+    /// The states at the start of the level. Switches lie on listed tiles only.
+    pub fn from_level(level: &Level) -> Result<Self, String> {
+        let switches = level
+            .layers
+            .iter()
+            .flat_map(|layer| &layer.tiles)
+            .filter_map(|tile| {
+                let id = tile.switch.as_deref()?;
+                Some(
+                    level
+                        .get_switch(id)
+                        .map(|switch| (id, switch.kind, (tile.q, tile.height, tile.r))),
+                )
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(Self::new(
+            level.get_switches_on(),
+            &switches,
+            &level.get_carriable_positions(),
+        ))
+    }
+
+    pub fn is_on(&self, id: &str) -> bool {
+        self.on.contains(id)
+    }
+
+    /// Presses the switches that something is standing on, and releases the others.
+    pub fn update(
+        &mut self,
+        switches: &[(&str, SwitchKind, Position)],
+        occupied: &HashSet<Position>,
+    ) {
+        let pressed: BTreeSet<Position> = switches
+            .iter()
+            .filter(|(_, _, position)| occupied.contains(position))
+            .map(|(_, _, position)| *position)
+            .collect();
+
+        let flipped_toggles: BTreeSet<&str> = switches
+            .iter()
+            .filter(|(_, kind, position)| {
+                *kind == SwitchKind::Toggle
+                    && pressed.contains(position)
+                    && !self.pressed.contains(position)
+            })
+            .map(|(id, _, _)| *id)
+            .collect();
+
+        for id in flipped_toggles {
+            if !self.on.remove(id) {
+                self.on.insert(id.to_owned());
+            }
+        }
+
+        self.pressed = pressed;
+        self.update_plates(switches);
+    }
+
+    /// A plate is on while any plate with its id is pressed.
+    fn update_plates(&mut self, switches: &[(&str, SwitchKind, Position)]) {
+        for (id, kind, _) in switches {
+            if *kind != SwitchKind::Plate {
+                continue;
+            }
+
+            if switches
+                .iter()
+                .any(|(other_id, _, position)| other_id == id && self.pressed.contains(position))
+            {
+                self.on.insert(id.to_string());
+            } else {
+                self.on.remove(*id);
+            }
+        }
+    }
+}
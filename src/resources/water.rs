@@ -0,0 +1,31 @@
+use bevy::ecs::resource::Resource;
+
+use crate::{components::tile::MovementMap, resources::switches::SwitchStates};
+
+/// Where the water surface is drawn in levels without water, between the tiles of height -1 and 0.
+pub const WATER_SURFACE_HEIGHT: f32 = 0.3;
+
+/// The water that floods the level. Tiles below its height cannot be walked on,
+/// and players that end up below it drown.
+#[derive(Resource, Clone, Default)]
+pub struct Water {
+    /// `None` if the level has no water, in which case the water is only decoration.
+    pub height: Option<isize>,
+    /// How the height changes in each turn. Only the vertical part of each offset is used.
+    pub movement_map: MovementMap,
+}
+
+impl Water {
+    pub fn is_under_water(&self, y: isize) -> bool {
+        self.height.is_some_and(|height| y < height)
+    }
+
+    /// The change of the height in the level effects of this turn, if the water follows its movement map.
+    pub fn next_step(&self, switch_states: &SwitchStates) -> Option<isize> {
+        if self.height.is_none() || !self.movement_map.is_running(switch_states) {
+            return None;
+        }
+
+        self.movement_map.next_offset().map(|offset| offset.1)
+    }
+}
@@ -0,0 +1,310 @@
+//! The rules that decide where objects go during a turn. The systems that play a turn take them one
+//! step at a time, while `Simulation` follows them to the end of each move to solve levels, so that
+//! both always agree.
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use crate::components::{
+    enemy::EnemyBehaviour,
+    key::KeyColor,
+    tile::{TileEdges, is_edge_blocked},
+    tile_coordinates::MovementDirection,
+};
+
+pub type Position = (isize, isize, isize);
+
+/// The furthest an object can slide over ice in one move before giving up.
+const MAX_SLIDE_LENGTH: usize = 64;
+
+pub fn add(a: Position, b: Position) -> Position {
+    (a.0 + b.0, a.1 + b.1, a.2 + b.2)
+}
+
+/// The neighbouring position in the direction, at the same height.
+pub fn step(position: Position, direction: MovementDirection) -> Position {
+    let offset = direction.get_tile_coordinate_offset();
+    add(position, (offset.x as isize, 0, offset.z as isize))
+}
+
+/// The tiles that stand still, as the objects that move over them see them.
+#[derive(Clone, Default)]
+pub struct Board {
+    /// Every tile, including the ones below the top of a pillar.
+    pub tiles: HashSet<Position>,
+    pub top_tiles: HashSet<Position>,
+    pub icy_tiles: HashSet<Position>,
+    /// The walls, one-way directions and locked gates of the tiles that have any.
+    pub edges: HashMap<Position, TileEdges>,
+    /// For the position of each teleporter pad, the position of the pad it is paired with.
+    pub teleporter_exits: HashMap<Position, Position>,
+    /// For the position of each conveyor, the direction in which it pushes.
+    pub conveyors: HashMap<Position, MovementDirection>,
+    /// The keys that lie on the tiles and have not been collected yet.
+    pub keys_on_tiles: HashMap<Position, KeyColor>,
+    /// The height of the water, or `None` if the level has no water.
+    pub water_height: Option<isize>,
+}
+
+impl Board {
+    pub fn add_tile(&mut self, position: Position, is_on_top: bool, edges: Option<&TileEdges>) {
+        self.tiles.insert(position);
+
+        if is_on_top {
+            self.top_tiles.insert(position);
+        }
+
+        if let Some(edges) = edges.filter(|edges| !edges.is_empty()) {
+            self.edges.insert(position, edges.clone());
+        }
+    }
+
+    pub fn is_under_water(&self, y: isize) -> bool {
+        self.water_height.is_some_and(|height| y < height)
+    }
+
+    /// Anything that falls below this has fallen into the water.
+    pub fn lowest_y(&self) -> isize {
+        self.tiles.iter().map(|tile| tile.1).min().unwrap_or(0)
+    }
+
+    /// Whether an object can step from the position in the direction. There must be a tile at the
+    /// destination, or below it, that is not under water, and no wall, one-way tile or locked gate
+    /// in the way. Only players hold keys.
+    pub fn can_step(
+        &self,
+        from: Position,
+        direction: MovementDirection,
+        keys: &[KeyColor],
+    ) -> bool {
+        self.get_landing(from, direction).is_some()
+            && !is_edge_blocked(&self.edges, from, direction, keys)
+    }
+
+    /// The highest tile at the destination of a step, or below it, that is not under water.
+    /// Unless it slides on, this is where an object that takes the step ends up.
+    pub fn get_landing(&self, from: Position, direction: MovementDirection) -> Option<Position> {
+        let destination = step(from, direction);
+
+        self.top_tiles
+            .iter()
+            .filter(|tile| {
+                (tile.0, tile.2) == (destination.0, destination.2)
+                    && tile.1 <= from.1
+                    && !self.is_under_water(tile.1)
+            })
+            .max_by_key(|tile| tile.1)
+            .copied()
+    }
+
+    /// The step that each enemy takes, if any, by its behaviour. Enemies never step onto a hex
+    /// where another enemy stands or where an enemy that picked its step before it lands. They
+    /// pick their steps in the order of their positions, so that this does not depend on the order
+    /// in which they are given.
+    pub fn get_enemy_steps(
+        &self,
+        enemies: &[(Position, &EnemyBehaviour, usize)],
+        players: &[Position],
+    ) -> Vec<Option<MovementDirection>> {
+        let mut order: Vec<usize> = (0..enemies.len()).collect();
+        order.sort_by_key(|index| enemies[*index].0);
+
+        let mut reserved: Vec<Position> = enemies.iter().map(|(position, ..)| *position).collect();
+        let mut steps = vec![None; enemies.len()];
+
+        for index in order {
+            let (position, behaviour, route_index) = enemies[index];
+            let can_move = |direction: MovementDirection| {
+                self.can_step(position, direction, &[])
+                    && self
+                        .get_landing(position, direction)
+                        .is_some_and(|landing| !reserved.contains(&landing))
+            };
+
+            steps[index] =
+                behaviour.get_direction(route_index, position, players.iter().copied(), can_move);
+
+            if let Some(direction) = steps[index] {
+                reserved.extend(self.get_landing(position, direction));
+            }
+        }
+
+        steps
+    }
+
+    /// Whether an object that ends a step on the position while moving in the direction slides on.
+    /// It cannot slide into a higher tile, through walls or out of one-way tiles the wrong way.
+    /// Players slide through the gates that they hold a key for.
+    pub fn slides_on(
+        &self,
+        position: Position,
+        direction: MovementDirection,
+        keys: &[KeyColor],
+    ) -> bool {
+        self.icy_tiles.contains(&position)
+            && !self
+                .tiles
+                .contains(&add(step(position, direction), (0, 1, 0)))
+            && !is_edge_blocked(&self.edges, position, direction, keys)
+    }
+
+    /// The keys that a player holds once it has arrived at the position, before it moves on. A
+    /// locked gate there uses up its key, and a key that lies there is picked up, like `open_gates`
+    /// and `collect_keys` do at the end of the step.
+    pub fn arrive(&self, position: Position, keys: &mut Vec<KeyColor>) {
+        if let Some(gate) = self.edges.get(&position).and_then(|edges| edges.gate)
+            && let Some(index) = keys.iter().position(|key| *key == gate)
+        {
+            keys.remove(index);
+        }
+
+        keys.extend(self.keys_on_tiles.get(&position));
+    }
+
+    /// Where an object with gravity that ends a step on the position comes out. Pads only
+    /// transport objects if nothing is standing on the other pad.
+    pub fn teleport(&self, position: Position, is_occupied: impl Fn(Position) -> bool) -> Position {
+        match self.teleporter_exits.get(&position) {
+            Some(&exit) if !is_occupied(exit) => exit,
+            _ => position,
+        }
+    }
+
+    /// Whether an object at the position stands on a tile or on one of the resting objects.
+    pub fn is_held_up(&self, position: Position, resting: &[Position]) -> bool {
+        self.top_tiles.contains(&position) || resting.contains(&position)
+    }
+
+    /// The direction in which the conveyor at the position pushes, unless a higher tile, a wall or
+    /// a gate that the object holds no key for is in the way.
+    pub fn get_conveyor_push(
+        &self,
+        position: Position,
+        keys: &[KeyColor],
+    ) -> Option<MovementDirection> {
+        let direction = *self.conveyors.get(&position)?;
+        let wall = add(step(position, direction), (0, 1, 0));
+
+        (!self.tiles.contains(&wall) && !is_edge_blocked(&self.edges, position, direction, keys))
+            .then_some(direction)
+    }
+
+    /// Moves an object one hex, after which it slides over ice, and, if it has gravity, is
+    /// transported by teleporter pads. Anything that stands still can be landed on. A player,
+    /// which is given with the keys it holds, uses and collects keys along the way. Returns every
+    /// position that the object passes through, or `None` if it keeps on sliding.
+    pub fn slide(
+        &self,
+        start: Position,
+        direction: MovementDirection,
+        has_gravity: bool,
+        resting: &[Position],
+        mut keys: Option<Vec<KeyColor>>,
+    ) -> Option<Vec<Position>> {
+        let teleport = |position: Position| {
+            if has_gravity {
+                self.teleport(position, |exit| resting.contains(&exit))
+            } else {
+                position
+            }
+        };
+
+        let mut position = teleport(step(start, direction));
+        let mut path = vec![position];
+
+        loop {
+            if let Some(keys) = &mut keys {
+                self.arrive(position, keys);
+            }
+
+            if !self.slides_on(position, direction, keys.as_deref().unwrap_or_default()) {
+                break;
+            }
+
+            if path.len() > MAX_SLIDE_LENGTH {
+                return None;
+            }
+
+            position = teleport(step(position, direction));
+            path.push(position);
+        }
+
+        Some(path)
+    }
+
+    /// Lets an object at the end of the path fall until it lands on a tile or a resting object.
+    /// Adds the positions it falls through to the path, and returns `None` if it ends up in the
+    /// water.
+    pub fn fall(&self, path: &mut Vec<Position>, resting: &[Position]) -> Option<()> {
+        let lowest_y = self.lowest_y();
+        let mut position = *path.last()?;
+        let mut has_fallen = false;
+
+        while !self.is_held_up(position, resting) {
+            if position.1 < lowest_y {
+                return None;
+            }
+
+            position.1 -= 1;
+            path.push(position);
+            has_fallen = true;
+        }
+
+        // An object that falls onto a pad is transported as well, but does not slide on.
+        if has_fallen {
+            path.push(self.teleport(position, |exit| resting.contains(&exit)));
+        }
+
+        Some(())
+    }
+}
+
+/// A tile that follows its movement map this turn.
+pub struct TileStep<T, P> {
+    pub tile: T,
+    pub position: Position,
+    pub destination: Position,
+    /// The tiles of a platform move together. A tile without a platform moves on its own.
+    pub platform: Option<P>,
+}
+
+/// The tiles that cannot take their step this turn, because they would move into a tile that
+/// stays where it is. If one tile of a platform is blocked, the whole platform is. A blocked tile
+/// stays where it is, which can block the tiles moving into it in turn.
+pub fn get_blocked_tiles<T: Copy + Eq + Hash, P: Eq + Hash>(
+    moving_tiles: &[TileStep<T, P>],
+    mut still_tiles: HashSet<Position>,
+) -> HashSet<T> {
+    let mut blocked_tiles = HashSet::new();
+
+    loop {
+        let blocked_platforms: HashSet<&P> = moving_tiles
+            .iter()
+            .filter(|tile| still_tiles.contains(&tile.destination))
+            .filter_map(|tile| tile.platform.as_ref())
+            .collect();
+
+        let newly_blocked: Vec<&TileStep<T, P>> = moving_tiles
+            .iter()
+            .filter(|tile| {
+                !blocked_tiles.contains(&tile.tile)
+                    && (still_tiles.contains(&tile.destination)
+                        || tile
+                            .platform
+                            .as_ref()
+                            .is_some_and(|platform| blocked_platforms.contains(platform)))
+            })
+            .collect();
+
+        if newly_blocked.is_empty() {
+            return blocked_tiles;
+        }
+
+        for tile in newly_blocked {
+            blocked_tiles.insert(tile.tile);
+            still_tiles.insert(tile.position);
+        }
+    }
+}
@@ -0,0 +1,846 @@
+//! The turns of the game, without the ECS or any animations, so that levels can be solved quickly.
+//! The phases follow `player_controls`, `move_enemies` and `on_enemies_finished_moving`, and play
+//! every move by the shared `rules`.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::{
+    components::{
+        enemy::EnemyBehaviour,
+        key::KeyColor,
+        level::{Level, TileContents},
+        switch::{Bridge, SwitchKind},
+        teleporter::get_teleporter_exits,
+        tile::TileEdges,
+        tile_coordinates::MovementDirection,
+    },
+    resources::{editor::EditableLevel, switches::SwitchStates, water::Water},
+    rules::{Board, Position, TileStep, add, get_blocked_tiles},
+};
+
+struct MovingTile {
+    start: Position,
+    is_on_top: bool,
+    /// Walls move together with their tile.
+    edges: TileEdges,
+    /// The index of the platform that moves this tile.
+    platform: usize,
+}
+
+/// Tiles that move together. A moving tile that is not linked to others is a platform of its own.
+struct Platform {
+    movement_map: Vec<Position>,
+    /// The switch that must be on for the platform to move.
+    switch: Option<String>,
+}
+
+impl Platform {
+    fn step_at(&self, index: usize) -> Position {
+        self.movement_map[index % self.movement_map.len()]
+    }
+
+    fn is_running(&self, switch_states: &SwitchStates) -> bool {
+        self.switch
+            .as_ref()
+            .is_none_or(|switch| switch_states.is_on(switch))
+    }
+}
+
+struct BridgeTile {
+    position: Position,
+    is_on_top: bool,
+    is_icy: bool,
+    edges: TileEdges,
+    bridge: Bridge,
+}
+
+/// Follows `open_gates` and `collect_keys` for every position the player passes through.
+/// A key and a gate are never on the same tile.
+fn use_keys(
+    path: &[Position],
+    keys: &mut Vec<(Position, KeyColor)>,
+    inventory: &mut Vec<KeyColor>,
+    gates: &mut Vec<(Position, KeyColor)>,
+) {
+    for position in path {
+        if let Some(index) = gates
+            .iter()
+            .position(|(gate, color)| gate == position && inventory.contains(color))
+        {
+            let (_, color) = gates.remove(index);
+            let key = inventory.iter().position(|key| *key == color);
+            inventory.remove(key.expect("The key was just found"));
+        }
+
+        keys.retain(|(key, color)| {
+            if key == position {
+                inventory.push(*color);
+            }
+
+            key != position
+        });
+        inventory.sort();
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SimulationState {
+    pub player: Position,
+    /// The goals that have not been collected yet, in a fixed order.
+    pub goals: Vec<Position>,
+    /// How far each platform has moved from where it started. Blocked platforms wait until they
+    /// can take their step, so these do not follow from the number of turns alone.
+    pub platform_offsets: Vec<Position>,
+    /// How far along its movement map each platform is. Platforms stop while they are blocked or
+    /// wait for their switch.
+    pub platform_indices: Vec<usize>,
+    pub switch_states: SwitchStates,
+    /// The height of the water, or `None` if the level has no water.
+    pub water_height: Option<isize>,
+    /// How far along its movement map the water is.
+    pub water_index: usize,
+    /// The keys that have not been collected yet, in a fixed order.
+    pub keys: Vec<(Position, KeyColor)>,
+    /// The keys that the player holds, sorted like `KeyInventory`.
+    pub inventory: Vec<KeyColor>,
+    /// The gates that are still locked, in a fixed order.
+    pub gates: Vec<(Position, KeyColor)>,
+    /// The position of each enemy and how far along its route it is, in the same order as
+    /// `Simulation::enemies`.
+    pub enemies: Vec<(Position, usize)>,
+}
+
+pub struct Simulation {
+    /// The tiles that never move or disappear, together with the pads and conveyors.
+    static_tiles: Board,
+    moving_tiles: Vec<MovingTile>,
+    platforms: Vec<Platform>,
+    bridge_tiles: Vec<BridgeTile>,
+    /// The id, kind and position of every switch.
+    switches: Vec<(String, SwitchKind, Position)>,
+    /// How the water rises and falls. Its height is part of the state.
+    water: Water,
+    /// Solutions that take more moves than this lose the level.
+    max_moves: Option<usize>,
+    /// How each enemy moves. Their positions are part of the state.
+    enemies: Vec<EnemyBehaviour>,
+    pub initial_state: SimulationState,
+}
+
+impl Simulation {
+    pub fn from_level(level: &Level) -> Result<Self, String> {
+        Self::from_editable_level(&EditableLevel::from_level(level)?)
+    }
+
+    /// Places the tiles the same way as `Level::render_level` spawns them.
+    pub fn from_editable_level(level: &EditableLevel) -> Result<Self, String> {
+        let mut static_tiles = Board::default();
+        let mut moving_tiles = vec![];
+        let mut platforms: Vec<Platform> = vec![];
+        let mut platform_ids: HashMap<&String, usize> = HashMap::new();
+        let mut bridge_tiles = vec![];
+        let mut switches = vec![];
+        let mut teleporters = vec![];
+        let mut players = vec![];
+        let mut goals = vec![];
+        let mut keys = vec![];
+        let mut gates = vec![];
+        let mut enemies = vec![];
+
+        for layer in &level.layers {
+            for (row_index, row) in layer.cells.iter().enumerate() {
+                for (col_index, cell) in row.iter().enumerate() {
+                    let (x, z) = level.get_tile_coordinates(row_index, col_index);
+                    let y = cell.height.unwrap_or(0);
+
+                    for switch in [
+                        &cell.switch,
+                        &cell.triggered_by,
+                        &cell.bridge.as_ref().map(|bridge| bridge.switch.clone()),
+                    ]
+                    .into_iter()
+                    .flatten()
+                    {
+                        level.get_switch(switch)?;
+                    }
+
+                    if cell.modifiers.contains(&'P') {
+                        players.push((x, y, z));
+                    }
+
+                    if cell.modifiers.contains(&'G') {
+                        goals.push((x, y, z));
+                    }
+
+                    if cell.height.is_none() {
+                        continue;
+                    }
+
+                    if let Some(id) = &cell.switch {
+                        switches.push((id.clone(), level.get_switch(id)?.kind, (x, y, z)));
+                    }
+
+                    let lowest_tile_y = if layer.pillars { y.min(0) } else { y };
+                    let is_moving = cell.modifiers.contains(&'M') && !cell.movement_map.is_empty();
+                    let is_icy = cell.modifiers.contains(&'I');
+                    let get_edges = |tile_y: isize| {
+                        if tile_y == y {
+                            cell.edges.clone()
+                        } else {
+                            TileEdges::default()
+                        }
+                    };
+
+                    TileContents {
+                        is_moving,
+                        is_bridge: cell.bridge.is_some(),
+                        has_teleporter: cell.teleporter.is_some(),
+                        has_conveyor: cell.conveyor.is_some(),
+                        has_key: cell.key.is_some(),
+                        has_gate: cell.edges.gate.is_some(),
+                    }
+                    .validate()?;
+
+                    if let Some(id) = &cell.teleporter {
+                        teleporters.push((id.as_str(), (x, y, z)));
+                    }
+
+                    if let Some(direction) = cell.conveyor {
+                        static_tiles.conveyors.insert((x, y, z), direction);
+                    }
+
+                    if let Some(color) = cell.key {
+                        keys.push(((x, y, z), color));
+                    }
+
+                    if let Some(behaviour) = &cell.enemy {
+                        enemies.push(((x, y, z), behaviour.clone()));
+                    }
+
+                    // Gates are opened during the level, so they are part of the state.
+                    let mut cell_edges = cell.edges.clone();
+                    if let Some(color) = cell_edges.gate.take() {
+                        gates.push(((x, y, z), color));
+                    }
+
+                    if let Some(bridge) = &cell.bridge {
+                        for tile_y in lowest_tile_y..=y {
+                            bridge_tiles.push(BridgeTile {
+                                position: (x, tile_y, z),
+                                is_on_top: tile_y == y,
+                                is_icy: is_icy && tile_y == y,
+                                edges: get_edges(tile_y),
+                                bridge: bridge.clone(),
+                            });
+                        }
+
+                        continue;
+                    }
+
+                    if is_icy {
+                        static_tiles.icy_tiles.insert((x, y, z));
+                    }
+
+                    for tile_y in lowest_tile_y..=y {
+                        if is_moving {
+                            let linked_platform = cell
+                                .platform
+                                .as_ref()
+                                .and_then(|id| platform_ids.get(id).copied());
+
+                            let platform = linked_platform.unwrap_or_else(|| {
+                                platforms.push(Platform {
+                                    movement_map: cell.movement_map.clone(),
+                                    switch: cell.triggered_by.clone(),
+                                });
+
+                                if let Some(id) = &cell.platform {
+                                    platform_ids.insert(id, platforms.len() - 1);
+                                }
+
+                                platforms.len() - 1
+                            });
+
+                            moving_tiles.push(MovingTile {
+                                start: (x, tile_y, z),
+                                is_on_top: tile_y == y,
+                                edges: get_edges(tile_y),
+                                platform,
+                            });
+                        } else {
+                            static_tiles.add_tile(
+                                (x, tile_y, z),
+                                tile_y == y,
+                                (tile_y == y).then_some(&cell_edges),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        let [player] = players[..] else {
+            return Err(format!("Expected a single player, found {}", players.len()));
+        };
+
+        if let Some(switch) = &level.water.movement_map.switch {
+            level.get_switch(switch)?;
+        }
+
+        let switch_states = SwitchStates::new(
+            level
+                .switches
+                .iter()
+                .filter(|(_, switch)| switch.on)
+                .map(|(id, _)| id.clone())
+                .collect(),
+            &switches
+                .iter()
+                .map(|(id, kind, position)| (id.as_str(), *kind, *position))
+                .collect::<Vec<_>>(),
+            &players.iter().chain(&goals).copied().collect(),
+        );
+
+        goals.sort();
+        keys.sort();
+        gates.sort();
+        static_tiles.teleporter_exits = get_teleporter_exits(teleporters.into_iter())?;
+
+        Ok(Self {
+            static_tiles,
+            moving_tiles,
+            bridge_tiles,
+            switches,
+            water: level.water.clone(),
+            max_moves: level.metadata.max_moves,
+            initial_state: SimulationState {
+                player,
+                goals,
+                platform_offsets: vec![(0, 0, 0); platforms.len()],
+                platform_indices: vec![0; platforms.len()],
+                switch_states,
+                water_height: level.water.height,
+                water_index: 0,
+                keys,
+                inventory: vec![],
+                gates,
+                enemies: enemies.iter().map(|(position, _)| (*position, 0)).collect(),
+            },
+            enemies: enemies
+                .into_iter()
+                .map(|(_, behaviour)| behaviour)
+                .collect(),
+            platforms,
+        })
+    }
+
+    fn get_tile_position(&self, tile: &MovingTile, state: &SimulationState) -> Position {
+        add(tile.start, state.platform_offsets[tile.platform])
+    }
+
+    fn get_shown_bridge_tiles<'a>(
+        &'a self,
+        switch_states: &'a SwitchStates,
+    ) -> impl Iterator<Item = &'a BridgeTile> {
+        self.bridge_tiles
+            .iter()
+            .filter(|tile| tile.bridge.is_shown(switch_states))
+    }
+
+    /// The tiles during the turn, where bridges are shown according to the given switch states.
+    fn get_board(&self, state: &SimulationState, switch_states: &SwitchStates) -> Board {
+        let mut board = Board {
+            water_height: state.water_height,
+            ..self.static_tiles.clone()
+        };
+
+        for (position, color) in &state.gates {
+            board.edges.entry(*position).or_default().gate = Some(*color);
+        }
+
+        board.keys_on_tiles = state.keys.iter().copied().collect();
+
+        for tile in &self.moving_tiles {
+            let position = self.get_tile_position(tile, state);
+            board.add_tile(position, tile.is_on_top, Some(&tile.edges));
+        }
+
+        for tile in self.get_shown_bridge_tiles(switch_states) {
+            board.add_tile(tile.position, tile.is_on_top, Some(&tile.edges));
+
+            if tile.is_icy {
+                board.icy_tiles.insert(tile.position);
+            }
+        }
+
+        board
+    }
+
+    /// The platforms that cannot take their step this turn, following `get_blocked_tiles`.
+    /// The switches have already been updated for this turn.
+    fn get_blocked_platforms(
+        &self,
+        state: &SimulationState,
+        switch_states: &SwitchStates,
+    ) -> HashSet<usize> {
+        let get_step = |platform: usize| {
+            let platform_index = state.platform_indices[platform];
+            let platform = &self.platforms[platform];
+            platform
+                .is_running(switch_states)
+                .then(|| platform.step_at(platform_index))
+                .filter(|step| *step != (0, 0, 0))
+        };
+
+        let mut still_tiles: HashSet<Position> = self
+            .get_shown_bridge_tiles(switch_states)
+            .map(|tile| tile.position)
+            .chain(self.static_tiles.tiles.iter().copied())
+            .collect();
+        let mut moving_tiles = vec![];
+
+        for (index, tile) in self.moving_tiles.iter().enumerate() {
+            let position = self.get_tile_position(tile, state);
+
+            match get_step(tile.platform) {
+                Some(step) => moving_tiles.push(TileStep {
+                    tile: index,
+                    position,
+                    destination: add(position, step),
+                    platform: Some(tile.platform),
+                }),
+                None => {
+                    still_tiles.insert(position);
+                }
+            }
+        }
+
+        get_blocked_tiles(&moving_tiles, still_tiles)
+            .into_iter()
+            .map(|tile| self.moving_tiles[tile].platform)
+            .collect()
+    }
+
+    /// Moves an object one hex, after which it slides over ice, and, if it has gravity, is transported
+    /// by teleporter pads and falls until it lands on something. Anything that stands still can be
+    /// landed on, including goals. Only the player is given with the keys it holds. Returns every
+    /// position that the object passes through, or `None` if it ends up in the water.
+    fn move_object(
+        &self,
+        board: &Board,
+        start: Position,
+        direction: MovementDirection,
+        has_gravity: bool,
+        resting: &[Position],
+        keys: Option<Vec<KeyColor>>,
+    ) -> Option<Vec<Position>> {
+        let mut path = board.slide(start, direction, has_gravity, resting, keys)?;
+
+        if has_gravity {
+            board.fall(&mut path, resting)?;
+        }
+
+        Some(path)
+    }
+
+    /// Plays a single turn. Returns `None` if the move is not allowed,
+    /// or if the player ends up in the water or is caught by an enemy.
+    pub fn play_move(
+        &self,
+        state: &SimulationState,
+        direction: MovementDirection,
+    ) -> Option<SimulationState> {
+        let mut board = self.get_board(state, &state.switch_states);
+
+        if !board.can_step(state.player, direction, &state.inventory) {
+            return None;
+        }
+
+        // The player can land on goals and enemies, and is caught if it does the latter.
+        let resting: Vec<Position> = state
+            .goals
+            .iter()
+            .copied()
+            .chain(state.enemies.iter().map(|(enemy, _)| *enemy))
+            .collect();
+        let path = self.move_object(
+            &board,
+            state.player,
+            direction,
+            true,
+            &resting,
+            Some(state.inventory.clone()),
+        )?;
+        let position = *path.last()?;
+
+        // The player drowns if it slides or falls into the water.
+        if board.is_under_water(position.1)
+            || state.enemies.iter().any(|(enemy, _)| *enemy == position)
+        {
+            return None;
+        }
+        let mut goals = state.goals.clone();
+        goals.retain(|goal| !path.contains(goal));
+
+        let mut keys = state.keys.clone();
+        let mut inventory = state.inventory.clone();
+        let mut gates = state.gates.clone();
+        use_keys(&path, &mut keys, &mut inventory, &mut gates);
+
+        for (gate, _) in state.gates.iter().filter(|gate| !gates.contains(gate)) {
+            if let Some(edges) = board.edges.get_mut(gate) {
+                edges.gate = None;
+            }
+        }
+
+        let mut enemies = self.move_enemies(&board, state, position, &goals)?;
+
+        // The level effects start with pressing the switches that anything ended up on.
+        let occupied: HashSet<Position> = goals
+            .iter()
+            .copied()
+            .chain([position])
+            .chain(enemies.iter().map(|(enemy, _)| *enemy))
+            .collect();
+        let switches: Vec<(&str, SwitchKind, Position)> = self
+            .switches
+            .iter()
+            .map(|(id, kind, position)| (id.as_str(), *kind, *position))
+            .collect();
+        let mut switch_states = state.switch_states.clone();
+        switch_states.update(&switches, &occupied);
+
+        // The water rises or falls together with the tiles.
+        let water_map = &self.water.movement_map;
+        let is_water_running = state.water_height.is_some()
+            && !water_map.map.is_empty()
+            && water_map.is_running(&switch_states);
+        let (water_height, water_index) = if is_water_running {
+            let step = water_map.map[state.water_index % water_map.map.len()].1;
+            (
+                state.water_height.map(|height| height + step),
+                (state.water_index + 1) % water_map.map.len(),
+            )
+        } else {
+            (state.water_height, state.water_index)
+        };
+
+        // Moving tiles carry along anything that is standing on them, unless their platform
+        // is blocked or waits for its switch.
+        let blocked_platforms = self.get_blocked_platforms(state, &switch_states);
+        let steps: Vec<Position> = self
+            .platforms
+            .iter()
+            .enumerate()
+            .map(|(index, platform)| {
+                if !platform.is_running(&switch_states) || blocked_platforms.contains(&index) {
+                    (0, 0, 0)
+                } else {
+                    platform.step_at(state.platform_indices[index])
+                }
+            })
+            .collect();
+
+        let mut player = position;
+        let mut carried_goals = goals.clone();
+        let mut carried_enemies = enemies.clone();
+
+        for tile in &self.moving_tiles {
+            let step = steps[tile.platform];
+            let tile_position = self.get_tile_position(tile, state);
+
+            if position == tile_position {
+                player = add(position, step);
+            }
+
+            for (carried_enemy, (enemy, _)) in carried_enemies.iter_mut().zip(&enemies) {
+                if *enemy == tile_position {
+                    carried_enemy.0 = add(*enemy, step);
+                }
+            }
+
+            for (carried_goal, goal) in carried_goals.iter_mut().zip(&goals) {
+                if *goal == tile_position {
+                    *carried_goal = add(*goal, step);
+                }
+            }
+        }
+
+        // At the same time, conveyors push anything that rests on them. Conveyors never move,
+        // so nothing is both carried and pushed.
+        if !self.static_tiles.conveyors.is_empty() {
+            let board = self.get_board(
+                &SimulationState {
+                    keys: keys.clone(),
+                    gates: gates.clone(),
+                    ..state.clone()
+                },
+                &switch_states,
+            );
+
+            for (carried_goal, goal) in carried_goals.iter_mut().zip(&goals) {
+                if let Some(push) = board.get_conveyor_push(*goal, &[]) {
+                    *carried_goal = *self
+                        .move_object(&board, *goal, push, false, &goals, None)?
+                        .last()?;
+                }
+            }
+
+            for (carried_enemy, (enemy, _)) in carried_enemies.iter_mut().zip(&enemies) {
+                if let Some(push) = board.get_conveyor_push(*enemy, &[]) {
+                    let path = board.slide(*enemy, push, true, &goals, None)?;
+                    carried_enemy.0 = *path.last()?;
+                }
+            }
+
+            if let Some(push) = board.get_conveyor_push(position, &inventory) {
+                let path = board.slide(position, push, true, &goals, Some(inventory.clone()))?;
+                player = *path.last()?;
+                use_keys(&path, &mut keys, &mut inventory, &mut gates);
+
+                carried_goals = carried_goals
+                    .into_iter()
+                    .zip(&goals)
+                    .filter(|(_, goal)| !path.contains(goal))
+                    .map(|(carried_goal, _)| carried_goal)
+                    .collect();
+            }
+        }
+
+        let platform_offsets: Vec<Position> = state
+            .platform_offsets
+            .iter()
+            .zip(steps)
+            .map(|(offset, step)| add(*offset, step))
+            .collect();
+
+        // Anything left without a tile or a resting object below it falls once every tile has
+        // arrived where it is going. Objects that fall themselves cannot hold up anything else.
+        let board = self.get_board(
+            &SimulationState {
+                platform_offsets: platform_offsets.clone(),
+                gates: gates.clone(),
+                ..state.clone()
+            },
+            &switch_states,
+        );
+
+        let is_supported = |object: &Position| board.is_held_up(*object, &carried_goals);
+        let resting: Vec<Position> = carried_goals
+            .iter()
+            .copied()
+            .chain([player])
+            .chain(carried_enemies.iter().map(|(enemy, _)| *enemy))
+            .filter(is_supported)
+            .collect();
+
+        for object in [&mut player]
+            .into_iter()
+            .chain(carried_enemies.iter_mut().map(|(enemy, _)| enemy))
+        {
+            let mut path = vec![*object];
+            board.fall(&mut path, &resting)?;
+            *object = *path.last()?;
+        }
+
+        carried_goals.retain(|goal| *goal != player);
+        carried_goals.sort();
+        use_keys(&[player], &mut keys, &mut inventory, &mut gates);
+        enemies = carried_enemies;
+
+        // The player drowns if the water rises above it, or if it is carried or pushed into the water.
+        // It is also caught if it is carried or pushed onto an enemy, or the other way around.
+        if water_height.is_some_and(|height| player.1 < height)
+            || enemies.iter().any(|(enemy, _)| *enemy == player)
+        {
+            return None;
+        }
+
+        Some(SimulationState {
+            player,
+            goals: carried_goals,
+            platform_offsets,
+            platform_indices: self
+                .platforms
+                .iter()
+                .zip(&state.platform_indices)
+                .enumerate()
+                .map(|(platform_index, (platform, index))| {
+                    if platform.is_running(&switch_states)
+                        && !blocked_platforms.contains(&platform_index)
+                    {
+                        (index + 1) % platform.movement_map.len()
+                    } else {
+                        *index
+                    }
+                })
+                .collect(),
+            switch_states,
+            water_height,
+            water_index,
+            keys,
+            inventory,
+            gates,
+            enemies,
+        })
+    }
+
+    /// The enemy phase, following `move_enemies`. Every enemy decides where to go from where the
+    /// enemies started, and then moves like the player does, landing on anything that stands still.
+    /// Returns `None` if an enemy passes through the player or ends up in the water.
+    fn move_enemies(
+        &self,
+        board: &Board,
+        state: &SimulationState,
+        player: Position,
+        goals: &[Position],
+    ) -> Option<Vec<(Position, usize)>> {
+        let directions = board.get_enemy_steps(
+            &state
+                .enemies
+                .iter()
+                .zip(&self.enemies)
+                .map(|((enemy, route_index), behaviour)| (*enemy, behaviour, *route_index))
+                .collect::<Vec<_>>(),
+            &[player],
+        );
+
+        let resting: Vec<Position> = goals
+            .iter()
+            .copied()
+            .chain([player])
+            .chain(
+                state
+                    .enemies
+                    .iter()
+                    .zip(&directions)
+                    .filter(|(_, direction)| direction.is_none())
+                    .map(|((enemy, _), _)| *enemy),
+            )
+            .collect();
+
+        state
+            .enemies
+            .iter()
+            .zip(&self.enemies)
+            .zip(directions)
+            .map(|(((enemy, route_index), behaviour), direction)| {
+                let route_index = behaviour.next_route_index(*route_index);
+                let Some(direction) = direction else {
+                    return Some((*enemy, route_index));
+                };
+
+                let path = self.move_object(board, *enemy, direction, true, &resting, None)?;
+
+                (!path.contains(&player)).then_some((*path.last()?, route_index))
+            })
+            .collect()
+    }
+
+    /// The fewest moves needed to reach each position the player can stand on,
+    /// searching at most `max_states` states.
+    pub fn get_move_counts(&self, max_states: usize) -> HashMap<Position, usize> {
+        let mut move_counts = HashMap::from([(self.initial_state.player, 0)]);
+        let mut visited = HashSet::from([self.initial_state.clone()]);
+        let mut queue = VecDeque::from([(self.initial_state.clone(), 0)]);
+
+        while let Some((state, moves)) = queue.pop_front() {
+            for direction in MovementDirection::ALL {
+                let Some(next) = self.play_move(&state, direction) else {
+                    continue;
+                };
+
+                if visited.len() >= max_states {
+                    return move_counts;
+                }
+
+                move_counts.entry(next.player).or_insert(moves + 1);
+
+                if visited.insert(next.clone()) {
+                    queue.push_back((next, moves + 1));
+                }
+            }
+        }
+
+        move_counts
+    }
+
+    /// Finds the shortest sequence of moves that collects all goals within the move limit of
+    /// the level, searching at most `max_states` states.
+    pub fn solve(&self, max_states: usize) -> Option<Vec<MovementDirection>> {
+        let path = self.search(&self.initial_state, max_states, |state| {
+            state.goals.is_empty()
+        })?;
+
+        self.max_moves
+            .is_none_or(|max_moves| path.len() <= max_moves)
+            .then_some(path)
+    }
+
+    /// Finds the shortest sequence of moves after which the player stands at the destination,
+    /// searching at most `max_states` states.
+    pub fn find_path(
+        &self,
+        start: &SimulationState,
+        destination: Position,
+        max_states: usize,
+    ) -> Option<Vec<MovementDirection>> {
+        self.search(start, max_states, |state| state.player == destination)
+    }
+
+    /// The state after playing the moves from the start of the level, or `None` if one of them
+    /// is not allowed or loses the level.
+    pub fn replay(
+        &self,
+        mut moves: impl Iterator<Item = MovementDirection>,
+    ) -> Option<SimulationState> {
+        moves.try_fold(self.initial_state.clone(), |state, direction| {
+            self.play_move(&state, direction)
+        })
+    }
+
+    /// A breadth-first search for the fewest moves from the start to a state that is done.
+    fn search(
+        &self,
+        start: &SimulationState,
+        max_states: usize,
+        is_done: impl Fn(&SimulationState) -> bool,
+    ) -> Option<Vec<MovementDirection>> {
+        let mut previous: HashMap<SimulationState, Option<(SimulationState, MovementDirection)>> =
+            HashMap::from([(start.clone(), None)]);
+        let mut queue = VecDeque::from([start.clone()]);
+
+        while let Some(state) = queue.pop_front() {
+            if is_done(&state) {
+                let mut path = vec![];
+                let mut current = &state;
+
+                while let Some(Some((previous_state, direction))) = previous.get(current) {
+                    path.push(*direction);
+                    current = previous_state;
+                }
+
+                // The search is breadth-first, so no other path is shorter.
+                path.reverse();
+                return Some(path);
+            }
+
+            for direction in MovementDirection::ALL {
+                let Some(next) = self.play_move(&state, direction) else {
+                    continue;
+                };
+
+                if previous.len() >= max_states {
+                    return None;
+                }
+
+                if !previous.contains_key(&next) {
+                    previous.insert(next.clone(), Some((state.clone(), direction)));
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        None
+    }
+}
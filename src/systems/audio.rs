@@ -0,0 +1,58 @@
+use bevy::{audio::Volume, prelude::*};
+
+use crate::{
+    components::{
+        audio::{AmbientLoop, PlaySoundEffect},
+        level::LevelStarted,
+    },
+    resources::{audio::Sounds, settings::Settings},
+};
+
+pub fn play_sound_effect(
+    played: On<PlaySoundEffect>,
+    sounds: Res<Sounds>,
+    settings: Res<Settings>,
+    mut commands: Commands,
+) {
+    let Some(sound) = sounds.effects.get(&played.effect) else {
+        return;
+    };
+
+    commands.spawn((
+        AudioPlayer(sound.clone()),
+        PlaybackSettings::DESPAWN.with_volume(Volume::Linear(settings.audio.effects_volume)),
+    ));
+}
+
+/// Starts the ambient loop of the biome of the level. The loop that is already playing carries on
+/// if the level has the same biome, so that restarting a level does not restart it.
+pub fn play_ambient_loop(
+    started: On<LevelStarted>,
+    ambient_loops: Query<(&AmbientLoop, Entity)>,
+    sounds: Res<Sounds>,
+    settings: Res<Settings>,
+    mut commands: Commands,
+) {
+    if ambient_loops
+        .iter()
+        .any(|(ambient_loop, _)| ambient_loop.biome == started.biome)
+    {
+        return;
+    }
+
+    for (_, entity) in ambient_loops {
+        commands.entity(entity).despawn();
+    }
+
+    let Some(sound) = sounds.ambient_loops.get(&started.biome) else {
+        return;
+    };
+
+    commands.spawn((
+        AmbientLoop {
+            biome: started.biome,
+        },
+        AudioPlayer(sound.clone()),
+        PlaybackSettings::LOOP.with_volume(Volume::Linear(settings.audio.ambient_volume)),
+    ));
+}
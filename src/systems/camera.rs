@@ -1,12 +1,29 @@
 use std::f32::consts::TAU;
 
-use bevy::prelude::*;
+use bevy::{input::mouse::AccumulatedMouseScroll, prelude::*};
 
-use crate::components::camera::{CameraAngle, RotationDirection};
+use crate::{
+    components::{
+        camera::{CameraAngle, RotationDirection},
+        level::LevelMetadataCamera,
+        player::Player,
+    },
+    resources::{
+        camera::CameraFraming,
+        input::{ActionInput, InputAction},
+        replay::{ReplayRecorder, ReplayStep},
+        settings::Settings,
+    },
+};
 
 pub fn move_camera(
-    mut camera: Single<(&mut Transform, &mut CameraAngle)>,
-    keys: Res<ButtonInput<KeyCode>>,
+    mut camera: Single<(&mut Transform, &mut CameraAngle, &Projection), Without<Player>>,
+    players: Query<&Transform, With<Player>>,
+    actions: Res<ActionInput>,
+    mut recorder: ResMut<ReplayRecorder>,
+    mouse_scroll: Res<AccumulatedMouseScroll>,
+    framing: Res<CameraFraming>,
+    settings: Res<Settings>,
     timer: Res<Time>,
 ) {
     if let Some(value) = camera.1.rotation_animation_percentage {
@@ -24,17 +41,99 @@ pub fn move_camera(
             camera.1.rotation_direction = None;
         }
     } else {
-        if keys.just_pressed(KeyCode::ArrowLeft) {
+        if actions.just_pressed(InputAction::RotateCW) {
             camera.1.rotation_direction = Some(RotationDirection::Clockwise);
             camera.1.rotation_animation_percentage = Some(0.0);
         }
-        if keys.just_pressed(KeyCode::ArrowRight) {
+        if actions.just_pressed(InputAction::RotateCCW) {
             camera.1.rotation_direction = Some(RotationDirection::CounterClockwise);
             camera.1.rotation_animation_percentage = Some(0.0);
         }
+        if let Some(rotation_direction) = camera.1.rotation_direction {
+            recorder.steps.push(ReplayStep::Rotate(rotation_direction));
+        }
+    }
+
+    // Scrolling up or zooming in means moving the camera closer.
+    let mut zoom_steps = if mouse_scroll.delta.y > 0.0 {
+        -1.0
+    } else if mouse_scroll.delta.y < 0.0 {
+        1.0
+    } else {
+        0.0
+    };
+    if actions.just_pressed(InputAction::ZoomIn) {
+        zoom_steps -= 1.0;
+    }
+    if actions.just_pressed(InputAction::ZoomOut) {
+        zoom_steps += 1.0;
     }
 
-    let rotation_radius = 10.0;
+    camera.1.zoom = (camera.1.zoom * (1.0 + settings.camera.zoom_speed).powf(zoom_steps))
+        .clamp(settings.camera.min_zoom, settings.camera.max_zoom);
+
+    let mut pitch_direction = 0.0;
+    if actions.pressed(InputAction::TiltUp) {
+        pitch_direction += 1.0;
+    }
+    if actions.pressed(InputAction::TiltDown) {
+        pitch_direction -= 1.0;
+    }
+
+    camera.1.pitch = (camera.1.pitch
+        + pitch_direction * settings.camera.pitch_speed.to_radians() * timer.delta_secs())
+    .clamp(
+        settings.camera.min_pitch.to_radians(),
+        settings.camera.max_pitch.to_radians(),
+    );
+
+    let view_radius = match framing.mode {
+        LevelMetadataCamera::Fixed => {
+            camera.1.focus = framing.center;
+            framing.radius
+        }
+        LevelMetadataCamera::Follow => {
+            let player_centroid = match players.is_empty() {
+                true => framing.center,
+                false => {
+                    players
+                        .iter()
+                        .map(|transform| transform.translation)
+                        .sum::<Vec3>()
+                        / players.iter().len() as f32
+                }
+            };
+
+            if framing.is_changed() {
+                camera.1.focus = player_centroid;
+            }
+
+            // Only follow once the players leave the dead zone, and then
+            // only as far as is needed to get them back inside of it.
+            let offset = player_centroid - camera.1.focus;
+            let excess = offset.length() - settings.camera.follow.dead_zone;
+
+            if excess > 0.0 {
+                let target = camera.1.focus + offset.normalize() * excess;
+                camera.1.focus = camera.1.focus.lerp(
+                    target,
+                    (settings.camera.follow.speed * timer.delta_secs()).clamp(0.0, 1.0),
+                );
+            }
+
+            settings.camera.follow.view_radius
+        }
+    };
+
+    // Place the camera at the distance where the view radius fits in the view.
+    let fov = match camera.2 {
+        Projection::Perspective(perspective) => perspective
+            .fov
+            .min(2.0 * ((perspective.fov / 2.0).tan() * perspective.aspect_ratio).atan()),
+        _ => TAU / 8.0,
+    };
+    let distance = camera.1.zoom * view_radius / (fov / 2.0).sin();
+
     let angle = TAU
         * ((camera.1.total_6th_rotations as f32
             + (camera.1.rotation_animation_percentage.unwrap_or_default()
@@ -46,10 +145,15 @@ pub fn move_camera(
             + 1.5)
             / 6.0);
 
-    let new_x = rotation_radius * angle.cos();
-    let new_z = rotation_radius * angle.sin();
+    let horizontal_distance = distance * camera.1.pitch.cos();
+
+    let focus = camera.1.focus;
 
-    camera.0.translation.x = new_x;
-    camera.0.translation.z = new_z;
-    camera.0.look_at(Vec3::ZERO, Vec3::Y);
+    camera.0.translation = focus
+        + Vec3::new(
+            horizontal_distance * angle.cos(),
+            distance * camera.1.pitch.sin(),
+            horizontal_distance * angle.sin(),
+        );
+    camera.0.look_at(focus, Vec3::Y);
 }
@@ -0,0 +1,59 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+
+use crate::{
+    components::{
+        conveyor::{Conveyor, ConveyorArrow},
+        tile_coordinates::tile_coordinates_to_transform_coordinates,
+    },
+    resources::effects::GlobalEffects,
+};
+
+/// How many times per second an arrow moves across its conveyor.
+const ARROW_SPEED: f32 = 0.75;
+
+/// How far an arrow moves across its conveyor.
+const ARROW_DISTANCE: f32 = 1.0;
+
+/// Just above the top of an icy tile, so that arrows can be seen on ice as well.
+const ARROW_HEIGHT: f32 = 0.11;
+
+pub fn add_conveyor_arrows(
+    mut commands: Commands,
+    conveyors: Query<(Entity, &Conveyor), Added<Conveyor>>,
+    effects: Res<GlobalEffects>,
+) {
+    let (Some(mesh), Some(material)) = (
+        &effects.conveyor_arrow_mesh,
+        &effects.conveyor_arrow_material,
+    ) else {
+        return;
+    };
+
+    for (entity, conveyor) in conveyors {
+        let direction = tile_coordinates_to_transform_coordinates(
+            &conveyor.direction.get_tile_coordinate_offset(),
+        )
+        .normalize();
+
+        let mut transform = Transform::default().looking_to(direction, Vec3::Y);
+        transform.rotate_local_x(-FRAC_PI_2);
+
+        commands.entity(entity).with_child((
+            ConveyorArrow { direction },
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            transform,
+        ));
+    }
+}
+
+pub fn animate_conveyor_arrows(time: Res<Time>, arrows: Query<(&mut Transform, &ConveyorArrow)>) {
+    let progress = (time.elapsed_secs() * ARROW_SPEED).fract() - 0.5;
+
+    for (mut transform, arrow) in arrows {
+        transform.translation =
+            arrow.direction * progress * ARROW_DISTANCE + Vec3::Y * ARROW_HEIGHT;
+    }
+}
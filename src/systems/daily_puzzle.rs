@@ -0,0 +1,141 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, futures::check_ready},
+};
+
+use crate::{
+    components::{goal::Goal, level::LevelEntityMarker},
+    generator::{generate_level, get_daily_seed},
+    resources::{
+        daily_puzzle::DailyPuzzleGeneration,
+        input::{ActionInput, InputAction},
+        levels::{LevelResource, LevelState},
+        settings::{GeneratorSettings, Settings},
+    },
+    systems::level::spawn_level,
+};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+pub fn is_playing_daily_puzzle(levels: Res<LevelResource>) -> bool {
+    levels.daily_puzzle_seed.is_some()
+}
+
+/// Starts generating the level for the given seed in the background. It is played instead of the
+/// level file once `finish_daily_puzzle` finds it done.
+pub fn start_daily_puzzle(
+    generation: &mut DailyPuzzleGeneration,
+    seed: u64,
+    settings: &GeneratorSettings,
+) {
+    let settings = settings.clone();
+    let task = AsyncComputeTaskPool::get().spawn(async move { generate_level(seed, &settings) });
+
+    generation.task = Some((seed, task));
+}
+
+/// In the daily puzzle mode, the next puzzle is generated once the current one is completed.
+pub fn go_to_next_daily_puzzle(
+    mut generation: ResMut<DailyPuzzleGeneration>,
+    levels: Res<LevelResource>,
+    goals: Query<&Goal>,
+    actions: Res<ActionInput>,
+    settings: Res<Settings>,
+) {
+    if !goals.is_empty() || !actions.just_pressed(InputAction::Confirm) || generation.task.is_some()
+    {
+        return;
+    }
+
+    if let Some(seed) = levels.daily_puzzle_seed {
+        start_daily_puzzle(&mut generation, seed + 1, &settings.generator);
+    }
+}
+
+/// Plays the generated daily puzzle once it is done. If no level could be generated, the levels are
+/// played again instead.
+pub fn finish_daily_puzzle(
+    mut commands: Commands,
+    mut levels: ResMut<LevelResource>,
+    mut generation: ResMut<DailyPuzzleGeneration>,
+    entities: Query<(&LevelEntityMarker, Entity)>,
+    asset_server: Res<AssetServer>,
+) {
+    let Some((seed, task)) = &mut generation.task else {
+        return;
+    };
+    let seed = *seed;
+    let Some(result) = check_ready(task) else {
+        return;
+    };
+    generation.task = None;
+
+    match result {
+        Ok(generated) => {
+            let mut level = generated.level;
+            level.metadata.help_text = format!(
+                "Daily puzzle {} - {{PlayDailyPuzzle}}: back to the levels",
+                (seed - get_daily_seed(seed >> 32)) + 1
+            );
+
+            levels.daily_puzzle_seed = Some(seed);
+            levels.edited_level = Some(level.to_toml());
+        }
+        Err(error) => {
+            error!("{error}");
+
+            // Staying on the last puzzle would leave the player stuck on a completed level.
+            if levels.daily_puzzle_seed.is_none() {
+                return;
+            }
+
+            levels.daily_puzzle_seed = None;
+            levels.edited_level = None;
+        }
+    }
+
+    for entity in entities {
+        commands.entity(entity.1).despawn();
+    }
+
+    spawn_level(&levels, &mut commands, &asset_server);
+}
+
+/// Switches between the levels and an endless series of generated puzzles,
+/// which starts with the same puzzle for everyone on the same day.
+pub fn toggle_daily_puzzle(
+    mut commands: Commands,
+    mut levels: ResMut<LevelResource>,
+    mut generation: ResMut<DailyPuzzleGeneration>,
+    entities: Query<(&LevelEntityMarker, Entity)>,
+    asset_server: Res<AssetServer>,
+    actions: Res<ActionInput>,
+    settings: Res<Settings>,
+) {
+    if !actions.just_pressed(InputAction::PlayDailyPuzzle)
+        || !matches!(levels.level_state, LevelState::WaitingForPlayerInput)
+        || generation.task.is_some()
+    {
+        return;
+    }
+
+    if levels.daily_puzzle_seed.is_none() {
+        let day = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs() / SECONDS_PER_DAY);
+
+        start_daily_puzzle(&mut generation, get_daily_seed(day), &settings.generator);
+        return;
+    }
+
+    levels.daily_puzzle_seed = None;
+    levels.edited_level = None;
+
+    for entity in entities {
+        commands.entity(entity.1).despawn();
+    }
+
+    spawn_level(&levels, &mut commands, &asset_server);
+}
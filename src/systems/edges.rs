@@ -0,0 +1,63 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+
+use crate::{
+    components::{tile::TileEdges, tile_coordinates::tile_coordinates_to_transform_coordinates},
+    resources::effects::GlobalEffects,
+};
+
+/// Half of the height of a wall, so that it stands on top of the tile.
+const WALL_HEIGHT: f32 = 0.4;
+
+/// Just above the top of an icy tile, like the arrows of conveyors.
+const ONE_WAY_ARROW_HEIGHT: f32 = 0.11;
+
+/// Places the walls on the edges of the tiles, and an arrow on one-way tiles.
+/// These are children of the tile, so they move, and are hidden, together with it.
+pub fn add_tile_edges(
+    mut commands: Commands,
+    tiles: Query<(Entity, &TileEdges), Added<TileEdges>>,
+    effects: Res<GlobalEffects>,
+) {
+    let (Some(wall_mesh), Some(wall_material), Some(arrow_mesh), Some(arrow_material)) = (
+        &effects.wall_mesh,
+        &effects.wall_material,
+        &effects.conveyor_arrow_mesh,
+        &effects.one_way_arrow_material,
+    ) else {
+        return;
+    };
+
+    for (entity, edges) in tiles {
+        for wall in &edges.walls {
+            // The neighbour is a whole hex away, so the edge lies halfway.
+            let offset =
+                tile_coordinates_to_transform_coordinates(&wall.get_tile_coordinate_offset());
+            let transform = Transform::from_translation(offset / 2.0 + Vec3::Y * WALL_HEIGHT)
+                .looking_to(offset.normalize(), Vec3::Y);
+
+            commands.entity(entity).with_child((
+                Mesh3d(wall_mesh.clone()),
+                MeshMaterial3d(wall_material.clone()),
+                transform,
+            ));
+        }
+
+        if let Some(one_way) = edges.one_way {
+            let direction =
+                tile_coordinates_to_transform_coordinates(&one_way.get_tile_coordinate_offset())
+                    .normalize();
+
+            let mut transform = Transform::from_translation(Vec3::Y * ONE_WAY_ARROW_HEIGHT)
+                .looking_to(direction, Vec3::Y);
+            transform.rotate_local_x(-FRAC_PI_2);
+
+            commands.entity(entity).with_child((
+                Mesh3d(arrow_mesh.clone()),
+                MeshMaterial3d(arrow_material.clone()),
+                transform,
+            ));
+        }
+    }
+}
@@ -0,0 +1,393 @@
+use std::{fs, path::PathBuf};
+
+use bevy::prelude::*;
+
+use crate::{
+    components::{
+        camera::CameraAngle,
+        editor::EditorTextMarker,
+        level::{Level, LevelEntityMarker, MAX_TILE_HEIGHT},
+        tile_coordinates::{TileCoordinates, transform_coordinates_to_nearest_column},
+    },
+    resources::{
+        editor::{EditableCell, EditableLevel, EditorTool, LevelEditor},
+        input::{ActionInput, InputAction},
+        levels::{LevelResource, LevelState},
+        settings::Settings,
+    },
+    systems::level::spawn_level,
+};
+
+pub fn is_editing(editor: Res<LevelEditor>) -> bool {
+    editor.enabled
+}
+
+/// Plays the edited level instead of the level file from now on, and renders it again.
+fn apply_edit(editor: &mut LevelEditor, levels: &mut LevelResource) {
+    if let Some(level) = &editor.level {
+        levels.edited_level = Some(level.to_toml());
+        editor.has_unrendered_changes = true;
+    }
+}
+
+/// Stops drawing the current movement map. Movement maps are repeated,
+/// so a step back to the start is added if the path does not end where it started.
+fn finish_path(editor: &mut LevelEditor, levels: &mut LevelResource) {
+    let (Some((row, col)), Some(level)) = (editor.path_cell.take(), &mut editor.level) else {
+        return;
+    };
+
+    let movement_map = &mut level.layers[editor.layer_index].cells[row][col].movement_map;
+    let (x, y, z) = movement_map.iter().fold((0, 0, 0), |total, step| {
+        (total.0 + step.0, total.1 + step.1, total.2 + step.2)
+    });
+
+    if (x, y, z) != (0, 0, 0) {
+        movement_map.push((-x, -y, -z));
+        apply_edit(editor, levels);
+    }
+}
+
+pub fn toggle_editor(
+    mut editor: ResMut<LevelEditor>,
+    mut levels: ResMut<LevelResource>,
+    actions: Res<ActionInput>,
+) {
+    // Generated puzzles are not level files, so they cannot be saved.
+    if !actions.just_pressed(InputAction::ToggleEditor)
+        || !matches!(levels.level_state, LevelState::WaitingForPlayerInput)
+        || levels.daily_puzzle_seed.is_some()
+    {
+        return;
+    }
+
+    if editor.enabled {
+        finish_path(&mut editor, &mut levels);
+        editor.enabled = false;
+        editor.level = None;
+    } else {
+        match levels
+            .get_level()
+            .and_then(|level| EditableLevel::from_level(&level))
+        {
+            Ok(level) => {
+                editor.enabled = true;
+                editor.level = Some(level);
+                editor.layer_index = 0;
+                editor.message.clear();
+            }
+            Err(error) => {
+                warn!("Could not edit the level: {error}");
+                return;
+            }
+        }
+    }
+
+    // Either way the level starts over, so that it is edited and playtested from the start.
+    editor.has_unrendered_changes = true;
+}
+
+pub fn edit_level(
+    mut editor: ResMut<LevelEditor>,
+    mut levels: ResMut<LevelResource>,
+    camera: Single<&CameraAngle>,
+    actions: Res<ActionInput>,
+    settings: Res<Settings>,
+) {
+    if actions.just_pressed(InputAction::EditorNextTool) {
+        finish_path(&mut editor, &mut levels);
+        editor.tool = editor.tool.next();
+    }
+
+    if actions.just_pressed(InputAction::EditorNextLayer) {
+        finish_path(&mut editor, &mut levels);
+
+        let editor = &mut *editor;
+        if let Some(level) = &mut editor.level {
+            editor.layer_index = if editor.layer_index + 1 < level.layers.len() {
+                editor.layer_index + 1
+            } else {
+                // Going past the top layer adds a new one, unless the top layer is still empty.
+                let top_layer_index = level.add_layer();
+                if top_layer_index > editor.layer_index {
+                    top_layer_index
+                } else {
+                    0
+                }
+            };
+        }
+    }
+
+    if actions.just_pressed(InputAction::EditorTogglePillars) {
+        let layer_index = editor.layer_index;
+        if let Some(level) = &mut editor.level {
+            level.layers[layer_index].pillars = !level.layers[layer_index].pillars;
+            apply_edit(&mut editor, &mut levels);
+        }
+    }
+
+    if actions.just_pressed(InputAction::EditorSave)
+        && let Some(level) = &editor.level
+    {
+        let path = PathBuf::from(&settings.editor.directory)
+            .join(format!("{}.toml", levels.current_level_number));
+
+        let result = fs::create_dir_all(&settings.editor.directory)
+            .and_then(|_| fs::write(&path, level.to_toml()));
+
+        editor.message = match result {
+            Ok(_) => format!("Saved level to {}", path.display()),
+            Err(error) => format!("Could not save level to {}: {error}", path.display()),
+        };
+        info!("{}", editor.message);
+    }
+
+    if editor.tool != EditorTool::Path {
+        return;
+    }
+
+    if actions.just_pressed(InputAction::Confirm) {
+        finish_path(&mut editor, &mut levels);
+        return;
+    }
+
+    let Some((row, col)) = editor.path_cell else {
+        return;
+    };
+
+    let step = InputAction::ALL
+        .iter()
+        .filter(|action| actions.just_pressed(**action))
+        .find_map(|action| action.movement_direction())
+        .map(|direction| {
+            let offset = direction
+                .rotate_y(camera.total_6th_rotations)
+                .get_tile_coordinate_offset();
+            (offset.x as isize, offset.y as isize, offset.z as isize)
+        })
+        .or(actions
+            .just_pressed(InputAction::EditorPathUp)
+            .then_some((0, 1, 0)))
+        .or(actions
+            .just_pressed(InputAction::EditorPathDown)
+            .then_some((0, -1, 0)));
+
+    let layer_index = editor.layer_index;
+    let Some(level) = &mut editor.level else {
+        return;
+    };
+    let movement_map = &mut level.layers[layer_index].cells[row][col].movement_map;
+
+    let is_changed = match step {
+        Some(step) => {
+            movement_map.push(step);
+            true
+        }
+        None => actions.just_pressed(InputAction::Undo) && movement_map.pop().is_some(),
+    };
+
+    if is_changed {
+        apply_edit(&mut editor, &mut levels);
+    }
+}
+
+pub fn on_editor_clicked(
+    mut click: On<Pointer<Click>>,
+    objects: Query<&TileCoordinates>,
+    parents: Query<&ChildOf>,
+    camera: Single<(&Camera, &GlobalTransform), With<CameraAngle>>,
+    mut editor: ResMut<LevelEditor>,
+    mut levels: ResMut<LevelResource>,
+) {
+    if !editor.enabled {
+        return;
+    }
+
+    // Handle the click only once, for the mesh that was clicked.
+    click.propagate(false);
+
+    let clicked_object = std::iter::once(click.entity)
+        .chain(parents.iter_ancestors(click.entity))
+        .find_map(|entity| objects.get(entity).ok());
+
+    let (x, z) = match clicked_object {
+        Some(tile_coordinates) => (tile_coordinates.x, tile_coordinates.z),
+        None => {
+            // Nothing on the grid was clicked, so find the hex on the water below the pointer.
+            let (camera, camera_transform) = *camera;
+            let position = click.event.hit.position.or_else(|| {
+                let ray = camera
+                    .viewport_to_world(camera_transform, click.pointer_location.position)
+                    .ok()?;
+                let distance = ray.intersect_plane(Vec3::ZERO, InfinitePlane3d::new(Vec3::Y))?;
+                Some(ray.get_point(distance))
+            });
+
+            let Some(position) = position else {
+                return;
+            };
+
+            transform_coordinates_to_nearest_column(&position)
+        }
+    };
+
+    let is_primary = click.event.button == PointerButton::Primary;
+    let layer_index = editor.layer_index;
+    let tool = editor.tool;
+    let Some(level) = &mut editor.level else {
+        return;
+    };
+
+    let (row, col) = level.get_grid_position(x, z);
+    let (width, height) = level.get_dimensions();
+    let is_in_grid = row >= 0 && col >= 0 && row < height as isize && col < width as isize;
+
+    // Only raising a tile can add a new hex to the level.
+    let (row, col) = if is_in_grid {
+        (row as usize, col as usize)
+    } else if tool == EditorTool::Height && is_primary {
+        let (position, (rows_added, cols_added)) = level.grow_to_include(row, col);
+        editor.path_cell = editor
+            .path_cell
+            .map(|(row, col)| (row + rows_added, col + cols_added));
+        position
+    } else {
+        return;
+    };
+
+    let Some(level) = &mut editor.level else {
+        return;
+    };
+    let cell = &mut level.layers[layer_index].cells[row][col];
+
+    match tool {
+        EditorTool::Height if is_primary => {
+            // Tiles are only raised as far as a height map can show them.
+            cell.height = Some(match cell.height {
+                None => 0,
+                Some(height) if height < MAX_TILE_HEIGHT => height + 1,
+                Some(height) => height,
+            });
+        }
+        EditorTool::Height => match cell.height {
+            None => return,
+            // Without a tile, anything placed on it would float above the water.
+            Some(0) => *cell = EditableCell::default(),
+            Some(height) => cell.height = Some(height - 1),
+        },
+        EditorTool::Path if is_primary => {
+            if cell.height.is_none() {
+                return;
+            }
+
+            if !cell.modifiers.contains(&'M') {
+                cell.modifiers.push('M');
+            }
+        }
+        EditorTool::Path => {
+            cell.modifiers.retain(|modifier| *modifier != 'M');
+            cell.movement_map.clear();
+
+            if editor.path_cell == Some((row, col)) {
+                editor.path_cell = None;
+            }
+        }
+        EditorTool::Player | EditorTool::Goal | EditorTool::Ice => {
+            let Some(modifier) = tool.modifier() else {
+                return;
+            };
+
+            cell.modifiers.retain(|existing| *existing != modifier);
+
+            if is_primary {
+                if cell.height.is_none() {
+                    return;
+                }
+
+                cell.modifiers.push(modifier);
+            }
+        }
+    }
+
+    if tool == EditorTool::Path && is_primary && editor.path_cell != Some((row, col)) {
+        finish_path(&mut editor, &mut levels);
+        editor.path_cell = Some((row, col));
+    }
+
+    apply_edit(&mut editor, &mut levels);
+}
+
+pub fn render_edited_level(
+    mut commands: Commands,
+    mut editor: ResMut<LevelEditor>,
+    mut levels: ResMut<LevelResource>,
+    entities: Query<(&LevelEntityMarker, Entity), Without<Level>>,
+    asset_server: Res<AssetServer>,
+) {
+    if !editor.has_unrendered_changes {
+        return;
+    }
+
+    editor.has_unrendered_changes = false;
+
+    for entity in entities {
+        commands.entity(entity.1).despawn();
+    }
+
+    levels.level_state = LevelState::WaitingForPlayerInput;
+    spawn_level(&levels, &mut commands, &asset_server);
+}
+
+pub fn update_editor_text(
+    mut commands: Commands,
+    editor: Res<LevelEditor>,
+    texts: Query<Entity, With<EditorTextMarker>>,
+    asset_server: Res<AssetServer>,
+) {
+    if !editor.is_changed() {
+        return;
+    }
+
+    for entity in texts {
+        commands.entity(entity).despawn();
+    }
+
+    let Some(level) = &editor.level else {
+        return;
+    };
+
+    let layer = &level.layers[editor.layer_index];
+    let path_text = match editor.path_cell {
+        Some((row, col)) => format!(
+            "\nDrawing a path of {} steps",
+            layer.cells[row][col].movement_map.len()
+        ),
+        None => String::new(),
+    };
+
+    commands.spawn((
+        EditorTextMarker,
+        Text::new(format!(
+            "Level editor - F2: playtest, F5: save\n\
+            Tab: next tool - {}\n\
+            L: next layer - layer {} of {}, K: pillars {}{path_text}\n{}",
+            editor.tool.description(),
+            editor.layer_index + 1,
+            level.layers.len(),
+            if layer.pillars { "on" } else { "off" },
+            editor.message,
+        )),
+        TextFont {
+            font: asset_server.load("fonts/main.ttf"),
+            font_size: 20.0,
+            ..default()
+        },
+        TextShadow::default(),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: px(20),
+            left: px(20),
+            ..default()
+        },
+    ));
+}
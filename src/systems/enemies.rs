@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::{
+        enemy::{Caught, Enemy},
+        movement::Movement,
+        player::{Player, PlayerFinishedMoving},
+        tile_coordinates::TileCoordinates,
+    },
+    resources::{effects::GlobalEffects, water::Water},
+    rules::Board,
+    systems::tiles::BoardTiles,
+};
+
+/// The enemy phase of a turn. Every enemy takes at most one step, by the same rules as the
+/// players, without using keys, following `Board::get_enemy_steps`. Afterwards they slide and fall
+/// like the players do, and `advance_turn` starts the level effects once they have all landed.
+pub fn move_enemies(
+    _event: On<PlayerFinishedMoving>,
+    enemies: Query<(&mut Enemy, &TileCoordinates, Entity)>,
+    players: Query<&TileCoordinates, (With<Player>, Without<Enemy>)>,
+    board_tiles: BoardTiles,
+    water: Res<Water>,
+    mut commands: Commands,
+) {
+    let board = Board {
+        water_height: water.height,
+        ..board_tiles.get()
+    };
+    let player_positions: Vec<(isize, isize, isize)> = players
+        .iter()
+        .map(|player| (player.x, player.y, player.z))
+        .collect();
+
+    let steps = board.get_enemy_steps(
+        &enemies
+            .iter()
+            .map(|(enemy, tile, _)| {
+                (
+                    (tile.x, tile.y, tile.z),
+                    &enemy.behaviour,
+                    enemy.route_index,
+                )
+            })
+            .collect::<Vec<_>>(),
+        &player_positions,
+    );
+
+    for ((mut enemy, tile_coordinates, entity), direction) in enemies.into_iter().zip(steps) {
+        enemy.route_index = enemy.behaviour.next_route_index(enemy.route_index);
+
+        if let Some(direction) = direction {
+            commands.entity(entity).insert(Movement {
+                offset: direction.get_tile_coordinate_offset(),
+                movement_speed: tile_coordinates.movement_speed,
+                animation_percentage: 0.0,
+                is_sliding: false,
+            });
+        }
+    }
+}
+
+/// Players that stand still on the same hex as an enemy are caught, including when an enemy
+/// slides or is carried through them.
+pub fn catch_players(
+    mut commands: Commands,
+    players: Query<(Entity, &TileCoordinates, Has<Movement>), With<Player>>,
+    enemies: Query<&TileCoordinates, With<Enemy>>,
+) {
+    for (entity, player, is_moving) in players {
+        if !is_moving
+            && enemies
+                .iter()
+                .any(|enemy| (enemy.x, enemy.y, enemy.z) == (player.x, player.y, player.z))
+        {
+            commands.entity(entity).insert(Caught);
+        }
+    }
+}
+
+/// The enemy itself is invisible, like keys.
+pub fn add_enemy_meshes(
+    mut commands: Commands,
+    enemies: Query<Entity, Added<Enemy>>,
+    effects: Res<GlobalEffects>,
+) {
+    let (Some(mesh), Some(material)) = (&effects.enemy_mesh, &effects.enemy_material) else {
+        return;
+    };
+
+    for entity in enemies {
+        commands
+            .entity(entity)
+            .with_child((Mesh3d(mesh.clone()), MeshMaterial3d(material.clone())));
+    }
+}
@@ -0,0 +1,119 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+
+use crate::{
+    components::{
+        ghost::PredictionGhost,
+        level::LevelEntityMarker,
+        tile::Carriable,
+        tile_coordinates::{TileCoordinates, tile_coordinates_to_transform_coordinates},
+    },
+    resources::{
+        effects::GlobalEffects,
+        levels::{LevelResource, LevelState},
+        settings::Settings,
+        switches::SwitchStates,
+    },
+    systems::tiles::Environment,
+};
+
+/// Shows where the environment will be after the next turn, if the players stay where they are.
+/// The steps are the ones that `on_enemies_finished_moving` will play.
+pub fn update_prediction_ghosts(
+    mut commands: Commands,
+    levels: Res<LevelResource>,
+    effects: Res<GlobalEffects>,
+    settings: Res<Settings>,
+    switch_states: Res<SwitchStates>,
+    ghosts: Query<Entity, With<PredictionGhost>>,
+    environment: Environment,
+    tiles: Query<&TileCoordinates, Without<Carriable>>,
+    carriables: Query<&TileCoordinates, With<Carriable>>,
+) {
+    let ghosts_enabled = settings
+        .display
+        .prediction_ghost
+        .difficulties
+        .contains(&settings.difficulty);
+
+    // Ghosts are only shown while the level is at rest. During a turn they would
+    // be outdated, so they are removed and rebuilt once the turn has finished.
+    if !ghosts_enabled || !matches!(levels.level_state, LevelState::WaitingForPlayerInput) {
+        for entity in ghosts {
+            commands.entity(entity).despawn();
+        }
+
+        return;
+    }
+
+    if !ghosts.is_empty() {
+        return;
+    }
+
+    let (Some(tile_mesh), Some(object_mesh), Some(material)) = (
+        effects.prediction_ghost_tile_mesh.clone(),
+        effects.prediction_ghost_object_mesh.clone(),
+        effects.prediction_ghost_material.clone(),
+    ) else {
+        return;
+    };
+
+    let get_translation = |tile: &TileCoordinates, offset: Vec3| {
+        tile_coordinates_to_transform_coordinates(
+            &(Vec3::new(tile.x as f32, tile.y as f32, tile.z as f32) + offset),
+        )
+    };
+    let object_ghost = |carriable: &TileCoordinates, offset: Vec3| {
+        (
+            PredictionGhost,
+            LevelEntityMarker,
+            Mesh3d(object_mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            Transform::from_translation(
+                get_translation(carriable, offset)
+                    + Vec3::new(0.0, 0.9, 0.0)
+                    + carriable.visual_offset,
+            ),
+        )
+    };
+
+    let steps = environment.get_steps(&switch_states);
+
+    // Objects on conveyors are pushed one hex.
+    for (entity, direction) in steps.pushes {
+        if let Ok(carriable) = carriables.get(entity) {
+            commands.spawn(object_ghost(
+                carriable,
+                direction.get_tile_coordinate_offset(),
+            ));
+        }
+    }
+
+    for (entity, offset) in steps.tiles {
+        let Ok(tile) = tiles.get(entity) else {
+            continue;
+        };
+
+        if !tile.is_on_top || offset == (0, 0, 0) {
+            continue;
+        }
+
+        let offset = Vec3::new(offset.0 as f32, offset.1 as f32, offset.2 as f32);
+        commands.spawn((
+            PredictionGhost,
+            LevelEntityMarker,
+            Mesh3d(tile_mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            Transform::from_translation(get_translation(tile, offset) + Vec3::new(0.0, 0.65, 0.0))
+                .with_rotation(Quat::from_rotation_x(-FRAC_PI_2)),
+        ));
+
+        // Objects resting on the tile are carried along, just like in `on_enemies_finished_moving`.
+        for carriable in carriables {
+            if carriable.x == tile.x && carriable.y == tile.y && carriable.z == tile.z {
+                commands.spawn(object_ghost(carriable, offset));
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::{
+        enemy::Caught,
+        goal::Goal,
+        level::{HudMarker, HudTextMarker, LevelEntityMarker, LoseTextMarker},
+        player::{Drowned, Player},
+    },
+    resources::{
+        input::{InputAction, InputBindings},
+        levels::LevelResource,
+        stats::LevelStats,
+    },
+};
+
+/// The clock runs until the level is won or lost.
+pub fn count_elapsed_time(
+    mut stats: ResMut<LevelStats>,
+    goals: Query<(), With<Goal>>,
+    drowned_players: Query<(), With<Drowned>>,
+    caught_players: Query<(), With<Caught>>,
+    levels: Res<LevelResource>,
+    timer: Res<Time>,
+) {
+    if goals.is_empty()
+        || !drowned_players.is_empty()
+        || !caught_players.is_empty()
+        || stats.is_out_of_moves(levels.level_state)
+    {
+        return;
+    }
+
+    stats.elapsed_secs += timer.delta_secs();
+}
+
+pub fn update_hud_text(hud_text: Query<&mut Text, With<HudTextMarker>>, stats: Res<LevelStats>) {
+    let moves = match stats.max_moves {
+        Some(max_moves) => format!("{} / {max_moves}", stats.moves),
+        None => stats.moves.to_string(),
+    };
+    let seconds = stats.elapsed_secs as usize;
+
+    for mut text in hud_text {
+        text.0 = format!(
+            "Moves: {moves}\nUndos: {}\nTime: {}:{:02}",
+            stats.undos,
+            seconds / 60,
+            seconds % 60
+        );
+    }
+}
+
+/// Shows why the level was lost below the move counter. Drowning takes precedence over being
+/// caught, and running out of moves only counts while the players are still alive.
+pub fn show_lose_text(
+    mut commands: Commands,
+    hud: Query<Entity, With<HudMarker>>,
+    mut lose_text: Query<(Entity, &mut Text, &mut TextColor), With<LoseTextMarker>>,
+    goals: Query<(), With<Goal>>,
+    players: Query<(Has<Drowned>, Has<Caught>), With<Player>>,
+    stats: Res<LevelStats>,
+    levels: Res<LevelResource>,
+    bindings: Res<InputBindings>,
+    asset_server: Res<AssetServer>,
+) {
+    let reason = if players.iter().any(|(is_drowned, _)| is_drowned) {
+        Some(("Drowned!", LinearRgba::rgb(0.4, 0.8, 1.0)))
+    } else if players.iter().any(|(_, is_caught)| is_caught) {
+        Some(("Caught!", LinearRgba::rgb(1.0, 0.3, 0.3)))
+    } else if stats.is_out_of_moves(levels.level_state) && !goals.is_empty() {
+        Some(("Out of moves!", LinearRgba::rgb(1.0, 0.5, 0.3)))
+    } else {
+        None
+    };
+
+    let Some((reason, color)) = reason else {
+        for (entity, ..) in lose_text {
+            commands.entity(entity).despawn();
+        }
+
+        return;
+    };
+
+    let message = format!(
+        "{reason}\n<{}> to undo, <{}> to restart",
+        bindings.name(InputAction::Undo),
+        bindings.name(InputAction::Restart)
+    );
+
+    if let Ok((_, mut text, mut text_color)) = lose_text.single_mut() {
+        if text.0 != message {
+            text.0 = message;
+            text_color.0 = color.into();
+        }
+
+        return;
+    }
+
+    let Ok(hud) = hud.single() else {
+        return;
+    };
+
+    commands.entity(hud).with_child((
+        LoseTextMarker,
+        LevelEntityMarker,
+        Text::new(message),
+        TextFont {
+            font: asset_server.load("fonts/main.ttf"),
+            font_size: 28.0,
+            ..default()
+        },
+        TextLayout::new_with_justify(Justify::Right),
+        TextShadow::default(),
+        TextColor::from(color),
+    ));
+}
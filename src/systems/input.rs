@@ -0,0 +1,249 @@
+use bevy::{
+    ecs::system::SystemParam,
+    input::{ButtonState, gamepad::GamepadButtonStateChangedEvent, keyboard::KeyboardInput},
+    prelude::*,
+};
+
+use crate::{
+    components::{
+        camera::CameraAngle,
+        input::{KeyHint, RebindingTextMarker},
+        movement::Movement,
+    },
+    resources::{
+        input::{ActionInput, InputAction, InputBindings, InputBuffer, InputRebinding},
+        levels::{LevelResource, LevelState},
+    },
+};
+
+/// The key and button presses of this frame, in the order in which they happened.
+#[derive(SystemParam)]
+pub struct Presses<'w, 's> {
+    keyboard: MessageReader<'w, 's, KeyboardInput>,
+    gamepad: MessageReader<'w, 's, GamepadButtonStateChangedEvent>,
+}
+
+pub fn read_input_actions(
+    mut actions: ResMut<ActionInput>,
+    bindings: Res<InputBindings>,
+    rebinding: Res<InputRebinding>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut presses: Presses,
+    touches: Res<Touches>,
+    gamepads: Query<&Gamepad>,
+) {
+    actions.just_pressed.clear();
+    actions.pressed.clear();
+
+    // Presses while rebinding are meant for the rebinding, not for the game.
+    if rebinding.action.is_some() {
+        presses.keyboard.clear();
+        presses.gamepad.clear();
+        return;
+    }
+
+    // The presses are read in the order in which they happened, so that the buffer replays them in
+    // that order.
+    for input in presses.keyboard.read() {
+        if input.state != ButtonState::Pressed || input.repeat {
+            continue;
+        }
+
+        for (action, key_codes) in &bindings.keyboard {
+            if key_codes.contains(&input.key_code) {
+                actions.press(*action);
+            }
+        }
+    }
+
+    for change in presses.gamepad.read() {
+        if change.state != ButtonState::Pressed {
+            continue;
+        }
+
+        for (action, buttons) in &bindings.gamepad {
+            if buttons.contains(&change.button) {
+                actions.press(*action);
+            }
+        }
+    }
+
+    for (action, key_codes) in &bindings.keyboard {
+        if key_codes.iter().any(|key_code| keys.pressed(*key_code)) {
+            actions.pressed.insert(*action);
+        }
+    }
+
+    let mut stick_engaged = false;
+
+    for gamepad in gamepads {
+        for (action, buttons) in &bindings.gamepad {
+            if buttons.iter().any(|button| gamepad.pressed(*button)) {
+                actions.pressed.insert(*action);
+            }
+        }
+
+        let stick = gamepad.left_stick();
+
+        if stick.length() < bindings.gamepad_stick_threshold {
+            continue;
+        }
+
+        stick_engaged = true;
+
+        if !actions.stick_engaged {
+            // The stick is in screen space, just like the movement actions,
+            // so the camera rotation is applied later on by `player_controls`.
+            actions.press(InputAction::from_screen_angle(stick.to_angle()));
+        }
+    }
+
+    actions.stick_engaged = stick_engaged;
+
+    for touch in touches.iter_just_released() {
+        let swipe = touch.distance();
+
+        if swipe.length() < bindings.swipe_threshold {
+            continue;
+        }
+
+        // Screen coordinates point downwards, unlike the stick of a gamepad.
+        actions.press(InputAction::from_screen_angle(
+            Vec2::new(swipe.x, -swipe.y).to_angle(),
+        ));
+    }
+}
+
+pub fn buffer_input_actions(
+    mut actions: ResMut<ActionInput>,
+    mut buffer: ResMut<InputBuffer>,
+    bindings: Res<InputBindings>,
+    levels: Res<LevelResource>,
+    cameras: Query<&CameraAngle>,
+    moving_objects: Query<(), With<Movement>>,
+) {
+    // A restart makes the actions before it pointless, so it only waits for the current animation.
+    if actions.just_pressed(InputAction::Restart) || actions.just_pressed(InputAction::Confirm) {
+        buffer.actions.clear();
+    }
+
+    let is_animating = !matches!(levels.level_state, LevelState::WaitingForPlayerInput)
+        || !moving_objects.is_empty()
+        || cameras
+            .iter()
+            .any(|camera| camera.rotation_animation_percentage.is_some());
+
+    // Every bufferable press goes through the buffer, in the order of the presses, so that it waits
+    // for the animations of the actions before it.
+    let pressed_actions: Vec<InputAction> = actions
+        .just_pressed
+        .iter()
+        .copied()
+        .filter(InputAction::is_bufferable)
+        .collect();
+    actions
+        .just_pressed
+        .retain(|action| !action.is_bufferable());
+
+    for action in pressed_actions {
+        if buffer.actions.len() < bindings.buffer_length {
+            buffer.actions.push_back(action);
+        }
+    }
+
+    // Replay a single action, and let it finish animating before replaying the next one.
+    if !is_animating && let Some(action) = buffer.actions.pop_front() {
+        actions.press(action);
+    }
+}
+
+pub fn show_key_hints(hints: Query<(Ref<KeyHint>, &mut Text)>, bindings: Res<InputBindings>) {
+    for (hint, mut text) in hints {
+        if hint.is_added() || bindings.is_changed() {
+            text.0 = bindings.fill_in_names(&hint.0);
+        }
+    }
+}
+
+pub fn rebind_inputs(
+    mut commands: Commands,
+    mut bindings: ResMut<InputBindings>,
+    mut rebinding: ResMut<InputRebinding>,
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    prompts: Query<Entity, With<RebindingTextMarker>>,
+    asset_server: Res<AssetServer>,
+) {
+    let Some(action) = rebinding.action else {
+        if keys.just_pressed(bindings.rebind_key) {
+            rebinding.action = Some(InputAction::ALL[0]);
+        }
+
+        return;
+    };
+
+    let mut is_action_handled = false;
+
+    if keys.just_pressed(KeyCode::Escape) {
+        // Skipping keeps the current binding.
+        is_action_handled = true;
+    } else if let Some(key_code) = keys
+        .get_just_pressed()
+        .find(|key_code| **key_code != bindings.rebind_key)
+    {
+        let key_code = *key_code;
+        bindings.bind_key(action, key_code);
+        is_action_handled = true;
+    } else if let Some(button) = gamepads
+        .iter()
+        .find_map(|gamepad| gamepad.get_just_pressed().next().copied())
+    {
+        bindings.bind_button(action, button);
+        is_action_handled = true;
+    }
+
+    if bindings.is_changed()
+        && let Err(error) = bindings.save_bindings()
+    {
+        warn!("{error}");
+    }
+
+    if is_action_handled {
+        let index = InputAction::ALL
+            .iter()
+            .position(|item| *item == action)
+            .unwrap_or_default();
+
+        rebinding.action = InputAction::ALL.get(index + 1).copied();
+    }
+
+    if !rebinding.is_changed() {
+        return;
+    }
+
+    for entity in prompts {
+        commands.entity(entity).despawn();
+    }
+
+    if let Some(action) = rebinding.action {
+        commands.spawn((
+            RebindingTextMarker,
+            Text::new(format!(
+                "Press a key or button for {action:?}\n<Esc> to keep the current binding"
+            )),
+            TextFont {
+                font: asset_server.load("fonts/main.ttf"),
+                font_size: 28.0,
+                ..default()
+            },
+            TextShadow::default(),
+            TextColor::from(LinearRgba::rgb(1.0, 1.0, 0.0)),
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: px(20),
+                left: px(20),
+                ..default()
+            },
+        ));
+    }
+}
@@ -0,0 +1,179 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+
+use crate::{
+    components::{
+        key::{GateMesh, Key, KeyHudMarker},
+        level::{HudMarker, LevelEntityMarker},
+        player::Player,
+        tile::TileEdges,
+        tile_coordinates::TileCoordinates,
+    },
+    resources::{effects::GlobalEffects, keys::KeyInventory},
+};
+
+/// Half of the height of a gate, so that it stands on top of the tile.
+const GATE_HEIGHT: f32 = 0.4;
+
+/// The size of a key in the row of held keys.
+const KEY_HUD_SIZE: f32 = 32.0;
+
+pub fn collect_keys(
+    mut commands: Commands,
+    players: Query<&TileCoordinates, With<Player>>,
+    keys: Query<(&Key, &TileCoordinates, Entity), Without<Player>>,
+    mut inventory: ResMut<KeyInventory>,
+) {
+    for player in players {
+        if player.movement_animation_percentage.is_some() {
+            // Allow movement to finish first
+            continue;
+        }
+
+        for (key, tile, entity) in keys {
+            if player.x == tile.x && player.y == tile.y && player.z == tile.z {
+                commands.entity(entity).despawn();
+                inventory.add(key.color);
+            }
+        }
+    }
+}
+
+/// Uses up a key to open a locked gate that a player stands on. Players can only walk into a gate
+/// while they hold its key, but they can also fall onto one, in which case the gate stays locked
+/// if they have none.
+pub fn open_gates(
+    players: Query<&TileCoordinates, With<Player>>,
+    gates: Query<(&TileCoordinates, &mut TileEdges), Without<Player>>,
+    mut inventory: ResMut<KeyInventory>,
+) {
+    for (tile, mut edges) in gates {
+        let Some(color) = edges.gate else {
+            continue;
+        };
+
+        if players.iter().any(|player| {
+            player.movement_animation_percentage.is_none()
+                && (player.x, player.y, player.z) == (tile.x, tile.y, tile.z)
+        }) && inventory.take(color)
+        {
+            edges.gate = None;
+        }
+    }
+}
+
+/// The key itself is invisible, so that its mesh can stand upright while the key turns around.
+pub fn add_key_meshes(
+    mut commands: Commands,
+    keys: Query<(Entity, &Key), Added<Key>>,
+    effects: Res<GlobalEffects>,
+) {
+    let Some(mesh) = &effects.key_mesh else {
+        return;
+    };
+
+    for (entity, key) in keys {
+        let Some(material) = effects.key_materials.get(&key.color) else {
+            continue;
+        };
+
+        commands.entity(entity).with_child((
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            Transform::from_rotation(Quat::from_rotation_x(FRAC_PI_2)),
+        ));
+    }
+}
+
+pub fn rotate_keys(query: Query<&mut Transform, With<Key>>, timer: Res<Time>) {
+    for mut key in query {
+        key.rotate_local_y(timer.delta_secs() * 1.5);
+    }
+}
+
+/// Shows the locked gates on their tiles. Gates are opened during a turn and locked again by undoing it,
+/// so the gate mesh is replaced whenever the edges of a tile change.
+pub fn update_gate_meshes(
+    mut commands: Commands,
+    tiles: Query<(Entity, &TileEdges, Option<&Children>), Changed<TileEdges>>,
+    gate_meshes: Query<(), With<GateMesh>>,
+    effects: Res<GlobalEffects>,
+) {
+    let Some(mesh) = &effects.gate_mesh else {
+        return;
+    };
+
+    for (entity, edges, children) in tiles {
+        for child in children.into_iter().flatten() {
+            if gate_meshes.contains(*child) {
+                commands.entity(*child).despawn();
+            }
+        }
+
+        let Some(material) = edges
+            .gate
+            .and_then(|color| effects.gate_materials.get(&color))
+        else {
+            continue;
+        };
+
+        commands.entity(entity).with_child((
+            GateMesh,
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            Transform::from_translation(Vec3::Y * GATE_HEIGHT),
+        ));
+    }
+}
+
+/// Shows the held keys in the HUD, below the move counter.
+pub fn update_key_hud(
+    mut commands: Commands,
+    hud: Query<Entity, With<HudMarker>>,
+    key_huds: Query<Entity, With<KeyHudMarker>>,
+    inventory: Res<KeyInventory>,
+) {
+    if !inventory.is_changed() {
+        return;
+    }
+
+    let Ok(hud) = hud.single() else {
+        return;
+    };
+
+    for entity in key_huds {
+        commands.entity(entity).despawn();
+    }
+
+    if inventory.keys.is_empty() {
+        return;
+    }
+
+    commands.entity(hud).with_children(|builder| {
+        builder
+            .spawn((
+                KeyHudMarker,
+                LevelEntityMarker,
+                Node {
+                    column_gap: px(8),
+                    ..default()
+                },
+            ))
+            .with_children(|builder| {
+                for key in &inventory.keys {
+                    builder.spawn((
+                        Node {
+                            width: px(KEY_HUD_SIZE),
+                            height: px(KEY_HUD_SIZE),
+                            border: UiRect::all(px(2)),
+                            ..default()
+                        },
+                        BackgroundColor(key.color()),
+                        BorderColor::all(Color::WHITE),
+                        BorderRadius::MAX,
+                    ));
+                }
+            });
+    });
+}
@@ -2,12 +2,27 @@ use bevy::prelude::*;
 
 use crate::{
     components::{
+        audio::{PlaySoundEffect, SoundEffect},
+        enemy::{Caught, Enemy},
         goal::Goal,
-        level::{Level, LevelCompleteTextMarker, LevelEntityMarker},
+        input::KeyHint,
+        key::Key,
+        level::{Level, LevelCompleteTextMarker, LevelEntityMarker, LevelStarted},
+        movement::Movement,
+        player::Drowned,
+        tile::{MovementMap, Settling, TileEdges},
+        tile_coordinates::TileCoordinates,
     },
     resources::{
-        levels::{LevelResource, LevelState},
+        input::{ActionInput, InputAction},
+        keys::KeyInventory,
+        levels::{LevelResource, LevelState, UndoHistory},
+        pathfinding::PlannedMoves,
+        replay::{ReplayRecorder, ReplayStep},
         settings::Settings,
+        stats::LevelStats,
+        switches::SwitchStates,
+        water::Water,
     },
 };
 
@@ -16,9 +31,55 @@ pub fn build_level(
     levels: Res<LevelResource>,
     asset_server: Res<AssetServer>,
 ) {
-    levels
+    spawn_level(&levels, &mut commands, &asset_server);
+}
+
+/// Spawns the current level. A level file that cannot be read or is broken is reported on screen
+/// instead, so that the game keeps running and another level can be picked.
+pub fn spawn_level(
+    levels: &LevelResource,
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+) {
+    let Err(error) = levels
         .get_level()
-        .render_level(&mut commands, &asset_server);
+        .and_then(|level| level.render_level(commands, asset_server))
+    else {
+        return;
+    };
+
+    error!(
+        "Could not load level {}: {error}",
+        levels.current_level_number
+    );
+    commands.spawn((
+        LevelEntityMarker,
+        Text::new(format!("Could not load the level: {error}")),
+        TextFont {
+            font: asset_server.load("fonts/main.ttf"),
+            font_size: 28.0,
+            ..default()
+        },
+        TextShadow::default(),
+        TextColor::from(LinearRgba::rgb(1.0, 0.3, 0.3)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: px(20),
+            left: px(20),
+            ..default()
+        },
+    ));
+}
+
+pub fn on_level_started(
+    _event: On<LevelStarted>,
+    mut history: ResMut<UndoHistory>,
+    mut planned_moves: ResMut<PlannedMoves>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    history.snapshots.clear();
+    planned_moves.directions.clear();
+    *recorder = ReplayRecorder::default();
 }
 
 pub fn restart_level(
@@ -26,18 +87,16 @@ pub fn restart_level(
     levels: Res<LevelResource>,
     entities: Query<(&LevelEntityMarker, Entity), Without<Level>>,
     asset_server: Res<AssetServer>,
-    keys: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionInput>,
 ) {
-    if keys.just_pressed(KeyCode::Backspace)
+    if actions.just_pressed(InputAction::Restart)
         && matches!(levels.level_state, LevelState::WaitingForPlayerInput)
     {
         for entity in entities {
             commands.entity(entity.1).despawn();
         }
 
-        levels
-            .get_level()
-            .render_level(&mut commands, &asset_server);
+        spawn_level(&levels, &mut commands, &asset_server);
     }
 }
 
@@ -47,14 +106,14 @@ pub fn go_to_next_level(
     goals: Query<&Goal>,
     entities: Query<(&LevelEntityMarker, Entity)>,
     asset_server: Res<AssetServer>,
-    keys: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionInput>,
 ) {
     // TODO: replace with more generic check for whether forwarding to next level is allowed
     if !goals.is_empty() {
         return;
     }
 
-    if keys.just_pressed(KeyCode::Enter) {
+    if actions.just_pressed(InputAction::Confirm) {
         // Destroy current level
         for entity in entities {
             commands.entity(entity.1).despawn();
@@ -62,12 +121,103 @@ pub fn go_to_next_level(
 
         // Render new level
         levels.current_level_number += 1;
-        levels
-            .get_level()
-            .render_level(&mut commands, &asset_server);
+        levels.edited_level = None;
+        spawn_level(&levels, &mut commands, &asset_server);
     }
 }
 
+pub fn undo_turn(
+    mut commands: Commands,
+    mut levels: ResMut<LevelResource>,
+    mut history: ResMut<UndoHistory>,
+    mut objects: Query<(Entity, &mut TileCoordinates, Option<&mut MovementMap>)>,
+    mut enemies: Query<&mut Enemy>,
+    goals: Query<Entity, With<Goal>>,
+    keys: Query<Entity, With<Key>>,
+    mut tile_edges: Query<&mut TileEdges>,
+    asset_server: Res<AssetServer>,
+    actions: Res<ActionInput>,
+    mut planned_moves: ResMut<PlannedMoves>,
+    mut recorder: ResMut<ReplayRecorder>,
+    mut switch_states: ResMut<SwitchStates>,
+    mut water: ResMut<Water>,
+    mut inventory: ResMut<KeyInventory>,
+    mut stats: ResMut<LevelStats>,
+) {
+    // A completed level stays completed.
+    if !actions.just_pressed(InputAction::Undo) || goals.is_empty() {
+        return;
+    }
+
+    let Some(snapshot) = history.snapshots.pop() else {
+        return;
+    };
+
+    planned_moves.directions.clear();
+    stats.moves = stats.moves.saturating_sub(1);
+    stats.undos += 1;
+
+    // Forget the undone move, and any camera rotations that came after it.
+    if let Some(index) = recorder
+        .steps
+        .iter()
+        .rposition(|step| matches!(step, ReplayStep::Move(_)))
+    {
+        recorder.steps.truncate(index);
+    }
+
+    // Undoing is also allowed halfway through a turn, so any ongoing movement is cancelled.
+    for (entity, _, _) in &objects {
+        commands.entity(entity).remove::<(Movement, Settling)>();
+    }
+
+    for object in snapshot.objects {
+        let Ok((_, mut tile_coordinates, movement_map)) = objects.get_mut(object.entity) else {
+            continue;
+        };
+
+        (tile_coordinates.x, tile_coordinates.y, tile_coordinates.z) = object.coordinates;
+        commands.entity(object.entity).remove::<(Drowned, Caught)>();
+
+        if let (Some(mut movement_map), Some(index)) = (movement_map, object.movement_map_index) {
+            movement_map.index = index;
+        }
+
+        if let (Ok(mut enemy), Some(index)) = (enemies.get_mut(object.entity), object.route_index) {
+            enemy.route_index = index;
+        }
+    }
+
+    for entity in goals.iter().chain(&keys) {
+        commands.entity(entity).despawn();
+    }
+
+    // Gates only ever open, so the ones that were locked are locked again.
+    for (entity, color) in snapshot.gates {
+        if let Ok(mut edges) = tile_edges.get_mut(entity) {
+            edges.gate = Some(color);
+        }
+    }
+
+    // Bridges follow the switches again in `update_bridges`.
+    *switch_states = snapshot.switch_states;
+    *water = snapshot.water;
+    *inventory = snapshot.inventory;
+
+    // Turns are only played in levels that could be spawned, so the level can be read.
+    if let Ok(level) = levels.get_level() {
+        for (x, y, z) in snapshot.goals {
+            level.get_goal_entity(x, y, z, &mut commands, &asset_server);
+        }
+
+        for ((x, y, z), color) in snapshot.keys {
+            level.get_key_entity(x, y, z, color, &mut commands);
+        }
+    }
+
+    levels.level_state = LevelState::WaitingForPlayerInput;
+}
+
 pub fn show_level_complete(
     mut commands: Commands,
     goals: Query<&Goal>,
@@ -83,6 +233,10 @@ pub fn show_level_complete(
         return;
     }
 
+    commands.trigger(PlaySoundEffect {
+        effect: SoundEffect::LevelComplete,
+    });
+
     commands
         .spawn((
             Node {
@@ -127,7 +281,8 @@ pub fn show_level_complete(
         ))
         .with_children(|builder| {
             builder.spawn((
-                Text::new("<Enter> to continue"),
+                KeyHint("<{Confirm}> to continue".to_owned()),
+                Text::default(),
                 TextFont {
                     font: asset_server.load("fonts/main.ttf"),
                     font_size: 28.0,
@@ -1,7 +1,23 @@
+pub mod audio;
 pub mod camera;
+pub mod conveyors;
+pub mod daily_puzzle;
+pub mod edges;
+pub mod editor;
+pub mod enemies;
+pub mod ghost;
 pub mod goal;
+pub mod hud;
+pub mod input;
+pub mod keys;
 pub mod level;
 pub mod lighting;
+pub mod pathfinding;
 pub mod player;
+pub mod replay;
 pub mod setup;
+pub mod switches;
+pub mod teleporters;
 pub mod tiles;
+pub mod turn;
+pub mod water;
@@ -0,0 +1,82 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::{
+        player::Player,
+        tile_coordinates::{MovementDirection, TileCoordinates},
+    },
+    resources::{
+        editor::LevelEditor,
+        levels::{LevelResource, LevelState},
+        pathfinding::PlannedMoves,
+        replay::{ReplayRecorder, ReplayStep},
+    },
+    rules::step,
+    simulation::Simulation,
+};
+
+/// The most states that are searched for a path to the clicked tile.
+const MAX_SEARCH_STATES: usize = 20_000;
+
+pub fn on_tile_clicked(
+    mut click: On<Pointer<Click>>,
+    objects: Query<&TileCoordinates>,
+    players: Query<&TileCoordinates, With<Player>>,
+    levels: Res<LevelResource>,
+    recorder: Res<ReplayRecorder>,
+    editor: Res<LevelEditor>,
+    mut planned_moves: ResMut<PlannedMoves>,
+) {
+    // The click bubbles up from the clicked mesh to its ancestors.
+    // Only the first ancestor that is placed on the grid is relevant.
+    let Ok(destination) = objects.get(click.entity) else {
+        return;
+    };
+
+    click.propagate(false);
+
+    if click.event.button != PointerButton::Primary
+        || editor.enabled
+        || !matches!(levels.level_state, LevelState::WaitingForPlayerInput)
+    {
+        return;
+    }
+
+    let destination = (destination.x, destination.y, destination.z);
+
+    // Clicking a neighbouring tile that the player can step down onto always means moving towards
+    // it, even if the player then slides or falls somewhere else.
+    for player in players {
+        let start = (player.x, player.y, player.z);
+
+        if let Some(direction) = MovementDirection::ALL.into_iter().find(|direction| {
+            let neighbour = step(start, *direction);
+            (neighbour.0, neighbour.2) == (destination.0, destination.2) && destination.1 <= start.1
+        }) {
+            planned_moves.directions = [direction].into();
+            return;
+        }
+    }
+
+    // Distant tiles are found by playing the moves in the simulation, so that moving tiles, the
+    // water and everything else change along the way just like they will in the level. The
+    // simulation catches up with the level by replaying the moves made so far. Levels that it
+    // cannot play, such as levels with several players, are walked one neighbouring tile at a time.
+    let Ok(simulation) = levels
+        .get_level()
+        .and_then(|level| Simulation::from_level(&level))
+    else {
+        return;
+    };
+
+    let Some(state) = simulation.replay(recorder.steps.iter().filter_map(|step| match step {
+        ReplayStep::Move(direction) => Some(*direction),
+        ReplayStep::Rotate(_) => None,
+    })) else {
+        return;
+    };
+
+    if let Some(path) = simulation.find_path(&state, destination, MAX_SEARCH_STATES) {
+        planned_moves.directions = path.into();
+    }
+}
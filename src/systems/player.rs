@@ -4,17 +4,26 @@ use bevy_hanabi::ParticleEffect;
 
 use crate::{
     components::{
+        audio::{PlaySoundEffect, SoundEffect},
         camera::CameraAngle,
+        enemy::Caught,
         goal::Goal,
         movement::Movement,
-        player::{Player, PlayerStartedMoving},
+        player::{Drowned, Player, PlayerStartedMoving},
         tile::Tile,
-        tile_coordinates::{MovementDirection, TileCoordinates},
+        tile_coordinates::TileCoordinates,
     },
     resources::{
         effects::GlobalEffects,
+        input::{ActionInput, InputAction},
+        keys::KeyInventory,
         levels::{LevelResource, LevelState},
+        pathfinding::PlannedMoves,
+        stats::LevelStats,
+        water::Water,
     },
+    rules::{Board, step},
+    systems::tiles::BoardTiles,
 };
 
 const BLOOM_COLOR: LinearRgba = LinearRgba::rgb(1.0, 0.0, 1.0);
@@ -67,6 +76,9 @@ pub fn collect_goals(
                     ParticleEffect::new(effects.goal_explosion_effect.clone().unwrap()),
                     goal.2.clone(),
                 ));
+                commands.trigger(PlaySoundEffect {
+                    effect: SoundEffect::GoalCollected,
+                });
             }
         }
     }
@@ -75,73 +87,89 @@ pub fn collect_goals(
 pub fn player_controls(
     mut commands: Commands,
     players: Query<(&Player, &TileCoordinates, Option<&mut Movement>, Entity), Without<Tile>>,
-    tiles: Query<(&Tile, &TileCoordinates)>,
+    drowned_players: Query<(), With<Drowned>>,
+    caught_players: Query<(), With<Caught>>,
+    board_tiles: BoardTiles,
     camera: Single<&CameraAngle>,
-    keys: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionInput>,
+    mut planned_moves: ResMut<PlannedMoves>,
     level: Res<LevelResource>,
+    water: Res<Water>,
+    inventory: Res<KeyInventory>,
+    stats: Res<LevelStats>,
 ) {
     if !matches!(level.level_state, LevelState::WaitingForPlayerInput) {
         return;
     }
 
-    for player in players {
-        if player.2.is_some() {
-            // If already moving, then movement cannot be altered
-            continue;
+    let movement_direction = match InputAction::ALL
+        .iter()
+        .filter(|action| actions.just_pressed(**action))
+        .find_map(|action| action.movement_direction())
+    {
+        Some(direction) => {
+            // Moving manually overrides any moves that were planned.
+            planned_moves.directions.clear();
+            Some(direction.rotate_y(camera.total_6th_rotations))
         }
+        None => planned_moves.directions.pop_front(),
+    };
 
-        let mut movement_direction: Option<MovementDirection> = None;
+    let Some(direction) = movement_direction else {
+        return;
+    };
 
-        if keys.just_pressed(KeyCode::KeyA) {
-            movement_direction = Some(MovementDirection::West);
-        }
-        if keys.just_pressed(KeyCode::KeyD) {
-            movement_direction = Some(MovementDirection::East);
-        }
-        if keys.just_pressed(KeyCode::KeyW) {
-            movement_direction = Some(MovementDirection::NorthWest);
-        }
-        if keys.just_pressed(KeyCode::KeyE) {
-            movement_direction = Some(MovementDirection::NorthEast);
-        }
-        if keys.just_pressed(KeyCode::KeyZ) {
-            movement_direction = Some(MovementDirection::SouthWest);
-        }
-        if keys.just_pressed(KeyCode::KeyX) {
-            movement_direction = Some(MovementDirection::SouthEast);
+    // A level that has run out of moves can only be undone or restarted.
+    if stats.is_out_of_moves(level.level_state) {
+        planned_moves.directions.clear();
+        return;
+    }
+
+    let offset = direction.get_tile_coordinate_offset();
+    let mut has_any_player_moved = false;
+    let board = Board {
+        water_height: water.height,
+        ..board_tiles.get()
+    };
+
+    // Each key opens a single gate, even when several players walk into gates at once.
+    let mut keys = inventory.keys.clone();
+
+    for (_, player, movement, entity) in players {
+        if movement.is_some() || drowned_players.contains(entity) || caught_players.contains(entity)
+        {
+            // If already moving, then movement cannot be altered. Drowned or caught players cannot
+            // move at all.
+            continue;
         }
 
-        match movement_direction {
-            Some(direction) => {
-                let offset = direction
-                    .rotate_y(camera.total_6th_rotations)
-                    .get_tile_coordinate_offset();
-
-                let destination_tile = (
-                    player.1.x + offset.x as isize,
-                    player.1.y + offset.y as isize,
-                    player.1.z + offset.z as isize,
-                );
-
-                // Only move the player if there is a destination tile
-                // at the destination (or below it). Otherwise the player
-                // could fall off the island.
-                if tiles.iter().any(|tile| {
-                    tile.1.is_on_top
-                        && tile.1.x == destination_tile.0
-                        && tile.1.y <= destination_tile.1
-                        && tile.1.z == destination_tile.2
-                }) {
-                    commands.entity(player.3).insert(Movement {
-                        animation_percentage: 0.0,
-                        movement_speed: player.1.movement_speed,
-                        offset: offset,
-                    });
-
-                    commands.trigger(PlayerStartedMoving {});
-                }
+        // Only move the player if there is a destination tile at the destination (or below it).
+        // Otherwise the player could fall off the island.
+        let position = (player.x, player.y, player.z);
+        if board.can_step(position, direction, &keys) {
+            if let Some(gate) = board
+                .edges
+                .get(&step(position, direction))
+                .and_then(|edges| edges.gate)
+                && let Some(index) = keys.iter().position(|key| *key == gate)
+            {
+                keys.remove(index);
             }
-            None => {}
-        };
+
+            commands.entity(entity).insert(Movement {
+                animation_percentage: 0.0,
+                movement_speed: player.movement_speed,
+                offset: offset,
+                is_sliding: false,
+            });
+
+            commands.trigger(PlayerStartedMoving { direction });
+            has_any_player_moved = true;
+        }
+    }
+
+    // The level did not turn out as planned, so the rest of the plan is no longer valid.
+    if !has_any_player_moved {
+        planned_moves.directions.clear();
     }
 }
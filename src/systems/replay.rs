@@ -0,0 +1,240 @@
+use std::{fs, path::PathBuf};
+
+use bevy::prelude::*;
+
+use crate::{
+    components::{
+        camera::{CameraAngle, RotationDirection},
+        goal::Goal,
+        level::{Level, LevelEntityMarker},
+        movement::Movement,
+        replay::ReplayTextMarker,
+    },
+    resources::{
+        input::{ActionInput, InputAction},
+        levels::{LevelResource, LevelState},
+        pathfinding::PlannedMoves,
+        replay::{ReplayFile, ReplayPlayback, ReplayRecorder, ReplayStep},
+        settings::Settings,
+    },
+    systems::level::spawn_level,
+};
+
+fn get_replay_path(settings: &Settings, file_name: String) -> PathBuf {
+    PathBuf::from(&settings.replays.directory).join(file_name)
+}
+
+fn write_replay(path: &PathBuf, replay: &ReplayFile) {
+    let contents = toml::to_string(replay).expect("Could not serialize replay");
+
+    let result =
+        fs::create_dir_all(path.parent().unwrap_or(path)).and_then(|_| fs::write(path, &contents));
+
+    match result {
+        Ok(_) => info!("Saved replay to {}:\n{contents}", path.display()),
+        Err(error) => warn!("Could not save replay to {}: {error}", path.display()),
+    }
+}
+
+fn spawn_replay_text(commands: &mut Commands, asset_server: &AssetServer, text: String) {
+    commands.spawn((
+        ReplayTextMarker,
+        LevelEntityMarker,
+        Text::new(text),
+        TextFont {
+            font: asset_server.load("fonts/main.ttf"),
+            font_size: 28.0,
+            ..default()
+        },
+        TextShadow::default(),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: px(20),
+            right: px(20),
+            ..default()
+        },
+    ));
+}
+
+pub fn save_completed_replay(
+    goals: Query<&Goal>,
+    levels: Res<LevelResource>,
+    mut recorder: ResMut<ReplayRecorder>,
+    settings: Res<Settings>,
+) {
+    if !goals.is_empty() || recorder.is_saved || recorder.steps.is_empty() {
+        return;
+    }
+
+    recorder.is_saved = true;
+
+    let level_hash = match levels.get_level_hash() {
+        Ok(level_hash) => level_hash,
+        Err(error) => {
+            warn!("Could not save replay: {error}");
+            return;
+        }
+    };
+
+    write_replay(
+        &get_replay_path(
+            &settings,
+            format!("level-{}.toml", levels.current_level_number),
+        ),
+        &ReplayFile::new(levels.current_level_number, level_hash, &recorder.steps),
+    );
+}
+
+pub fn export_replay(
+    levels: Res<LevelResource>,
+    recorder: Res<ReplayRecorder>,
+    actions: Res<ActionInput>,
+    settings: Res<Settings>,
+) {
+    if !actions.just_pressed(InputAction::ExportReplay) {
+        return;
+    }
+
+    let level_hash = match levels.get_level_hash() {
+        Ok(level_hash) => level_hash,
+        Err(error) => {
+            warn!("Could not export replay: {error}");
+            return;
+        }
+    };
+
+    write_replay(
+        &get_replay_path(
+            &settings,
+            format!("level-{}-export.toml", levels.current_level_number),
+        ),
+        &ReplayFile::new(levels.current_level_number, level_hash, &recorder.steps),
+    );
+}
+
+pub fn start_replay(
+    mut commands: Commands,
+    mut playback: ResMut<ReplayPlayback>,
+    levels: Res<LevelResource>,
+    entities: Query<(&LevelEntityMarker, Entity), Without<Level>>,
+    texts: Query<Entity, With<ReplayTextMarker>>,
+    asset_server: Res<AssetServer>,
+    actions: Res<ActionInput>,
+    settings: Res<Settings>,
+) {
+    if !actions.just_pressed(InputAction::PlayReplay)
+        || !matches!(levels.level_state, LevelState::WaitingForPlayerInput)
+    {
+        return;
+    }
+
+    for entity in texts {
+        commands.entity(entity).despawn();
+    }
+
+    let path = get_replay_path(
+        &settings,
+        format!("level-{}.toml", levels.current_level_number),
+    );
+
+    let steps = fs::read_to_string(&path)
+        .map_err(|error| format!("Could not read replay {}: {error}", path.display()))
+        .and_then(|contents| {
+            toml::from_str::<ReplayFile>(&contents)
+                .map_err(|error| format!("Could not parse replay {}: {error}", path.display()))
+        })
+        .and_then(|replay| {
+            replay.parse_steps(levels.current_level_number, levels.get_level_hash()?)
+        });
+
+    let steps = match steps {
+        Ok(steps) => steps,
+        Err(message) => {
+            warn!("{message}");
+            spawn_replay_text(&mut commands, &asset_server, message);
+            return;
+        }
+    };
+
+    // Replays always start from the beginning of the level.
+    for entity in entities {
+        commands.entity(entity.1).despawn();
+    }
+
+    spawn_level(&levels, &mut commands, &asset_server);
+
+    playback.steps = steps.into();
+
+    spawn_replay_text(
+        &mut commands,
+        &asset_server,
+        format!("Playing replay at {}x", playback.speed),
+    );
+}
+
+pub fn play_replay(
+    mut commands: Commands,
+    mut playback: ResMut<ReplayPlayback>,
+    mut planned_moves: ResMut<PlannedMoves>,
+    mut actions: ResMut<ActionInput>,
+    mut time: ResMut<Time<Virtual>>,
+    cameras: Query<&CameraAngle>,
+    moving_objects: Query<(), With<Movement>>,
+    texts: Query<(Entity, &mut Text), With<ReplayTextMarker>>,
+    levels: Res<LevelResource>,
+    settings: Res<Settings>,
+) {
+    if playback.steps.is_empty() {
+        time.set_relative_speed(1.0);
+        return;
+    }
+
+    // Any manual interaction with the level stops the replay.
+    if actions.just_pressed(InputAction::Restart) || actions.just_pressed(InputAction::Undo) {
+        playback.steps.clear();
+
+        for (entity, _) in texts {
+            commands.entity(entity).despawn();
+        }
+
+        return;
+    }
+
+    if actions.just_pressed(InputAction::ReplayFaster) {
+        playback.speed = (playback.speed * 2.0).min(settings.replays.max_speed);
+    }
+    if actions.just_pressed(InputAction::ReplaySlower) {
+        playback.speed = (playback.speed / 2.0).max(settings.replays.min_speed);
+    }
+
+    time.set_relative_speed(playback.speed);
+
+    // Wait for the previous step to finish, just like a player would.
+    if !matches!(levels.level_state, LevelState::WaitingForPlayerInput)
+        || !moving_objects.is_empty()
+        || !planned_moves.directions.is_empty()
+        || cameras
+            .iter()
+            .any(|camera| camera.rotation_animation_percentage.is_some())
+    {
+        return;
+    }
+
+    match playback.steps.pop_front() {
+        Some(ReplayStep::Move(direction)) => planned_moves.directions.push_back(direction),
+        Some(ReplayStep::Rotate(RotationDirection::Clockwise)) => {
+            actions.press(InputAction::RotateCW);
+        }
+        Some(ReplayStep::Rotate(RotationDirection::CounterClockwise)) => {
+            actions.press(InputAction::RotateCCW);
+        }
+        None => {}
+    }
+
+    for (_, mut text) in texts {
+        text.0 = match playback.steps.is_empty() {
+            true => "Replay finished".to_owned(),
+            false => format!("Playing replay at {}x", playback.speed),
+        };
+    }
+}
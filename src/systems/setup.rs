@@ -1,6 +1,7 @@
 use bevy::{
     asset::{AssetServer, Assets},
     camera::Camera3d,
+    color::{Alpha, Color},
     core_pipeline::tonemapping::Tonemapping,
     ecs::{
         entity::Entity,
@@ -8,9 +9,14 @@ use bevy::{
         system::{Commands, Query, Res, ResMut},
     },
     light::NotShadowReceiver,
-    math::{Vec2, Vec3, Vec4},
-    mesh::{Mesh, MeshBuilder, SphereKind, SphereMeshBuilder},
+    math::{
+        Vec2, Vec3, Vec4,
+        primitives::{Capsule3d, Cuboid, Cylinder, RegularPolygon, Sphere, Torus, Triangle2d},
+    },
+    mesh::{Mesh, MeshBuilder, Meshable, SphereKind, SphereMeshBuilder},
+    pbr::StandardMaterial,
     post_process::bloom::{Bloom, BloomCompositeMode},
+    render::alpha::AlphaMode,
     transform::components::Transform,
     utils::default,
 };
@@ -22,7 +28,7 @@ use bevy_hanabi::{
 use bevy_water::WaterTile;
 
 use crate::{
-    components::camera::CameraAngle,
+    components::{camera::CameraAngle, key::KeyColor, switch::SwitchKind},
     resources::{effects::GlobalEffects, settings::Settings},
 };
 
@@ -31,6 +37,8 @@ pub fn setup(mut commands: Commands, settings: Res<Settings>) {
         Camera3d::default(),
         CameraAngle {
             rotation_speed: settings.camera.rotation_speed,
+            zoom: 1.0,
+            pitch: settings.camera.pitch.to_radians(),
             ..default()
         },
         Transform::from_xyz(0.0, 16.0, 12.0).looking_at(Vec3::ZERO, Vec3::Y),
@@ -108,7 +116,7 @@ pub fn setup_effects(
     .init(init_size_attr)
     .init(init_lifetime)
     .update(update_accel)
-    .mesh(mesh)
+    .mesh(mesh.clone())
     // Render the particles with a color gradient over their
     // lifetime. This maps the gradient key 0 to the particle spawn
     // time, and the gradient key 1 to the particle death (10s).
@@ -119,6 +127,168 @@ pub fn setup_effects(
 
     // Insert into the asset system and save a handle for later use
     effects.goal_explosion_effect = Some(assets.add(effect));
+
+    // A short burst of cyan sparks that rises out of both teleporter pads.
+    let mut gradient = Gradient::new();
+    gradient.add_key(0.0, Vec4::new(0., 1., 1., 1.));
+    gradient.add_key(1.0, Vec4::new(0., 0., 0., 0.));
+
+    let mut module = Module::default();
+
+    let init_pos = SetPositionSphereModifier {
+        center: module.lit(Vec3::ZERO),
+        radius: module.lit(0.4),
+        dimension: ShapeDimension::Surface,
+    };
+
+    // Centered below the pad, so that the sparks move upwards.
+    let init_vel = SetVelocitySphereModifier {
+        center: module.lit(Vec3::new(0., -1., 0.)),
+        speed: module.lit(2.),
+    };
+
+    let lifetime = module.lit(1.);
+    let init_lifetime = SetAttributeModifier::new(Attribute::LIFETIME, lifetime);
+
+    let init_size_attr = SetAttributeModifier {
+        attribute: Attribute::SIZE,
+        value: module.lit(0.05),
+    };
+
+    let effect = EffectAsset::new(4096, SpawnerSettings::once(200.0.into()), module)
+        .with_name("TeleportEffect")
+        .init(init_pos)
+        .init(init_vel)
+        .init(init_size_attr)
+        .init(init_lifetime)
+        .mesh(mesh)
+        .render(ColorOverLifetimeModifier {
+            gradient,
+            ..default()
+        });
+
+    effects.teleport_effect = Some(assets.add(effect));
+}
+
+pub fn setup_prediction_ghosts(
+    mut effects: ResMut<GlobalEffects>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    settings: Res<Settings>,
+) {
+    effects.prediction_ghost_tile_mesh = Some(meshes.add(RegularPolygon::new(0.9, 6)));
+    effects.prediction_ghost_object_mesh = Some(meshes.add(Sphere::new(0.25)));
+    effects.prediction_ghost_material = Some(materials.add(StandardMaterial {
+        base_color: Color::srgba(1.0, 1.0, 1.0, settings.display.prediction_ghost.alpha),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    }));
+}
+
+pub fn setup_conveyor_arrows(
+    mut effects: ResMut<GlobalEffects>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // The arrow points along the y axis, and is laid flat when it is placed on a conveyor.
+    effects.conveyor_arrow_mesh = Some(meshes.add(Triangle2d::new(
+        Vec2::new(0.0, 0.3),
+        Vec2::new(-0.25, -0.1),
+        Vec2::new(0.25, -0.1),
+    )));
+    effects.conveyor_arrow_material = Some(materials.add(StandardMaterial {
+        base_color: Color::hsl(50.0, 1.0, 0.6),
+        unlit: true,
+        ..default()
+    }));
+}
+
+pub fn setup_tile_edges(
+    mut effects: ResMut<GlobalEffects>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // As wide as a side of a hex, and thin along the direction that it blocks.
+    effects.wall_mesh = Some(meshes.add(Cuboid::new(1.0, 0.8, 0.1)));
+    effects.wall_material = Some(materials.add(StandardMaterial {
+        base_color: Color::hsl(30.0, 0.3, 0.45),
+        perceptual_roughness: 0.9,
+        ..default()
+    }));
+    // One-way tiles reuse the conveyor arrow mesh, in a different color.
+    effects.one_way_arrow_material = Some(materials.add(StandardMaterial {
+        base_color: Color::hsl(0.0, 0.0, 0.95),
+        unlit: true,
+        ..default()
+    }));
+}
+
+pub fn setup_keys(
+    mut effects: ResMut<GlobalEffects>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    effects.key_mesh = Some(meshes.add(Torus::new(0.08, 0.2)));
+    // A hex prism that fills the tile, through which the tile below can still be seen.
+    effects.gate_mesh = Some(meshes.add(Cylinder::new(0.8, 0.8).mesh().resolution(6).build()));
+
+    for color in KeyColor::ALL {
+        let key_material = materials.add(StandardMaterial {
+            base_color: color.color(),
+            emissive: color.color().to_linear() * 0.5,
+            metallic: 0.8,
+            ..default()
+        });
+        let gate_material = materials.add(StandardMaterial {
+            base_color: color.color().with_alpha(0.4),
+            alpha_mode: AlphaMode::Blend,
+            ..default()
+        });
+
+        effects.key_materials.insert(color, key_material);
+        effects.gate_materials.insert(color, gate_material);
+    }
+}
+
+pub fn setup_enemies(
+    mut effects: ResMut<GlobalEffects>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    effects.enemy_mesh = Some(meshes.add(Capsule3d::new(0.2, 0.3)));
+    effects.enemy_material = Some(materials.add(StandardMaterial {
+        base_color: Color::hsl(350.0, 0.8, 0.35),
+        emissive: Color::hsl(350.0, 1.0, 0.2).to_linear(),
+        ..default()
+    }));
+}
+
+pub fn setup_switches(
+    mut effects: ResMut<GlobalEffects>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // A flat hex in the middle of the tile, and a ring around the edge of the tile for pads.
+    effects.switch_mesh = Some(meshes.add(Cylinder::new(0.45, 0.08).mesh().resolution(6).build()));
+    effects.teleporter_mesh = Some(meshes.add(Torus::new(0.5, 0.65)));
+    effects.teleporter_material = Some(materials.add(StandardMaterial {
+        base_color: Color::hsl(180.0, 0.9, 0.6),
+        emissive: Color::hsl(180.0, 1.0, 0.3).to_linear(),
+        ..default()
+    }));
+
+    for (kind, hue) in [(SwitchKind::Plate, 30.0), (SwitchKind::Toggle, 280.0)] {
+        for is_on in [false, true] {
+            let lightness = if is_on { 0.7 } else { 0.3 };
+            let material = materials.add(StandardMaterial {
+                base_color: Color::hsl(hue, 0.9, lightness),
+                ..default()
+            });
+
+            effects.switch_materials.insert((kind, is_on), material);
+        }
+    }
 }
 
 pub fn enable_water_shadows(
@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::{
+        switch::{Bridge, Switch, SwitchMesh},
+        tile::HiddenTile,
+    },
+    resources::{effects::GlobalEffects, switches::SwitchStates},
+};
+
+/// Just above the top of an icy tile, so that switches can be seen on ice as well.
+const SWITCH_HEIGHT: f32 = 0.08;
+
+/// Shows and hides the tiles of bridges according to their switches. The switches themselves
+/// are updated in `on_enemies_finished_moving`, and restored by `undo_turn`.
+pub fn update_bridges(
+    mut commands: Commands,
+    switch_states: Res<SwitchStates>,
+    bridges: Query<(Entity, &Bridge, Has<HiddenTile>)>,
+) {
+    for (entity, bridge, is_hidden) in bridges {
+        let is_shown = bridge.is_shown(&switch_states);
+
+        if is_shown && is_hidden {
+            commands
+                .entity(entity)
+                .remove::<HiddenTile>()
+                .insert(Visibility::Inherited);
+        } else if !is_shown && !is_hidden {
+            commands
+                .entity(entity)
+                .insert((HiddenTile, Visibility::Hidden));
+        }
+    }
+}
+
+/// The switch itself is invisible, like keys. Its mesh is colored by `colorize_switches`.
+pub fn add_switch_meshes(
+    mut commands: Commands,
+    switches: Query<Entity, Added<Switch>>,
+    effects: Res<GlobalEffects>,
+) {
+    let Some(mesh) = &effects.switch_mesh else {
+        return;
+    };
+
+    for entity in switches {
+        commands.entity(entity).with_child((
+            SwitchMesh,
+            Mesh3d(mesh.clone()),
+            Transform::from_translation(Vec3::Y * SWITCH_HEIGHT),
+        ));
+    }
+}
+
+/// Switches are turned on and off during a turn and back by undoing it, so their colors follow the
+/// switch states.
+pub fn colorize_switches(
+    mut commands: Commands,
+    switches: Query<(&Switch, &Children)>,
+    switch_meshes: Query<Option<&MeshMaterial3d<StandardMaterial>>, With<SwitchMesh>>,
+    switch_states: Res<SwitchStates>,
+    effects: Res<GlobalEffects>,
+) {
+    for (switch, children) in switches {
+        let state = (switch.kind, switch_states.is_on(&switch.id));
+        let Some(material) = effects.switch_materials.get(&state) else {
+            continue;
+        };
+
+        for child in children {
+            if let Ok(current) = switch_meshes.get(*child)
+                && current.is_none_or(|current| current.0 != *material)
+            {
+                commands
+                    .entity(*child)
+                    .insert(MeshMaterial3d(material.clone()));
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use bevy::prelude::*;
+use bevy_hanabi::ParticleEffect;
+
+use crate::{
+    components::{
+        teleporter::{ObjectTeleported, Teleporter},
+        tile_coordinates::tile_coordinates_to_transform_coordinates,
+    },
+    resources::effects::GlobalEffects,
+};
+
+/// Just above the top of an icy tile, so that pads can be seen on ice as well.
+const PAD_HEIGHT: f32 = 0.11;
+
+/// Shows a burst of sparks at both pads when something is transported.
+pub fn on_object_teleported(
+    teleported: On<ObjectTeleported>,
+    effects: Res<GlobalEffects>,
+    mut commands: Commands,
+) {
+    let Some(effect) = &effects.teleport_effect else {
+        return;
+    };
+
+    for (x, y, z) in [teleported.from, teleported.to] {
+        let mut translation =
+            tile_coordinates_to_transform_coordinates(&Vec3::new(x as f32, y as f32, z as f32));
+        translation.y += 0.6;
+
+        commands.spawn((
+            ParticleEffect::new(effect.clone()),
+            Transform::from_translation(translation),
+        ));
+    }
+}
+
+/// The pad itself is invisible, like keys.
+pub fn add_teleporter_meshes(
+    mut commands: Commands,
+    teleporters: Query<Entity, Added<Teleporter>>,
+    effects: Res<GlobalEffects>,
+) {
+    let (Some(mesh), Some(material)) = (&effects.teleporter_mesh, &effects.teleporter_material)
+    else {
+        return;
+    };
+
+    for entity in teleporters {
+        commands.entity(entity).with_child((
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            Transform::from_translation(Vec3::Y * PAD_HEIGHT),
+        ));
+    }
+}
@@ -1,4 +1,6 @@
-use bevy::prelude::*;
+use std::collections::HashSet;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
 use bevy_gltf::GltfMaterialName;
 use bevy_polyline::prelude::{
     Polyline, PolylineBundle, PolylineHandle, PolylineMaterial, PolylineMaterialHandle,
@@ -6,14 +8,36 @@ use bevy_polyline::prelude::{
 
 use crate::{
     components::{
+        audio::{PlaySoundEffect, SoundEffect},
+        conveyor::Conveyor,
+        enemy::{EnemiesFinishedMoving, Enemy},
+        goal::Goal,
+        key::Key,
+        level::LevelEntityMarker,
         movement::Movement,
         player::{Player, PlayerFinishedMoving, PlayerStartedMoving},
+        switch::{Bridge, Switch, SwitchKind},
+        teleporter::{ObjectTeleported, Teleporter, get_teleporter_exits},
         tile::{
-            Carriable, HasGravity, IcyTile, MovementMap, ShouldRenderMovementMapPolylines, Tile,
+            Carriable, HasGravity, HiddenTile, IcyTile, MovementMap, MovementMapPolyline, Settling,
+            Tile, TileEdges,
+        },
+        tile_coordinates::{
+            MovementDirection, TileCoordinates, tile_coordinates_to_transform_coordinates,
         },
-        tile_coordinates::{TileCoordinates, tile_coordinates_to_transform_coordinates},
     },
-    resources::levels::LevelState,
+    resources::{
+        input::{ActionInput, InputAction},
+        keys::KeyInventory,
+        levels::{LevelSnapshot, LevelState, ObjectSnapshot, UndoHistory},
+        overlays::MovementMapOverlay,
+        replay::{ReplayRecorder, ReplayStep},
+        settings::Settings,
+        stats::LevelStats,
+        switches::SwitchStates,
+        water::Water,
+    },
+    rules::{self, Board, TileStep, add},
 };
 
 use crate::resources::levels::LevelResource;
@@ -74,92 +98,445 @@ pub fn patch_icy_tile_texture(
     }
 }
 
+pub fn toggle_movement_map_overlay(
+    mut overlay: ResMut<MovementMapOverlay>,
+    actions: Res<ActionInput>,
+) {
+    if actions.just_pressed(InputAction::ToggleMovementMapOverlay) {
+        overlay.enabled = !overlay.enabled;
+    }
+}
+
 pub fn draw_moving_tiles_polylines(
     mut commands: Commands,
     mut polyline_materials: ResMut<Assets<PolylineMaterial>>,
     mut polylines: ResMut<Assets<Polyline>>,
-    tiles: Query<(Entity, &TileCoordinates, &MovementMap), With<ShouldRenderMovementMapPolylines>>,
+    overlay: Res<MovementMapOverlay>,
+    settings: Res<Settings>,
+    existing_polylines: Query<Entity, With<MovementMapPolyline>>,
+    tiles: Query<(
+        Entity,
+        Ref<TileCoordinates>,
+        Ref<MovementMap>,
+        Option<&Movement>,
+    )>,
 ) {
-    let polyline_material_handle = PolylineMaterialHandle(
-        polyline_materials.add(PolylineMaterial {
-            width: 300.0,
-            color: Hsla {
-                hue: 0.0,
-                saturation: 1.0,
-                lightness: 1.0,
-                alpha: 0.1,
-            }
-            .into(),
-            perspective: true,
-            ..default()
-        }),
-    );
+    if !overlay.is_changed()
+        && !tiles.iter().any(|(_, tile_coordinates, movement_map, _)| {
+            tile_coordinates.is_changed() || movement_map.is_changed()
+        })
+    {
+        return;
+    }
+
+    for entity in existing_polylines {
+        commands.entity(entity).despawn();
+    }
 
-    for (entity, tile_coordinates, movement_map) in tiles {
+    if !overlay.enabled {
+        return;
+    }
+
+    let mut moving_tiles: Vec<_> = tiles
+        .iter()
+        .filter(|(_, tile_coordinates, movement_map, _)| {
+            tile_coordinates.is_on_top && !movement_map.map.is_empty()
+        })
+        .collect();
+
+    // Sort so that each tile keeps its color when the overlay is redrawn.
+    moving_tiles.sort_by_key(|(entity, ..)| *entity);
+
+    for (tile_number, (_entity, tile_coordinates, movement_map, movement)) in
+        moving_tiles.into_iter().enumerate()
+    {
+        // The index of the movement map is advanced as soon as the tile starts moving,
+        // so the path has to start from where the tile is headed to.
         let mut vertex = Vec3::new(
             tile_coordinates.x as f32,
             tile_coordinates.y as f32,
             tile_coordinates.z as f32,
-        );
+        ) + movement.map_or(Vec3::ZERO, |movement| movement.offset);
 
         let mut vertices = vec![tile_coordinates_to_transform_coordinates(&vertex)];
 
-        for offset in &movement_map.map {
+        for step in 0..movement_map.map.len() {
+            let offset = movement_map.map[(movement_map.index + step) % movement_map.map.len()];
             vertex += Vec3::new(offset.0 as f32, offset.1 as f32, offset.2 as f32);
             vertices.push(tile_coordinates_to_transform_coordinates(&vertex));
         }
 
-        commands.spawn(PolylineBundle {
-            polyline: PolylineHandle(polylines.add(Polyline { vertices: vertices })),
-            material: polyline_material_handle.clone(),
+        // Lift the lines slightly above the tile tops so they are not hidden inside them.
+        for vertex in &mut vertices {
+            vertex.y += 0.65;
+        }
+
+        // Spread the hues using the golden angle, so that neighbouring tiles get distinct colors.
+        let hue = (tile_number as f32 * 137.5).rem_euclid(360.0);
+
+        let path_material = polyline_materials.add(PolylineMaterial {
+            width: settings.display.movement_map_overlay.line_width,
+            color: Color::hsla(hue, 1.0, 0.6, 0.4).into(),
+            perspective: true,
             ..default()
         });
 
-        commands
-            .entity(entity)
-            .remove::<ShouldRenderMovementMapPolylines>();
-    }
-}
+        let next_step_material = polyline_materials.add(PolylineMaterial {
+            width: settings.display.movement_map_overlay.next_step_line_width,
+            color: Color::hsla(hue, 1.0, 0.7, 1.0).into(),
+            perspective: true,
+            ..default()
+        });
+
+        let next_step_vertices = vertices[..2].to_vec();
+
+        commands.spawn((
+            MovementMapPolyline,
+            LevelEntityMarker,
+            PolylineBundle {
+                polyline: PolylineHandle(polylines.add(Polyline { vertices })),
+                material: PolylineMaterialHandle(path_material),
+                ..default()
+            },
+        ));
 
-pub fn on_player_started_moving(_event: On<PlayerStartedMoving>, mut level: ResMut<LevelResource>) {
-    level.level_state = LevelState::ProcessingPlayerInput;
+        commands.spawn((
+            MovementMapPolyline,
+            LevelEntityMarker,
+            PolylineBundle {
+                polyline: PolylineHandle(polylines.add(Polyline {
+                    vertices: next_step_vertices,
+                })),
+                material: PolylineMaterialHandle(next_step_material),
+                ..default()
+            },
+        ));
+    }
 }
 
-pub fn on_players_finished_moving(
-    _event: On<PlayerFinishedMoving>,
-    query: Query<
+pub fn on_player_started_moving(
+    event: On<PlayerStartedMoving>,
+    objects: Query<
         (
-            &TileCoordinates,
-            &mut MovementMap,
-            Option<&Movement>,
             Entity,
+            &TileCoordinates,
+            Option<&MovementMap>,
+            Option<&Enemy>,
         ),
-        Without<Carriable>,
+        Or<(With<Player>, With<MovementMap>, With<Enemy>)>,
     >,
-    carriables: Query<(&TileCoordinates, &Carriable, Entity), Without<Movement>>,
+    goals: Query<&TileCoordinates, With<Goal>>,
+    keys: Query<(&Key, &TileCoordinates)>,
+    gates: Query<(Entity, &TileEdges)>,
+    switch_states: Res<SwitchStates>,
+    water: Res<Water>,
+    inventory: Res<KeyInventory>,
+    mut stats: ResMut<LevelStats>,
+    mut history: ResMut<UndoHistory>,
+    mut recorder: ResMut<ReplayRecorder>,
     mut level: ResMut<LevelResource>,
     mut commands: Commands,
 ) {
-    level.level_state = LevelState::ProcessingLevelEffects;
+    // Every moving player triggers this event, but the turn should only be recorded once.
+    if matches!(level.level_state, LevelState::WaitingForPlayerInput) {
+        recorder.steps.push(ReplayStep::Move(event.direction));
+        commands.trigger(PlaySoundEffect {
+            effect: SoundEffect::Move,
+        });
+        stats.moves += 1;
+        history.snapshots.push(LevelSnapshot {
+            objects: objects
+                .iter()
+                .map(
+                    |(entity, tile_coordinates, movement_map, enemy)| ObjectSnapshot {
+                        entity,
+                        coordinates: (tile_coordinates.x, tile_coordinates.y, tile_coordinates.z),
+                        movement_map_index: movement_map.map(|movement_map| movement_map.index),
+                        route_index: enemy.map(|enemy| enemy.route_index),
+                    },
+                )
+                .collect(),
+            goals: goals.iter().map(|goal| (goal.x, goal.y, goal.z)).collect(),
+            switch_states: switch_states.clone(),
+            water: water.clone(),
+            keys: keys
+                .iter()
+                .map(|(key, tile)| ((tile.x, tile.y, tile.z), key.color))
+                .collect(),
+            inventory: inventory.clone(),
+            gates: gates
+                .iter()
+                .filter_map(|(entity, edges)| Some((entity, edges.gate?)))
+                .collect(),
+        });
+    }
+
+    level.level_state = LevelState::PlayerMoving;
+}
+
+/// The tiles that cannot follow their movement map this turn, following `rules::get_blocked_tiles`.
+/// Tiles that wait for their switch stay where they are as well.
+pub fn get_blocked_tiles<'a>(
+    tiles: impl Iterator<Item = (Entity, &'a TileCoordinates, &'a MovementMap)> + Clone,
+    switch_states: &SwitchStates,
+) -> HashSet<Entity> {
+    let moving_tiles: Vec<TileStep<Entity, &String>> = tiles
+        .clone()
+        .filter(|(_, _, movement_map)| movement_map.is_running(switch_states))
+        .filter_map(|(entity, tile, movement_map)| {
+            let offset = movement_map.next_offset()?;
+            let position = (tile.x, tile.y, tile.z);
+            (offset != (0, 0, 0)).then_some(TileStep {
+                tile: entity,
+                position,
+                destination: add(position, offset),
+                platform: movement_map.platform.as_ref(),
+            })
+        })
+        .collect();
+
+    let still_tiles = tiles
+        .filter(|(entity, _, _)| !moving_tiles.iter().any(|tile| tile.tile == *entity))
+        .map(|(_, tile, _)| (tile.x, tile.y, tile.z))
+        .collect();
+
+    rules::get_blocked_tiles(&moving_tiles, still_tiles)
+}
+
+/// The tiles with the component that are not in the middle of a step.
+type StillTiles<'w, 's, T> =
+    Query<'w, 's, (&'static TileCoordinates, Entity), (With<T>, Without<Movement>)>;
+
+/// The tiles, pads, conveyors and keys that stand still, from which the systems that move objects
+/// build the `Board` that the rules are played on. The water is left out, so the systems that need
+/// it set its height themselves.
+#[derive(SystemParam)]
+pub struct BoardTiles<'w, 's> {
+    tiles: StillTiles<'w, 's, Tile>,
+    edges: Query<'w, 's, &'static TileEdges>,
+    icy_tiles: StillTiles<'w, 's, IcyTile>,
+    bridges: Query<'w, 's, &'static Bridge>,
+    hidden_tiles: Query<'w, 's, (), With<HiddenTile>>,
+    teleporters: Query<'w, 's, (&'static Teleporter, &'static TileCoordinates), Without<Movement>>,
+    conveyors: Query<'w, 's, (&'static Conveyor, &'static TileCoordinates), Without<Movement>>,
+    keys: Query<'w, 's, (&'static Key, &'static TileCoordinates), Without<Movement>>,
+}
+
+impl BoardTiles<'_, '_> {
+    /// The board without the tiles that are hidden.
+    pub fn get(&self) -> Board {
+        self.get_board(|_, is_hidden| !is_hidden)
+    }
+
+    /// The board with the bridges shown according to the switch states. This is used before
+    /// `update_bridges` has shown or hidden them.
+    pub fn get_for_switches(&self, switch_states: &SwitchStates) -> Board {
+        self.get_board(|bridge, _| bridge.is_none_or(|bridge| bridge.is_shown(switch_states)))
+    }
+
+    fn get_board(&self, is_shown: impl Fn(Option<&Bridge>, bool) -> bool) -> Board {
+        let mut board = Board {
+            // Levels with unpaired pads are refused when they are rendered.
+            teleporter_exits: get_teleporter_exits(
+                self.teleporters
+                    .iter()
+                    .map(|(teleporter, tile)| (teleporter.id.as_str(), (tile.x, tile.y, tile.z))),
+            )
+            .unwrap_or_default(),
+            conveyors: self
+                .conveyors
+                .iter()
+                .map(|(conveyor, tile)| ((tile.x, tile.y, tile.z), conveyor.direction))
+                .collect(),
+            keys_on_tiles: self
+                .keys
+                .iter()
+                .map(|(key, tile)| ((tile.x, tile.y, tile.z), key.color))
+                .collect(),
+            ..default()
+        };
+
+        let is_shown = |entity: Entity| {
+            is_shown(
+                self.bridges.get(entity).ok(),
+                self.hidden_tiles.contains(entity),
+            )
+        };
+
+        for (tile, entity) in &self.tiles {
+            if is_shown(entity) {
+                let edges = self.edges.get(entity).ok();
+                board.add_tile((tile.x, tile.y, tile.z), tile.is_on_top, edges);
+            }
+        }
+
+        for (tile, entity) in &self.icy_tiles {
+            if is_shown(entity) {
+                board.icy_tiles.insert((tile.x, tile.y, tile.z));
+            }
+        }
+
+        board
+    }
+}
+
+/// The tiles that follow a movement map, with whether they are still in the middle of a step.
+type MovementMapTile = (
+    &'static TileCoordinates,
+    &'static mut MovementMap,
+    Entity,
+    Option<&'static Bridge>,
+    Has<Movement>,
+);
+
+/// The steps that the environment takes at the end of a turn, worked out from where everything is
+/// before it moves. `on_enemies_finished_moving` plays them, and the prediction ghosts show them.
+pub struct EnvironmentSteps {
+    /// The switch states once the switches have been pressed by whatever stands on them.
+    pub switch_states: SwitchStates,
+    /// The tiles that follow their movement map, with their offset. A tile that waits this turn has
+    /// an offset of zero.
+    pub tiles: Vec<(Entity, (isize, isize, isize))>,
+    /// The objects that a conveyor pushes, with the direction of the push.
+    pub pushes: Vec<(Entity, MovementDirection)>,
+}
+
+/// The tiles, switches and objects that decide how the environment moves at the end of a turn.
+#[derive(SystemParam)]
+pub struct Environment<'w, 's> {
+    tiles: Query<'w, 's, MovementMapTile, Without<Carriable>>,
+    carriables:
+        Query<'w, 's, (&'static TileCoordinates, Entity), (With<Carriable>, Without<Movement>)>,
+    switches: Query<'w, 's, (&'static Switch, &'static TileCoordinates)>,
+    players: Query<'w, 's, (), With<Player>>,
+    board_tiles: BoardTiles<'w, 's>,
+    inventory: Res<'w, KeyInventory>,
+}
+
+impl Environment<'_, '_> {
+    pub fn get_steps(&self, switch_states: &SwitchStates) -> EnvironmentSteps {
+        // Switches are pressed by anything that ended up on them, before any tile moves.
+        let mut switch_states = switch_states.clone();
+        let occupied: HashSet<(isize, isize, isize)> = self
+            .carriables
+            .iter()
+            .map(|(tile, _)| (tile.x, tile.y, tile.z))
+            .collect();
+        let switch_positions: Vec<(&str, SwitchKind, (isize, isize, isize))> = self
+            .switches
+            .iter()
+            .map(|(switch, tile)| (switch.id.as_str(), switch.kind, (tile.x, tile.y, tile.z)))
+            .collect();
+        switch_states.update(&switch_positions, &occupied);
+
+        // Bridges are shown or hidden by `update_bridges` afterwards, but they already count for
+        // this turn.
+        let is_shown =
+            |bridge: Option<&Bridge>| bridge.is_none_or(|bridge| bridge.is_shown(&switch_states));
+        let board = self.board_tiles.get_for_switches(&switch_states);
+
+        let blocked_tiles = get_blocked_tiles(
+            self.tiles
+                .iter()
+                .filter(|(.., bridge, _)| is_shown(*bridge))
+                .map(|(tile, movement_map, entity, ..)| (entity, tile, movement_map)),
+            &switch_states,
+        );
+
+        // Conveyors push anything resting on them while the tiles move. Players use their keys.
+        let pushes = self
+            .carriables
+            .iter()
+            .filter_map(|(tile, entity)| {
+                let keys = if self.players.contains(entity) {
+                    &self.inventory.keys[..]
+                } else {
+                    &[]
+                };
+
+                Some((
+                    entity,
+                    board.get_conveyor_push((tile.x, tile.y, tile.z), keys)?,
+                ))
+            })
+            .collect();
+
+        // A tile that is still moving is not given a second movement. A blocked tile stays where
+        // it is and tries the same step again next turn, so that it does not stray from its path.
+        let tiles = self
+            .tiles
+            .iter()
+            .filter(|(_, movement_map, entity, bridge, is_moving)| {
+                !is_moving
+                    && is_shown(*bridge)
+                    && movement_map.is_running(&switch_states)
+                    && !blocked_tiles.contains(entity)
+            })
+            .filter_map(|(_, movement_map, entity, ..)| Some((entity, movement_map.next_offset()?)))
+            .collect();
+
+        EnvironmentSteps {
+            switch_states,
+            tiles,
+            pushes,
+        }
+    }
+}
+
+pub fn on_enemies_finished_moving(
+    _event: On<EnemiesFinishedMoving>,
+    mut environment: Environment,
+    mut switch_states: ResMut<SwitchStates>,
+    mut water: ResMut<Water>,
+    mut commands: Commands,
+) {
+    let steps = environment.get_steps(&switch_states);
+    *switch_states = steps.switch_states;
+
+    // The water rises or falls together with the tiles. Anything it reaches drowns in `drown_players`.
+    if let Some(step) = water.next_step(&switch_states) {
+        water.height = water.height.map(|height| height + step);
+        water.movement_map.index += 1;
+    }
+
+    for (entity, direction) in steps.pushes {
+        let Ok((carriable_coordinates, _)) = environment.carriables.get(entity) else {
+            continue;
+        };
+
+        commands.entity(entity).insert(Movement {
+            offset: direction.get_tile_coordinate_offset(),
+            movement_speed: carriable_coordinates.movement_speed,
+            animation_percentage: 0.0,
+            is_sliding: false,
+        });
+    }
 
     // Apply movement maps
-    for (tile, mut movement_map, existing_movement, entity) in query {
-        if movement_map.map.is_empty() || existing_movement.is_some() {
+    let mut has_moving_tiles = false;
+    for (entity, offset) in steps.tiles {
+        let Ok((tile, mut movement_map, ..)) = environment.tiles.get_mut(entity) else {
             continue;
-        }
+        };
+
+        movement_map.index += 1;
 
-        let offset = movement_map.map[movement_map.index % movement_map.map.len()];
+        // The tile waits for this turn.
+        if offset == (0, 0, 0) {
+            continue;
+        }
 
         let movement = Movement {
             offset: Vec3::new(offset.0 as f32, offset.1 as f32, offset.2 as f32),
             movement_speed: tile.movement_speed,
             animation_percentage: 0.0,
+            is_sliding: false,
         };
 
         commands.entity(entity).insert(movement.clone());
-        movement_map.index += 1;
+        has_moving_tiles = true;
 
-        for (carriable_coordinates, _carriable, carriable_entity) in carriables {
+        for (carriable_coordinates, carriable_entity) in &environment.carriables {
             if carriable_coordinates.x == tile.x
                 && carriable_coordinates.y == tile.y
                 && carriable_coordinates.z == tile.z
@@ -168,23 +545,37 @@ pub fn on_players_finished_moving(
             }
         }
     }
+
+    if has_moving_tiles {
+        commands.trigger(PlaySoundEffect {
+            effect: SoundEffect::PlatformMove,
+        });
+    }
 }
 
+/// The objects in the middle of a step, with whether they have gravity and whether they are players.
+type MovingObject = (
+    &'static mut TileCoordinates,
+    &'static mut Movement,
+    Has<HasGravity>,
+    Has<Player>,
+    Entity,
+);
+
 pub fn apply_movement(
     mut commands: Commands,
-    moving_objects: Query<(
-        &mut TileCoordinates,
-        &mut Movement,
-        Option<&HasGravity>,
-        Option<&Player>,
-        Entity,
-    )>,
-    still_objects: Query<(&TileCoordinates, Option<&Tile>, Option<&IcyTile>), Without<Movement>>,
-    mut level: ResMut<LevelResource>,
+    moving_objects: Query<MovingObject>,
+    board_tiles: BoardTiles,
+    resting_carriables: Query<&TileCoordinates, (With<Carriable>, Without<Movement>)>,
+    level: Res<LevelResource>,
+    inventory: Res<KeyInventory>,
     timer: Res<Time>,
 ) {
-    let mut all_moving_players_finished_moving = true;
-    let mut all_moving_objects_finished_moving = true;
+    let board = board_tiles.get();
+    let resting: Vec<(isize, isize, isize)> = resting_carriables
+        .iter()
+        .map(|carriable| (carriable.x, carriable.y, carriable.z))
+        .collect();
 
     for (mut tile_coordinates, mut movement, has_gravity, is_player, entity) in moving_objects {
         let animation_percentage = movement.animation_percentage;
@@ -200,79 +591,144 @@ pub fn apply_movement(
 
             movement.animation_percentage -= 1.0;
 
-            let next_tile = (
-                tile_coordinates.x + movement.offset.x as isize,
-                tile_coordinates.y + movement.offset.y as isize,
-                tile_coordinates.z + movement.offset.z as isize,
-            );
+            // An object that ends a step on a teleporter pad comes out of the other pad, unless something
+            // is standing there. It keeps its movement, so that it slides on if the other pad is icy.
+            let position = (tile_coordinates.x, tile_coordinates.y, tile_coordinates.z);
+            let exit = board.teleport(position, |exit| resting.contains(&exit));
+            if has_gravity && exit != position {
+                (tile_coordinates.x, tile_coordinates.y, tile_coordinates.z) = exit;
+                commands.trigger(ObjectTeleported {
+                    entity,
+                    from: position,
+                    to: exit,
+                });
+            }
 
             // If the object is landing on an icy tile, and it can continue onwards, then make it slide onward.
-            if still_objects.iter().any(|object| {
-                object.2.is_some()
-                    && object.0.x == tile_coordinates.x
-                    && object.0.y == tile_coordinates.y
-                    && object.0.z == tile_coordinates.z
-            }) && !still_objects.iter().any(|object| {
-                object.1.is_some()
-                    && object.0.x == next_tile.0
-                    && object.0.y == next_tile.1 + 1
-                    && object.0.z == next_tile.2
-            }) {
-                all_moving_objects_finished_moving = false;
-
-                if is_player.is_some() {
-                    all_moving_players_finished_moving = false;
+            // Players can slide on through the gates they hold a key for, after they have used or
+            // collected any key where they are now.
+            let position = (tile_coordinates.x, tile_coordinates.y, tile_coordinates.z);
+            let mut keys = vec![];
+            if is_player {
+                keys = inventory.keys.clone();
+                board.arrive(position, &mut keys);
+            }
+
+            if MovementDirection::from_offset(movement.offset)
+                .is_some_and(|direction| board.slides_on(position, direction, &keys))
+            {
+                if !movement.is_sliding {
+                    movement.is_sliding = true;
+                    commands.trigger(PlaySoundEffect {
+                        effect: SoundEffect::Slide,
+                    });
                 }
                 continue;
             }
 
-            commands.entity(entity).remove::<Movement>();
-
-            // If there is no tile at the destination tile, the object is going to fall.
-            // NOTE: We only do this in the `ProcessingPlayerInput` phase right now, because
-            // otherwise there is a race condition with moving tiles that finish moving and have their coordinates updated.
-            // This check should technically come _after_ those tiles are done moving.
-            if has_gravity.is_some()
-                && !still_objects.iter().any(|object| {
-                    object.0.is_on_top
-                        && object.0.x == tile_coordinates.x
-                        && object.0.y == tile_coordinates.y
-                        && object.0.z == tile_coordinates.z
-                })
-                && matches!(level.level_state, LevelState::ProcessingPlayerInput)
+            // A falling object keeps falling until it lands on a tile or a resting object. Nothing
+            // else moves while objects fall, so the tiles below it are where they will stay for the
+            // rest of the turn.
+            if has_gravity
+                && level.level_state.is_falling()
+                && movement.offset.y < 0.0
+                && !board.is_held_up(position, &resting)
             {
-                commands.entity(entity).insert(Movement {
-                    offset: Vec3::new(0., -1., 0.),
-                    movement_speed: tile_coordinates.falling_speed,
-                    animation_percentage: 0.0,
-                });
+                continue;
+            }
+
+            commands.entity(entity).remove::<Movement>();
 
-                all_moving_objects_finished_moving = false;
+            if has_gravity {
+                commands.entity(entity).insert(Settling);
 
-                if is_player.is_some() {
-                    all_moving_players_finished_moving = false;
+                if level.level_state.is_falling() && movement.offset.y < 0.0 {
+                    commands.trigger(PlaySoundEffect {
+                        effect: SoundEffect::Land,
+                    });
                 }
             }
-        } else {
-            all_moving_objects_finished_moving = false;
-
-            if is_player.is_some() {
-                all_moving_players_finished_moving = false;
-            }
         }
     }
+}
 
-    if all_moving_players_finished_moving
-        && matches!(level.level_state, LevelState::ProcessingPlayerInput)
-    {
-        commands.trigger(PlayerFinishedMoving {});
+/// Objects that finished a step during the phase start to fall once the phase is over, if they
+/// ended up without a tile or a resting object below them. At the end of the environment phase,
+/// this goes for every object with gravity. `apply_movement` keeps them falling until they land.
+pub fn apply_gravity(
+    mut commands: Commands,
+    settling_objects: Query<(&TileCoordinates, Entity), With<Settling>>,
+    board_tiles: BoardTiles,
+    resting_carriables: Query<(&TileCoordinates, Entity), (With<Carriable>, Without<Movement>)>,
+    level: Res<LevelResource>,
+) {
+    if !level.level_state.is_falling() {
+        return;
+    }
+
+    // Objects that have not landed yet cannot hold up anything else, so a stack of objects
+    // without a tile below it falls as a whole.
+    let board = board_tiles.get();
+    let resting: Vec<(isize, isize, isize)> = resting_carriables
+        .iter()
+        .filter(|(_, entity)| !settling_objects.contains(*entity))
+        .map(|(carriable, _)| (carriable.x, carriable.y, carriable.z))
+        .collect();
+
+    for (tile_coordinates, entity) in settling_objects {
+        commands.entity(entity).remove::<Settling>();
+
+        let position = (tile_coordinates.x, tile_coordinates.y, tile_coordinates.z);
+        if !board.is_held_up(position, &resting) {
+            commands.entity(entity).insert(Movement {
+                offset: Vec3::new(0., -1., 0.),
+                movement_speed: tile_coordinates.falling_speed,
+                animation_percentage: 0.0,
+                is_sliding: false,
+            });
+        }
     }
+}
 
-    if all_moving_objects_finished_moving
-        && matches!(level.level_state, LevelState::ProcessingLevelEffects)
+/// Moves the turn on to its next phase once nothing is moving anymore. A falling phase also waits
+/// for every object to have landed. This is the only place where a turn moves on, apart from the
+/// players starting to move in `on_player_started_moving`.
+pub fn advance_turn(
+    moving_objects: Query<(), With<Movement>>,
+    settling_objects: Query<(), With<Settling>>,
+    objects_with_gravity: Query<Entity, With<HasGravity>>,
+    mut level: ResMut<LevelResource>,
+    mut commands: Commands,
+) {
+    if !moving_objects.is_empty()
+        || (level.level_state.is_falling() && !settling_objects.is_empty())
     {
-        level.level_state = LevelState::WaitingForPlayerInput;
+        return;
     }
+
+    level.level_state = match level.level_state {
+        LevelState::WaitingForPlayerInput => return,
+        LevelState::PlayerMoving => LevelState::PlayerFalling,
+        LevelState::PlayerFalling => {
+            commands.trigger(PlayerFinishedMoving {});
+            LevelState::EnemiesMoving
+        }
+        LevelState::EnemiesMoving => LevelState::EnemiesFalling,
+        LevelState::EnemiesFalling => {
+            commands.trigger(EnemiesFinishedMoving {});
+            LevelState::EnvironmentMoving
+        }
+        LevelState::EnvironmentMoving => {
+            // Tiles may have moved away or been hidden from under objects that stood still, so
+            // every object checks whether it is still held up.
+            for entity in objects_with_gravity {
+                commands.entity(entity).insert(Settling);
+            }
+
+            LevelState::EnvironmentFalling
+        }
+        LevelState::EnvironmentFalling => LevelState::WaitingForPlayerInput,
+    };
 }
 
 pub fn set_transform_based_on_tile_coordinates(
@@ -0,0 +1,76 @@
+use bevy::prelude::*;
+
+use crate::{
+    resources::{
+        editor::LevelEditor,
+        input::ActionInput,
+        keys::KeyInventory,
+        levels::{LevelResource, TurnSet, UndoHistory},
+        pathfinding::PlannedMoves,
+        replay::ReplayRecorder,
+        stats::LevelStats,
+        switches::SwitchStates,
+        water::Water,
+    },
+    systems::{
+        editor::is_editing,
+        enemies::{catch_players, move_enemies},
+        keys::{collect_keys, open_gates},
+        level::{on_level_started, undo_turn},
+        player::{collect_goals, player_controls},
+        switches::update_bridges,
+        tiles::{
+            advance_turn, apply_gravity, apply_movement, on_enemies_finished_moving,
+            on_player_started_moving,
+        },
+        water::drown_players,
+    },
+};
+
+/// The systems that play a turn, from the input of the players to the last object landing. The
+/// game and the headless tests both use this plugin, so that the tests play turns by the same
+/// schedule as the game.
+pub struct TurnPlugin;
+
+impl Plugin for TurnPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActionInput>()
+            .init_resource::<UndoHistory>()
+            .init_resource::<PlannedMoves>()
+            .init_resource::<ReplayRecorder>()
+            .init_resource::<LevelResource>()
+            .init_resource::<LevelEditor>()
+            .init_resource::<SwitchStates>()
+            .init_resource::<Water>()
+            .init_resource::<KeyInventory>()
+            .init_resource::<LevelStats>()
+            .configure_sets(
+                Update,
+                (TurnSet::Movement, TurnSet::Resolution, TurnSet::Input).chain(),
+            )
+            .add_systems(
+                Update,
+                (
+                    (apply_movement, apply_gravity, advance_turn)
+                        .chain()
+                        .in_set(TurnSet::Movement),
+                    (
+                        collect_goals,
+                        collect_keys,
+                        open_gates.after(collect_keys),
+                        drown_players,
+                        catch_players,
+                        update_bridges.after(undo_turn),
+                    )
+                        .in_set(TurnSet::Resolution),
+                    player_controls
+                        .run_if(not(is_editing))
+                        .in_set(TurnSet::Input),
+                ),
+            )
+            .add_observer(move_enemies)
+            .add_observer(on_enemies_finished_moving)
+            .add_observer(on_player_started_moving)
+            .add_observer(on_level_started);
+    }
+}
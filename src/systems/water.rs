@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+use bevy_water::{WaterSettings, WaterTile};
+
+use crate::{
+    components::{
+        audio::{PlaySoundEffect, SoundEffect},
+        movement::Movement,
+        player::{Drowned, Player},
+        tile_coordinates::{TileCoordinates, tile_coordinates_to_transform_coordinates},
+    },
+    resources::water::{WATER_SURFACE_HEIGHT, Water},
+};
+
+/// How fast the water surface moves to its new height, in units per second.
+const WATER_SPEED: f32 = 1.0;
+
+/// Players that stand still under water drown. Players that are still moving are
+/// checked once they stop, so that they can be carried out of the water in time.
+pub fn drown_players(
+    mut commands: Commands,
+    players: Query<(Entity, &TileCoordinates, Has<Movement>), With<Player>>,
+    drowned_players: Query<(), With<Drowned>>,
+    water: Res<Water>,
+) {
+    for (entity, player, is_moving) in players {
+        if !is_moving && !drowned_players.contains(entity) && water.is_under_water(player.y) {
+            commands.entity(entity).insert(Drowned);
+            commands.trigger(PlaySoundEffect {
+                effect: SoundEffect::Splash,
+            });
+        }
+    }
+}
+
+/// Moves the water surface towards the height of the water. Levels without water keep it where it was.
+pub fn update_water_height(
+    water: Res<Water>,
+    mut water_settings: ResMut<WaterSettings>,
+    water_tiles: Query<&mut Transform, With<WaterTile>>,
+    timer: Res<Time>,
+) {
+    let height = water.height.unwrap_or(0) as f32;
+    let target = WATER_SURFACE_HEIGHT
+        + tile_coordinates_to_transform_coordinates(&Vec3::new(0., height, 0.)).y;
+
+    let difference = target - water_settings.height;
+    if difference == 0.0 {
+        return;
+    }
+
+    let step = difference.clamp(
+        -WATER_SPEED * timer.delta_secs(),
+        WATER_SPEED * timer.delta_secs(),
+    );
+    water_settings.height += step;
+
+    // The water tiles are spawned at the height of the settings, so they are moved along with it.
+    for mut transform in water_tiles {
+        transform.translation.y += step;
+    }
+}
@@ -0,0 +1,51 @@
+use bevy::{audio::Decodable, prelude::*};
+
+use crate::{
+    components::{
+        audio::{PlaySoundEffect, SoundEffect},
+        level::LevelMetadataBiome,
+        tile_coordinates::MovementDirection,
+    },
+    resources::audio::SynthesizedSound,
+    tests::{headless_app, load_level, play_move, test_level},
+};
+
+#[derive(Resource, Default)]
+struct PlayedSoundEffects(Vec<SoundEffect>);
+
+fn record_sound_effect(played: On<PlaySoundEffect>, mut effects: ResMut<PlayedSoundEffects>) {
+    effects.0.push(played.effect);
+}
+
+#[test]
+fn moves_play_the_sounds_of_what_happens_during_the_turn() {
+    let level = test_level("", "1110", &["PII."], "");
+
+    let mut app = headless_app();
+    app.insert_resource(PlayedSoundEffects::default())
+        .add_observer(record_sound_effect);
+    load_level(&mut app, level);
+
+    assert!(play_move(&mut app, MovementDirection::East));
+    assert_eq!(
+        app.world().resource::<PlayedSoundEffects>().0,
+        [SoundEffect::Move, SoundEffect::Slide, SoundEffect::Land]
+    );
+}
+
+#[test]
+fn ambient_loops_repeat_without_a_click() {
+    for biome in LevelMetadataBiome::ALL {
+        let samples: Vec<f32> = SynthesizedSound::ambient_loop(biome).decoder().collect();
+        let largest_step = samples
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).abs())
+            .fold(0.0, f32::max);
+        let (first, last) = (samples[0], samples[samples.len() - 1]);
+
+        assert!(
+            (first - last).abs() <= largest_step,
+            "The {biome:?} loop jumps from {last} back to {first}"
+        );
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{
+    components::level::Level,
+    resources::{editor::EditableLevel, levels::LevelResource},
+    tests::{level_numbers, spawned_objects, test_level},
+};
+
+#[test]
+fn saving_a_level_in_the_editor_keeps_it_the_same() {
+    for level_number in level_numbers() {
+        let levels = LevelResource {
+            current_level_number: level_number,
+            ..Default::default()
+        };
+
+        let saved = EditableLevel::from_level(&levels.get_level().unwrap())
+            .unwrap()
+            .to_toml();
+        let saved_level: Level = toml::from_str(&saved).unwrap_or_else(|error| {
+            panic!("Level {level_number} was saved as invalid TOML: {error}")
+        });
+
+        assert_eq!(
+            EditableLevel::from_level(&saved_level).unwrap().to_toml(),
+            saved,
+            "Saving level {level_number} a second time changed it"
+        );
+        assert_eq!(
+            spawned_objects(saved_level),
+            spawned_objects(levels.get_level().unwrap()),
+            "Level {level_number} changed when it was saved"
+        );
+    }
+}
+
+#[test]
+fn growing_the_grid_keeps_the_tiles_in_place() {
+    let mut level = EditableLevel::from_level(&test_level("", "01\n2.", &["P.\n.G"], "")).unwrap();
+    let tiles_before: Vec<_> = (0..2)
+        .flat_map(|row| (0..2).map(move |col| (row, col)))
+        .map(|(row, col)| {
+            (
+                level.get_tile_coordinates(row, col),
+                level.layers[0].cells[row][col].height,
+            )
+        })
+        .collect();
+
+    let (position, (rows_added, cols_added)) = level.grow_to_include(3, -1);
+    assert_eq!(level.get_dimensions(), (4, 6));
+    assert_eq!(position, (5, 0));
+
+    for (index, (coordinates, height)) in tiles_before.into_iter().enumerate() {
+        let (row, col) = (index / 2 + rows_added, index % 2 + cols_added);
+        assert_eq!(level.get_tile_coordinates(row, col), coordinates);
+        assert_eq!(level.layers[0].cells[row][col].height, height);
+    }
+}
+
+#[test]
+fn movements_are_saved_by_their_shape_until_they_are_edited() {
+    let movements =
+        "[movements.a]\nshape = \"ping_pong\"\ndirection = \"E\"\namplitude = 1\nphase = 1";
+    let mut level = EditableLevel::from_level(&test_level(movements, "00", &["Pa"], "")).unwrap();
+    let saved = level.to_toml();
+    assert!(saved.contains(
+        "[movements.a]\nshape = \"ping_pong\"\ndirection = \"E\"\namplitude = 1\nphase = 1\n"
+    ));
+
+    level.layers[0].cells[0][1].movement_map.push((0, 0, 0));
+    let saved = level.to_toml();
+    assert!(saved.contains(
+        "[movements.a]\nshape = \"offsets\"\noffsets = [[-1, 0, 0], [1, 0, 0], [0, 0, 0]]\n"
+    ));
+}
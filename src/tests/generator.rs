@@ -0,0 +1,76 @@
+use crate::{
+    components::{level::Level, tile_coordinates::MovementDirection},
+    generator::{generate_level, get_daily_seed},
+    resources::{
+        levels::LevelResource,
+        settings::{GeneratorSettings, Settings},
+    },
+    simulation::Simulation,
+    tests::{count_goals, headless_app, level_numbers, load_level, play_move},
+};
+
+/// Plays the moves in the game itself, so that the solver is checked against the real turn rules.
+fn is_won_by(level: Level, moves: &[MovementDirection]) -> bool {
+    let mut app = headless_app();
+    load_level(&mut app, level);
+
+    moves
+        .iter()
+        .all(|direction| play_move(&mut app, *direction))
+        && count_goals(&mut app) == 0
+}
+
+fn generator_settings() -> GeneratorSettings {
+    let settings: Settings =
+        toml::from_str(include_str!("../settings.toml")).expect("Could not parse settings");
+    settings.generator
+}
+
+#[test]
+fn the_solver_finds_a_winning_solution_for_every_level() {
+    for level_number in level_numbers() {
+        let level = LevelResource {
+            current_level_number: level_number,
+            ..Default::default()
+        }
+        .get_level()
+        .unwrap();
+
+        let solution = Simulation::from_level(&level)
+            .unwrap_or_else(|error| panic!("Level {level_number}: {error}"))
+            .solve(1_000_000)
+            .unwrap_or_else(|| panic!("Level {level_number} could not be solved"));
+
+        assert!(
+            is_won_by(level, &solution),
+            "Level {level_number} was not won by the solver's solution"
+        );
+    }
+}
+
+#[test]
+fn generated_levels_are_won_by_their_solution() {
+    let settings = generator_settings();
+
+    for seed in get_daily_seed(20_000)..get_daily_seed(20_000) + 5 {
+        let generated = generate_level(seed, &settings).unwrap_or_else(|error| panic!("{error}"));
+        let level: Level = toml::from_str(&generated.level.to_toml()).unwrap();
+
+        assert!(generated.solution.len() >= settings.min_solution_length);
+        assert!(
+            is_won_by(level, &generated.solution),
+            "The level generated from seed {seed} was not won by its solution"
+        );
+    }
+}
+
+#[test]
+fn the_same_seed_generates_the_same_level() {
+    let settings = generator_settings();
+
+    let first = generate_level(42, &settings).unwrap();
+    let second = generate_level(42, &settings).unwrap();
+
+    assert_eq!(first.level.to_toml(), second.level.to_toml());
+    assert_eq!(first.solution, second.solution);
+}
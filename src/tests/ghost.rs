@@ -0,0 +1,46 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::{
+        ghost::PredictionGhost, tile_coordinates::tile_coordinates_to_transform_coordinates,
+    },
+    resources::{effects::GlobalEffects, settings::Settings},
+    systems::ghost::update_prediction_ghosts,
+    tests::{headless_app, load_level, test_level},
+};
+
+#[test]
+fn prediction_ghosts_show_the_steps_of_the_next_turn() {
+    let settings: Settings =
+        toml::from_str(include_str!("../settings.toml")).expect("Could not parse settings");
+    let mut app = headless_app();
+    app.insert_resource(settings)
+        .add_systems(Update, update_prediction_ghosts);
+
+    let mut effects = app.world_mut().resource_mut::<GlobalEffects>();
+    effects.prediction_ghost_tile_mesh = Some(Handle::default());
+    effects.prediction_ghost_object_mesh = Some(Handle::default());
+    effects.prediction_ghost_material = Some(Handle::default());
+
+    // The tile on the left is blocked by the elevator, which waits this turn, while the player
+    // stands on a conveyor.
+    let movements = "[movements.a]\nshape = \"ping_pong\"\ndirection = \"E\"\namplitude = 1\n\
+        [movements.b]\nshape = \"offsets\"\noffsets = [[0, 0, 0], [0, 1, 0], [0, -1, 0]]";
+    let conveyor = "[[layers.tiles]]\nq = 1\nr = 0\nheight = 0\nplayer = true\nconveyor = \"E\"";
+    load_level(
+        &mut app,
+        test_level(movements, ".00.0", &[".ab.."], conveyor),
+    );
+    app.update();
+
+    let world = app.world_mut();
+    let ghosts: Vec<Vec3> = world
+        .query_filtered::<&Transform, With<PredictionGhost>>()
+        .iter(world)
+        .map(|transform| transform.translation)
+        .collect();
+
+    let pushed = tile_coordinates_to_transform_coordinates(&Vec3::new(2.0, 0.0, 0.0));
+    assert_eq!(ghosts.len(), 1);
+    assert!((ghosts[0].x - pushed.x).abs() < 0.01 && (ghosts[0].z - pushed.z).abs() < 0.01);
+}
@@ -0,0 +1,109 @@
+use bevy::{
+    ecs::system::RunSystemOnce,
+    input::{gamepad::GamepadButton, keyboard::KeyCode},
+    prelude::*,
+};
+
+use crate::{
+    components::level::HelpTextMarker,
+    resources::{
+        input::{ActionInput, InputAction, InputBindings, InputBuffer},
+        levels::{LevelResource, LevelState},
+        settings::Settings,
+    },
+    systems::input::{buffer_input_actions, show_key_hints},
+    tests::{headless_app, load_level, test_level},
+};
+
+#[test]
+fn rebinding_a_key_that_is_in_use_swaps_the_bindings() {
+    let settings: Settings =
+        toml::from_str(include_str!("../settings.toml")).expect("Could not parse settings");
+    let mut bindings = settings.input;
+
+    bindings.bind_key(InputAction::Undo, KeyCode::Backspace);
+    assert_eq!(bindings.keyboard[&InputAction::Undo], [KeyCode::Backspace]);
+    assert_eq!(bindings.keyboard[&InputAction::Restart], [KeyCode::KeyR]);
+
+    bindings.bind_button(InputAction::Undo, GamepadButton::South);
+    assert_eq!(bindings.gamepad[&InputAction::Undo], [GamepadButton::South]);
+    assert_eq!(
+        bindings.gamepad[&InputAction::Confirm],
+        [GamepadButton::Start, GamepadButton::East]
+    );
+}
+
+#[test]
+fn buffered_actions_wait_for_animations_in_the_order_they_were_pressed() {
+    let settings: Settings =
+        toml::from_str(include_str!("../settings.toml")).expect("Could not parse settings");
+    let mut world = World::new();
+    world.insert_resource(settings.input);
+    world.insert_resource(InputBuffer::default());
+    world.insert_resource(LevelResource {
+        level_state: LevelState::PlayerMoving,
+        ..default()
+    });
+    world.insert_resource(ActionInput {
+        just_pressed: vec![InputAction::Undo, InputAction::RotateCW, InputAction::MoveE],
+        ..default()
+    });
+
+    world.run_system_once(buffer_input_actions).unwrap();
+    assert_eq!(
+        world.resource::<InputBuffer>().actions,
+        [InputAction::Undo, InputAction::RotateCW, InputAction::MoveE]
+    );
+    assert!(world.resource::<ActionInput>().just_pressed.is_empty());
+
+    // Once the animation is over, the actions come out one at a time.
+    world.resource_mut::<LevelResource>().level_state = LevelState::WaitingForPlayerInput;
+    world.run_system_once(buffer_input_actions).unwrap();
+    assert_eq!(
+        world.resource::<ActionInput>().just_pressed,
+        [InputAction::Undo]
+    );
+    assert_eq!(
+        world.resource::<InputBuffer>().actions,
+        [InputAction::RotateCW, InputAction::MoveE]
+    );
+
+    // A restart cancels everything before it.
+    world.resource_mut::<ActionInput>().just_pressed = vec![InputAction::Restart];
+    world.resource_mut::<LevelResource>().level_state = LevelState::PlayerMoving;
+    world.run_system_once(buffer_input_actions).unwrap();
+    assert_eq!(
+        world.resource::<InputBuffer>().actions,
+        [InputAction::Restart]
+    );
+    assert!(world.resource::<ActionInput>().just_pressed.is_empty());
+}
+
+#[test]
+fn help_texts_name_the_keys_that_are_bound() {
+    let settings: Settings =
+        toml::from_str(include_str!("../settings.toml")).expect("Could not parse settings");
+    let mut app = headless_app();
+    app.insert_resource(settings.input)
+        .add_systems(Update, show_key_hints);
+
+    let mut level = test_level("", "0", &["P"], "");
+    level.metadata.help_text = "<{Restart}>: Restart Level, {Undo}: Undo".to_owned();
+    load_level(&mut app, level);
+
+    let help_text = |app: &mut App| {
+        app.update();
+        let world = app.world_mut();
+        world
+            .query_filtered::<&Text, With<HelpTextMarker>>()
+            .single(world)
+            .map(|text| text.0.clone())
+            .expect("Expected a single help text")
+    };
+    assert_eq!(help_text(&mut app), "<Backspace>: Restart Level, R: Undo");
+
+    app.world_mut()
+        .resource_mut::<InputBindings>()
+        .bind_key(InputAction::Undo, KeyCode::KeyU);
+    assert_eq!(help_text(&mut app), "<Backspace>: Restart Level, U: Undo");
+}
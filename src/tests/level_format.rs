@@ -0,0 +1,430 @@
+use crate::{
+    components::level::{
+        Level, LevelMovement, MAX_TILE_HEIGHT, MIN_TILE_HEIGHT, get_height_char, parse_height,
+    },
+    resources::{editor::EditableLevel, levels::LevelResource},
+    tests::{level_numbers, spawned_objects},
+};
+
+const METADATA: &str = r#"
+[metadata]
+name = "Level format"
+biome = "DAYLIGHT"
+help_text = ""
+"#;
+
+#[test]
+fn every_height_map_height_is_read_back_the_same() {
+    for height in MIN_TILE_HEIGHT..=MAX_TILE_HEIGHT {
+        let char = get_height_char(height).expect("Height should fit in a height map");
+        assert_eq!(parse_height(char), Ok(Some(height)));
+    }
+
+    assert_eq!(get_height_char(MAX_TILE_HEIGHT + 1), None);
+    assert_eq!(get_height_char(MIN_TILE_HEIGHT - 1), None);
+    assert_eq!(parse_height('.'), Ok(None));
+    assert!(parse_height('#').is_err());
+}
+
+#[test]
+fn listed_tiles_are_the_same_as_height_map_tiles() {
+    let height_map: Level = toml::from_str(&format!(
+        r#"{METADATA}
+[[layers]]
+pillars = true
+height_map = """
+12a
+B0.
+"""
+modifiers = ["""
+P.G
+I..
+"""]
+"#
+    ))
+    .unwrap();
+
+    // The same level, where (q, r) = (0, 0) is the middle of the height map above.
+    let listed_tiles: Level = toml::from_str(&format!(
+        r#"{METADATA}
+[[layers]]
+pillars = true
+
+[[layers.tiles]]
+name = "start"
+q = -1
+r = 1
+height = 1
+player = true
+
+[[layers.tiles]]
+q = 0
+r = 1
+height = 2
+
+[[layers.tiles]]
+name = "tower"
+q = 1
+r = 1
+height = 10
+goal = true
+
+[[layers.tiles]]
+q = -1
+r = 0
+height = -2
+icy = true
+
+[[layers.tiles]]
+q = 0
+r = 0
+height = 0
+"#
+    ))
+    .unwrap();
+
+    assert_eq!(spawned_objects(listed_tiles), spawned_objects(height_map));
+}
+
+#[test]
+fn tiles_beyond_the_height_map_range_are_saved_as_listed_tiles() {
+    let level: Level = toml::from_str(&format!(
+        r#"{METADATA}
+[[layers]]
+pillars = false
+height_map = """
+1.1
+111
+"""
+modifiers = ["""
+P..
+..G
+"""]
+
+[[layers.tiles]]
+q = 0
+r = 1
+height = 40
+icy = true
+movement_map = [[1, 0, 0], [-1, 0, 0]]
+
+[[layers.tiles]]
+q = 0
+r = 0
+height = 5
+"#
+    ))
+    .unwrap();
+
+    let saved = EditableLevel::from_level(&level).unwrap().to_toml();
+    assert!(saved.contains("height = 40"), "{saved}");
+
+    let saved_level: Level = toml::from_str(&saved).unwrap();
+    assert_eq!(spawned_objects(saved_level), spawned_objects(level));
+}
+
+#[test]
+fn switches_teleporters_conveyors_and_walls_are_kept_when_saving() {
+    let level: Level = toml::from_str(&format!(
+        r#"{METADATA}
+[switches.door]
+kind = "toggle"
+on = true
+
+[movements.l]
+shape = "elevator"
+height = 1
+triggered_by = "door"
+
+[[layers]]
+height_map = """
+0.0
+"""
+modifiers = ["""
+P.l
+"""]
+
+[[layers.tiles]]
+q = 0
+r = 0
+height = 0
+switch = "door"
+
+[[layers.tiles]]
+q = 0
+r = 1
+height = 0
+hidden_by = "door"
+
+[[layers.tiles]]
+q = 1
+r = 1
+height = 0
+teleporter = "warp"
+
+[[layers.tiles]]
+q = -1
+r = 1
+height = 2
+teleporter = "warp"
+
+[[layers.tiles]]
+q = 0
+r = 1
+height = 3
+conveyor = "SW"
+
+[[layers.tiles]]
+q = 1
+r = 0
+height = 1
+walls = ["E", "NW"]
+one_way = "W"
+"#
+    ))
+    .unwrap();
+
+    let saved = EditableLevel::from_level(&level).unwrap().to_toml();
+    for expected in [
+        "[switches.door]",
+        "kind = \"toggle\"",
+        "on = true",
+        "triggered_by = \"door\"",
+        "hidden_by = \"door\"",
+        "teleporter = \"warp\"",
+        "conveyor = \"SW\"",
+        "walls = [\"E\", \"NW\"]",
+        "one_way = \"W\"",
+    ] {
+        assert!(saved.contains(expected), "{saved}");
+    }
+
+    let saved_level: Level = toml::from_str(&saved).unwrap();
+    assert_eq!(spawned_objects(saved_level), spawned_objects(level));
+}
+
+#[test]
+fn the_move_limit_is_kept_when_saving() {
+    let level: Level = toml::from_str(&format!(
+        r#"{METADATA}max_moves = 12
+
+[[layers]]
+height_map = "0"
+"#
+    ))
+    .unwrap();
+
+    let saved = EditableLevel::from_level(&level).unwrap().to_toml();
+    assert!(saved.contains("max_moves = 12\n"), "{saved}");
+    assert_eq!(
+        toml::from_str::<Level>(&saved).unwrap().metadata.max_moves,
+        Some(12)
+    );
+}
+
+#[test]
+fn keys_and_gates_are_kept_when_saving() {
+    let level: Level = toml::from_str(&format!(
+        r#"{METADATA}
+[[layers]]
+height_map = "0.."
+modifiers = ["P.."]
+
+[[layers.tiles]]
+q = 0
+r = 0
+height = 0
+key = "blue"
+
+[[layers.tiles]]
+q = 1
+r = 0
+height = 0
+gate = "blue"
+walls = ["E"]
+"#
+    ))
+    .unwrap();
+
+    let saved = EditableLevel::from_level(&level).unwrap().to_toml();
+    for expected in ["key = \"blue\"", "gate = \"blue\""] {
+        assert!(saved.contains(expected), "{saved}");
+    }
+
+    let saved_level: Level = toml::from_str(&saved).unwrap();
+    assert_eq!(spawned_objects(saved_level), spawned_objects(level));
+}
+
+#[test]
+fn water_is_kept_when_saving() {
+    let level: Level = toml::from_str(&format!(
+        r#"{METADATA}
+[switches.pump]
+kind = "plate"
+
+[water]
+height = -1
+
+[water.movement]
+shape = "elevator"
+height = 2
+triggered_by = "pump"
+
+[[layers]]
+height_map = "00"
+"#
+    ))
+    .unwrap();
+
+    let saved = EditableLevel::from_level(&level).unwrap().to_toml();
+    for expected in [
+        "[water]\nheight = -1\n",
+        "[water.movement]",
+        "triggered_by = \"pump\"",
+    ] {
+        assert!(saved.contains(expected), "{saved}");
+    }
+
+    let water = level.get_water().unwrap();
+    let saved_water = toml::from_str::<Level>(&saved)
+        .unwrap()
+        .get_water()
+        .unwrap();
+    assert_eq!(saved_water.height, water.height);
+    assert_eq!(saved_water.movement_map.map, water.movement_map.map);
+    assert_eq!(saved_water.movement_map.switch, water.movement_map.switch);
+}
+
+#[test]
+fn water_can_only_move_up_and_down() {
+    let level: Level = toml::from_str(&format!(
+        r#"{METADATA}
+[water]
+
+[water.movement]
+shape = "line"
+direction = "E"
+amplitude = 1
+
+[[layers]]
+height_map = "0"
+"#
+    ))
+    .unwrap();
+
+    assert!(level.water.unwrap().get_water().is_err());
+}
+
+#[test]
+fn broken_levels_are_reported_instead_of_panicking() {
+    let broken_layers = [
+        "height_map = \"0#\"",
+        "height_map = \"00\"\nmodifiers = [\"P\"]",
+        "height_map = \"0\"\nmodifiers = [\"a\"]",
+        "height_map = \"0\"\nmodifiers = [\"M\"]",
+        "[[layers.tiles]]\nq = 0\nr = 0\nheight = 0\nswitch = \"s\"",
+        "[[layers.tiles]]\nq = 0\nr = 0\nheight = 0\nshown_by = \"s\"\nhidden_by = \"s\"",
+        "[[layers.tiles]]\nq = 0\nr = 0\nheight = 0\nkey = \"red\"\ngate = \"red\"",
+        "[[layers.tiles]]\nq = 0\nr = 0\nheight = 0\nteleporter = \"t\"",
+        "[[layers.tiles]]\nq = 0\nr = 0\nheight = 0\nmovement_map = [[1, 0, 0]]\nconveyor = \"E\"",
+    ];
+
+    for layer in broken_layers {
+        let level: Level = toml::from_str(&format!("{METADATA}\n[[layers]]\n{layer}"))
+            .expect("Could not parse test level");
+
+        assert!(level.validate().is_err(), "{layer}");
+    }
+
+    let level: Level = toml::from_str(&format!(
+        "{METADATA}\n[[layers]]\nheight_map = \"0\"\nmodifiers = [\"a\"]"
+    ))
+    .unwrap();
+    assert!(EditableLevel::from_level(&level).is_err());
+
+    // Continuing after the last level.
+    let levels = LevelResource {
+        current_level_number: level_numbers().last().unwrap() + 1,
+        ..Default::default()
+    };
+    assert!(levels.get_level().is_err());
+}
+
+fn get_movement_map(definition: &str) -> Result<Vec<(isize, isize, isize)>, String> {
+    toml::from_str::<LevelMovement>(definition)
+        .expect("Could not parse movement")
+        .get_movement_map()
+}
+
+#[test]
+fn movement_shapes_are_turned_into_offsets() {
+    assert_eq!(
+        get_movement_map("shape = \"ping_pong\"\ndirection = \"NE\"\namplitude = 2"),
+        Ok(vec![(0, 0, 2), (0, 0, -2)])
+    );
+    assert_eq!(
+        get_movement_map("shape = \"line\"\ndirection = \"W\"\namplitude = 2"),
+        Ok(vec![(-1, 0, 0), (-1, 0, 0), (1, 0, 0), (1, 0, 0)])
+    );
+    assert_eq!(
+        get_movement_map("shape = \"elevator\"\nheight = -1"),
+        Ok(vec![(0, -1, 0), (0, 1, 0)])
+    );
+
+    let circle = get_movement_map("shape = \"circle\"\nradius = 2").unwrap();
+    assert_eq!(circle.len(), 12);
+    assert_eq!(
+        circle.iter().fold((0, 0, 0), |total, step| (
+            total.0 + step.0,
+            total.1 + step.1,
+            total.2 + step.2
+        )),
+        (0, 0, 0)
+    );
+}
+
+#[test]
+fn phase_and_period_shift_and_stretch_a_movement() {
+    assert_eq!(
+        get_movement_map("shape = \"elevator\"\nheight = 1\nperiod = 3\nphase = 1"),
+        Ok(vec![(0, -1, 0), (0, 0, 0), (0, 1, 0)])
+    );
+    assert!(get_movement_map("shape = \"elevator\"\nheight = 2\nperiod = 3").is_err());
+}
+
+#[test]
+fn enemies_are_kept_when_saving() {
+    let level: Level = toml::from_str(&format!(
+        r#"{METADATA}
+[[layers]]
+height_map = "0.."
+modifiers = ["P.."]
+
+[[layers.tiles]]
+q = 0
+r = 0
+height = 0
+enemy = "patrol"
+route = ["E", "W"]
+
+[[layers.tiles]]
+q = 1
+r = 0
+height = 0
+enemy = "chase"
+"#
+    ))
+    .unwrap();
+
+    let saved = EditableLevel::from_level(&level).unwrap().to_toml();
+    for expected in [
+        "enemy = \"patrol\"",
+        "route = [\"E\", \"W\"]",
+        "enemy = \"chase\"",
+    ] {
+        assert!(saved.contains(expected), "{saved}");
+    }
+
+    let saved_level: Level = toml::from_str(&saved).unwrap();
+    assert_eq!(spawned_objects(saved_level), spawned_objects(level));
+}
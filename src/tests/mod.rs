@@ -0,0 +1,223 @@
+//! Headless tests that run the turn rules without a window or GPU.
+
+use std::{fs, path::Path, time::Duration};
+
+use bevy::{ecs::system::RunSystemOnce, prelude::*, time::TimeUpdateStrategy};
+
+use crate::{
+    components::{
+        camera::CameraAngle,
+        enemy::Enemy,
+        goal::Goal,
+        key::Key,
+        level::Level,
+        movement::Movement,
+        player::Player,
+        tile::{IcyTile, MovementMap, Tile, TileEdges},
+        tile_coordinates::{MovementDirection, TileCoordinates},
+    },
+    resources::{
+        effects::GlobalEffects,
+        levels::{LevelResource, LevelState},
+        pathfinding::PlannedMoves,
+        replay::{ReplayRecorder, ReplayStep},
+    },
+    systems::turn::TurnPlugin,
+};
+
+mod audio;
+mod editor;
+mod generator;
+mod ghost;
+mod input;
+mod level_format;
+mod movement;
+mod solutions;
+
+/// The number of frames after which a turn is assumed to never finish,
+/// which happens when something falls into the water.
+const MAX_FRAMES_PER_TURN: usize = 1000;
+
+/// The numbers of all level files in `levels/`.
+pub fn level_numbers() -> Vec<isize> {
+    let mut level_numbers: Vec<isize> =
+        fs::read_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("levels"))
+            .expect("Could not read the levels directory")
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                path.extension()
+                    .is_some_and(|extension| extension == "toml")
+                    .then(|| path.file_stem()?.to_str()?.parse().ok())?
+            })
+            .collect();
+    level_numbers.sort();
+
+    assert!(!level_numbers.is_empty(), "No levels found");
+
+    level_numbers
+}
+
+/// Builds an app with only the gameplay systems, so that it can run without rendering.
+pub fn headless_app() -> App {
+    let mut app = App::new();
+
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<Scene>()
+        .init_asset::<Font>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            50,
+        )))
+        .insert_resource(GlobalEffects {
+            goal_explosion_effect: Some(Handle::default()),
+            ..default()
+        })
+        .add_plugins(TurnPlugin);
+
+    app.world_mut().spawn(CameraAngle::default());
+
+    app
+}
+
+/// A level with a single layer, for a test. The extra TOML follows the metadata, so it can add to
+/// the metadata as well as define movements, switches and the water. The tiles are listed in the
+/// layer.
+pub fn test_level(extra_toml: &str, height_map: &str, modifiers: &[&str], tiles: &str) -> Level {
+    let modifiers: Vec<String> = modifiers
+        .iter()
+        .map(|modifier_map| format!(r#""""{modifier_map}""""#))
+        .collect();
+
+    toml::from_str(&format!(
+        r#"
+        [metadata]
+        name = "Test"
+        biome = "DAYLIGHT"
+        help_text = ""
+        {extra_toml}
+
+        [[layers]]
+        height_map = """{height_map}"""
+        modifiers = [{}]
+        {tiles}
+        "#,
+        modifiers.join(", ")
+    ))
+    .expect("Could not parse test level")
+}
+
+pub fn load_level(app: &mut App, level: Level) {
+    app.world_mut()
+        .run_system_once(
+            move |mut commands: Commands, asset_server: Res<AssetServer>| {
+                level.render_level(&mut commands, &asset_server)
+            },
+        )
+        .expect("Could not run the level system")
+        .expect("Could not render level");
+
+    app.update();
+}
+
+/// Makes a single move and runs the game until the turn has finished.
+/// Returns whether the move was allowed.
+pub fn play_move(app: &mut App, direction: MovementDirection) -> bool {
+    let moves_before = app.world().resource::<ReplayRecorder>().steps.len();
+
+    app.world_mut()
+        .resource_mut::<PlannedMoves>()
+        .directions
+        .push_back(direction);
+
+    for _ in 0..MAX_FRAMES_PER_TURN {
+        app.update();
+
+        let world = app.world_mut();
+        let is_moving = world
+            .query_filtered::<(), With<Movement>>()
+            .iter(world)
+            .next()
+            .is_some();
+
+        if !is_moving
+            && world.resource::<PlannedMoves>().directions.is_empty()
+            && matches!(
+                world.resource::<LevelResource>().level_state,
+                LevelState::WaitingForPlayerInput
+            )
+        {
+            return world.resource::<ReplayRecorder>().steps.len() > moves_before;
+        }
+    }
+
+    panic!("The turn did not finish within {MAX_FRAMES_PER_TURN} frames");
+}
+
+pub fn play_steps(app: &mut App, steps: &[ReplayStep]) -> Result<(), String> {
+    for (index, step) in steps.iter().enumerate() {
+        if let ReplayStep::Move(direction) = step
+            && !play_move(app, *direction)
+        {
+            return Err(format!(
+                "Step {} ({}) could not be played",
+                index + 1,
+                step.to_token()
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+pub fn count_goals(app: &mut App) -> usize {
+    // Goals are collected in the frame after the player arrives.
+    app.update();
+
+    let world = app.world_mut();
+    world.query_filtered::<(), With<Goal>>().iter(world).count()
+}
+
+pub fn player_coordinates(app: &mut App) -> (isize, isize, isize) {
+    let world = app.world_mut();
+    let player = world
+        .query_filtered::<&TileCoordinates, With<Player>>()
+        .single(world)
+        .expect("Expected exactly one player");
+
+    (player.x, player.y, player.z)
+}
+
+/// A description of everything that is spawned for the level, in a stable order.
+pub fn spawned_objects(level: Level) -> Vec<String> {
+    let mut app = headless_app();
+    load_level(&mut app, level);
+
+    let world = app.world_mut();
+    let mut objects: Vec<String> = world
+        .query::<(
+            &TileCoordinates,
+            Has<Player>,
+            Has<Goal>,
+            Has<Tile>,
+            Has<IcyTile>,
+            Option<&MovementMap>,
+            Option<&TileEdges>,
+            Option<&Key>,
+            Option<&Enemy>,
+        )>()
+        .iter(world)
+        .map(|(tile_coordinates, is_player, is_goal, is_tile, is_icy, movement_map, edges, key, enemy)| {
+            format!(
+                "{:?} player: {is_player}, goal: {is_goal}, tile: {is_tile}, top: {}, icy: {is_icy}, movement map: {:?}, edges: {:?}, key: {:?}, enemy: {:?}",
+                (tile_coordinates.x, tile_coordinates.y, tile_coordinates.z),
+                tile_coordinates.is_on_top,
+                movement_map.map(|movement_map| &movement_map.map),
+                edges,
+                key.map(|key| key.color),
+                enemy.map(|enemy| &enemy.behaviour),
+            )
+        })
+        .collect();
+
+    objects.sort();
+    objects
+}
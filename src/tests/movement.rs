@@ -0,0 +1,793 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::{
+        enemy::{Caught, Enemy},
+        goal::Goal,
+        level::{Level, LoseTextMarker},
+        player::{Drowned, Player},
+        tile::{MovementMap, TileEdges},
+        tile_coordinates::{MovementDirection, TileCoordinates},
+    },
+    resources::{
+        keys::KeyInventory, pathfinding::PlannedMoves, settings::Settings, stats::LevelStats,
+        switches::SwitchStates,
+    },
+    simulation::Simulation,
+    systems::hud::{count_elapsed_time, show_lose_text},
+    tests::{count_goals, headless_app, load_level, play_move, player_coordinates, test_level},
+};
+
+#[test]
+fn moving_onto_a_tile_of_equal_height() {
+    let mut app = headless_app();
+    load_level(&mut app, test_level("", "00", &["P."], ""));
+    let (x, y, z) = player_coordinates(&mut app);
+
+    assert!(play_move(&mut app, MovementDirection::East));
+    assert_eq!(player_coordinates(&mut app), (x + 1, y, z));
+}
+
+#[test]
+fn cannot_move_onto_a_higher_tile_or_into_the_water() {
+    let mut app = headless_app();
+    load_level(&mut app, test_level("", "01", &["P."], ""));
+    let start = player_coordinates(&mut app);
+
+    assert!(!play_move(&mut app, MovementDirection::East));
+    assert!(!play_move(&mut app, MovementDirection::West));
+    assert_eq!(player_coordinates(&mut app), start);
+}
+
+#[test]
+fn falling_down_onto_a_lower_tile() {
+    let mut app = headless_app();
+    load_level(&mut app, test_level("", "20", &["P."], ""));
+    let (x, _, z) = player_coordinates(&mut app);
+
+    assert!(play_move(&mut app, MovementDirection::East));
+    assert_eq!(player_coordinates(&mut app), (x + 1, 0, z));
+}
+
+#[test]
+fn sliding_across_icy_tiles() {
+    let mut app = headless_app();
+    load_level(&mut app, test_level("", "0000", &["PII."], ""));
+    let (x, y, z) = player_coordinates(&mut app);
+
+    assert!(play_move(&mut app, MovementDirection::East));
+    assert_eq!(player_coordinates(&mut app), (x + 3, y, z));
+}
+
+#[test]
+fn sliding_stops_in_front_of_a_higher_tile() {
+    let mut app = headless_app();
+    load_level(&mut app, test_level("", "0001", &["PII."], ""));
+    let (x, y, z) = player_coordinates(&mut app);
+
+    assert!(play_move(&mut app, MovementDirection::East));
+    assert_eq!(player_coordinates(&mut app), (x + 2, y, z));
+}
+
+#[test]
+fn sliding_off_the_ice_and_falling() {
+    let mut app = headless_app();
+    load_level(&mut app, test_level("", "1110", &["PII."], ""));
+    let (x, _, z) = player_coordinates(&mut app);
+
+    assert!(play_move(&mut app, MovementDirection::East));
+    assert_eq!(player_coordinates(&mut app), (x + 3, 0, z));
+}
+
+/// An elevator that goes up and down, and waits at the bottom for a turn.
+const ELEVATOR_WITH_PERIOD: &str = "[movements.a]\nshape = \"elevator\"\nheight = 1\nperiod = 3";
+
+#[test]
+fn moving_tiles_wait_for_the_rest_of_their_period() {
+    let level = test_level(ELEVATOR_WITH_PERIOD, "000", &["P.a"], "");
+
+    let mut app = headless_app();
+    load_level(&mut app, level);
+
+    let mut heights = vec![];
+    for direction in [
+        MovementDirection::East,
+        MovementDirection::West,
+        MovementDirection::East,
+        MovementDirection::West,
+    ] {
+        assert!(play_move(&mut app, direction));
+
+        let world = app.world_mut();
+        let (_, platform) = world
+            .query::<(&MovementMap, &TileCoordinates)>()
+            .iter(world)
+            .find(|(movement_map, _)| !movement_map.map.is_empty())
+            .expect("Expected a moving tile");
+        heights.push(platform.y);
+    }
+
+    assert_eq!(heights, [1, 0, 0, 1]);
+}
+
+#[test]
+fn paths_to_distant_tiles_ride_on_moving_tiles() {
+    // The tile on the right can only be reached by riding the elevator up.
+    let level = test_level(ELEVATOR_WITH_PERIOD, "001", &["Pa."], "");
+
+    let simulation = Simulation::from_level(&level).unwrap();
+    let path = simulation
+        .find_path(&simulation.initial_state, (1, 1, 0), 1000)
+        .expect("Expected a path to the tile");
+
+    assert_eq!(path, [MovementDirection::East, MovementDirection::East]);
+    assert_eq!(play_moves(level, &path).1, (1, 1, 0));
+}
+
+const LINKED_PLATFORM: &str =
+    "[movements.a]\nshape = \"ping_pong\"\ndirection = \"NE\"\namplitude = 1\nlinked = true";
+
+/// Moves east in both the game and the simulation, and checks that the player ends up in the same place.
+/// Returns the position of the player before and after the move.
+fn move_east(level: Level) -> ((isize, isize, isize), (isize, isize, isize)) {
+    play_moves(level, &[MovementDirection::East])
+}
+
+/// Plays the moves in both the game and the simulation, and checks that the player ends up in the same
+/// place after every move. Returns the position of the player before and after the moves.
+fn play_moves(
+    level: Level,
+    directions: &[MovementDirection],
+) -> ((isize, isize, isize), (isize, isize, isize)) {
+    let simulation = Simulation::from_level(&level).unwrap();
+    let mut state = simulation.initial_state.clone();
+
+    let mut app = headless_app();
+    load_level(&mut app, level);
+    let start = player_coordinates(&mut app);
+
+    for direction in directions {
+        state = simulation
+            .play_move(&state, *direction)
+            .expect("The simulation did not allow the move");
+
+        assert!(play_move(&mut app, *direction));
+        assert_eq!(state.player, player_coordinates(&mut app));
+    }
+
+    (start, player_coordinates(&mut app))
+}
+
+#[test]
+fn linked_tiles_carry_the_player_on_any_of_them() {
+    for player in ["P..", ".P."] {
+        let ((x, y, z), end) = move_east(test_level(LINKED_PLATFORM, "000", &[player, ".aa"], ""));
+        assert_eq!(end, (x + 1, y, z + 1));
+    }
+}
+
+#[test]
+fn linked_tiles_stay_in_place_if_one_of_them_is_blocked() {
+    // The right tile of the platform would move into the tile above it, so neither tile moves.
+    let level = test_level(
+        LINKED_PLATFORM,
+        "..0.\n0000",
+        &["....\nP...", "....\n.aa."],
+        "",
+    );
+    let ((x, y, z), end) = move_east(level);
+    assert_eq!(end, (x + 1, y, z));
+}
+
+#[test]
+fn blocked_tiles_take_the_same_step_once_they_are_free() {
+    // The tile on the left moves east and back, but waits while the elevator next to it is down.
+    let movements = "[movements.a]\nshape = \"ping_pong\"\ndirection = \"E\"\namplitude = 1\n\
+        [movements.b]\nshape = \"offsets\"\noffsets = [[0, 0, 0], [0, 1, 0], [0, -1, 0]]";
+    let level = test_level(movements, ".0000", &[".abP."], "");
+
+    let simulation = Simulation::from_level(&level).unwrap();
+    let mut state = simulation.initial_state.clone();
+
+    let mut app = headless_app();
+    load_level(&mut app, level);
+
+    let mut positions = vec![];
+    for direction in [
+        MovementDirection::East,
+        MovementDirection::West,
+        MovementDirection::East,
+    ] {
+        state = simulation.play_move(&state, direction).unwrap();
+        assert!(play_move(&mut app, direction));
+
+        let world = app.world_mut();
+        let (_, tile) = world
+            .query::<(&MovementMap, &TileCoordinates)>()
+            .iter(world)
+            .find(|(movement_map, _)| movement_map.map.len() == 2)
+            .expect("Expected the tile moving east and back");
+        positions.push(tile.x);
+        assert_eq!(tile.x, -1 + state.platform_offsets[0].0);
+    }
+
+    assert_eq!(positions, [-1, 0, -1]);
+}
+
+/// The switch `s` of the kind, and an elevator that it triggers.
+fn triggered_elevator(kind: &str) -> String {
+    format!(
+        "[switches.s]\nkind = \"{kind}\"\n\
+        [movements.a]\nshape = \"elevator\"\nheight = 1\ntriggered_by = \"s\""
+    )
+}
+
+#[test]
+fn triggered_tiles_only_move_while_a_plate_is_pressed() {
+    let level = test_level(
+        &triggered_elevator("plate"),
+        "0.0",
+        &["P.a"],
+        "[[layers.tiles]]\nq = 0\nr = 0\nheight = 0\nswitch = \"s\"",
+    );
+
+    let mut app = headless_app();
+    load_level(&mut app, level);
+
+    let mut heights = vec![];
+    for direction in [
+        MovementDirection::East,
+        MovementDirection::West,
+        MovementDirection::West,
+        MovementDirection::East,
+    ] {
+        play_move(&mut app, direction);
+
+        let world = app.world_mut();
+        let (_, elevator) = world
+            .query::<(&MovementMap, &TileCoordinates)>()
+            .iter(world)
+            .find(|(movement_map, _)| !movement_map.map.is_empty())
+            .expect("Expected a moving tile");
+        heights.push(elevator.y);
+    }
+
+    // The move further west goes into the water and is not played.
+    assert_eq!(heights, [1, 1, 1, 0]);
+}
+
+#[test]
+fn toggle_switches_keep_bridges_shown_after_leaving_them() {
+    let tiles = "[[layers.tiles]]\nq = -1\nr = 0\nheight = 0\nswitch = \"s\"\n\
+        [[layers.tiles]]\nq = 0\nr = 0\nheight = 0\nshown_by = \"s\"";
+    let level = test_level(&triggered_elevator("toggle"), "0..0", &["P..."], tiles);
+
+    let ((x, y, z), end) = play_moves(level, &[MovementDirection::East; 3]);
+    assert_eq!(end, (x + 3, y, z));
+}
+
+#[test]
+fn plates_that_something_starts_on_are_pressed_right_away() {
+    let tiles = "[[layers.tiles]]\nq = -1\nr = 0\nheight = 0\nswitch = \"s\"\nplayer = true\n\
+        [[layers.tiles]]\nq = 0\nr = 0\nheight = 0\nshown_by = \"s\"";
+    let level = test_level(&triggered_elevator("plate"), "0...", &["...."], tiles);
+    let simulation = Simulation::from_level(&level).unwrap();
+    assert!(simulation.initial_state.switch_states.is_on("s"));
+
+    let mut app = headless_app();
+    load_level(&mut app, level);
+    assert_eq!(
+        *app.world().resource::<SwitchStates>(),
+        simulation.initial_state.switch_states
+    );
+}
+
+#[test]
+fn hidden_bridges_cannot_be_walked_on() {
+    let tiles = "[[layers.tiles]]\nq = -1\nr = 0\nheight = 0\nswitch = \"s\"\n\
+        [[layers.tiles]]\nq = 0\nr = 0\nheight = 0\nhidden_by = \"s\"";
+    let level = test_level(&triggered_elevator("toggle"), "0..0", &["P..."], tiles);
+
+    let simulation = Simulation::from_level(&level).unwrap();
+    let state = simulation
+        .play_move(&simulation.initial_state, MovementDirection::East)
+        .unwrap();
+    assert!(
+        simulation
+            .play_move(&state, MovementDirection::East)
+            .is_none()
+    );
+
+    let mut app = headless_app();
+    load_level(&mut app, level);
+    assert!(play_move(&mut app, MovementDirection::East));
+    assert_eq!(state.player, player_coordinates(&mut app));
+    assert!(!play_move(&mut app, MovementDirection::East));
+}
+
+/// Two pads with a gap between them. The exit pad is given the extra properties.
+fn teleporters(exit_pad: &str) -> String {
+    "[[layers.tiles]]\nq = -1\nr = 0\nheight = 0\nteleporter = \"t\"\n\
+        [[layers.tiles]]\nq = 1\nr = 0\nheight = 0\nteleporter = \"t\"\n"
+        .to_owned()
+        + exit_pad
+}
+
+#[test]
+fn teleporter_pads_transport_objects_to_the_other_pad() {
+    let ((x, y, z), end) = move_east(test_level("", "0...0", &["P...."], &teleporters("")));
+    assert_eq!(end, (x + 3, y, z));
+}
+
+#[test]
+fn sliding_continues_out_of_an_icy_teleporter_pad() {
+    let ((x, y, z), end) = move_east(test_level(
+        "",
+        "0...0",
+        &["P...."],
+        &teleporters("icy = true"),
+    ));
+    assert_eq!(end, (x + 4, y, z));
+}
+
+#[test]
+fn teleporter_pads_do_nothing_if_the_other_pad_is_taken() {
+    let ((x, y, z), end) = move_east(test_level(
+        "",
+        "0...0",
+        &["P...."],
+        &teleporters("goal = true"),
+    ));
+    assert_eq!(end, (x + 1, y, z));
+}
+
+fn east_conveyor(q: isize) -> String {
+    format!("[[layers.tiles]]\nq = {q}\nr = 0\nheight = 0\nconveyor = \"E\"")
+}
+
+#[test]
+fn conveyors_push_objects_one_hex() {
+    let ((x, y, z), end) = move_east(test_level("", "0.0", &["P.."], &east_conveyor(0)));
+    assert_eq!(end, (x + 2, y, z));
+}
+
+#[test]
+fn conveyors_do_not_push_objects_into_a_higher_tile() {
+    let ((x, y, z), end) = move_east(test_level("", "0.1", &["P.."], &east_conveyor(0)));
+    assert_eq!(end, (x + 1, y, z));
+}
+
+#[test]
+fn objects_pushed_off_an_edge_fall_down() {
+    let tiles = "[[layers.tiles]]\nq = 0\nr = 0\nheight = 2\nconveyor = \"E\"";
+    let ((x, _, z), end) = move_east(test_level("", "2.0", &["P.."], tiles));
+    assert_eq!(end, (x + 2, 0, z));
+}
+
+#[test]
+fn objects_pushed_onto_ice_slide_on() {
+    let ((x, y, z), end) = move_east(test_level("", "0.00", &["P.I."], &east_conveyor(-1)));
+    assert_eq!(end, (x + 3, y, z));
+}
+
+#[test]
+fn goals_are_pushed_along_consecutive_conveyors() {
+    let tiles = "[[layers.tiles]]\nq = 0\nr = 0\nheight = 0\nconveyor = \"E\"\ngoal = true\n\
+        [[layers.tiles]]\nq = 1\nr = 0\nheight = 0\nconveyor = \"E\"";
+    let level = test_level("", "00...0", &["P....."], tiles);
+
+    let simulation = Simulation::from_level(&level).unwrap();
+    let mut state = simulation.initial_state.clone();
+
+    let mut app = headless_app();
+    load_level(&mut app, level);
+
+    let mut goals = vec![];
+    for direction in [
+        MovementDirection::East,
+        MovementDirection::West,
+        MovementDirection::East,
+    ] {
+        state = simulation.play_move(&state, direction).unwrap();
+        assert!(play_move(&mut app, direction));
+
+        let world = app.world_mut();
+        let goal = world
+            .query_filtered::<&TileCoordinates, With<Goal>>()
+            .single(world)
+            .expect("Expected exactly one goal");
+        assert_eq!(state.goals, [(goal.x, goal.y, goal.z)]);
+        goals.push(goal.x);
+    }
+
+    assert_eq!(goals, [1, 2, 2]);
+}
+
+/// Checks that both the game and the simulation refuse the move after the given moves.
+fn assert_blocked_after(
+    level: Level,
+    directions: &[MovementDirection],
+    blocked: MovementDirection,
+) {
+    let simulation = Simulation::from_level(&level).unwrap();
+    let mut state = simulation.initial_state.clone();
+
+    let mut app = headless_app();
+    load_level(&mut app, level);
+
+    for direction in directions {
+        state = simulation.play_move(&state, *direction).unwrap();
+        assert!(play_move(&mut app, *direction));
+    }
+
+    assert!(simulation.play_move(&state, blocked).is_none());
+    assert!(!play_move(&mut app, blocked));
+    assert_eq!(state.player, player_coordinates(&mut app));
+}
+
+#[test]
+fn walls_block_moves_across_their_edge() {
+    let tiles = "[[layers.tiles]]\nq = 0\nr = 0\nheight = 0\nwalls = [\"W\"]";
+    assert_blocked_after(
+        test_level("", "0.0", &["P.."], tiles),
+        &[],
+        MovementDirection::East,
+    );
+
+    // Walls also block leaving the tile that they are on.
+    let tiles = "[[layers.tiles]]\nq = -1\nr = 0\nheight = 0\nplayer = true\nwalls = [\"E\"]";
+    assert_blocked_after(
+        test_level("", ".0.", &["..."], tiles),
+        &[],
+        MovementDirection::East,
+    );
+}
+
+#[test]
+fn sliding_stops_at_a_wall() {
+    let tiles = "[[layers.tiles]]\nq = 0\nr = 0\nheight = 0\nicy = true\nwalls = [\"E\"]";
+    let ((x, y, z), end) = move_east(test_level("", "00.0", &["PI.."], tiles));
+    assert_eq!(end, (x + 2, y, z));
+}
+
+#[test]
+fn one_way_tiles_can_only_be_crossed_in_their_direction() {
+    let tiles = "[[layers.tiles]]\nq = 0\nr = 0\nheight = 0\none_way = \"E\"";
+    let ((x, y, z), end) = play_moves(
+        test_level("", "0.0", &["P.."], tiles),
+        &[MovementDirection::East; 2],
+    );
+    assert_eq!(end, (x + 2, y, z));
+
+    assert_blocked_after(
+        test_level("", "0.0", &["P.."], tiles),
+        &[MovementDirection::East; 2],
+        MovementDirection::West,
+    );
+    assert_blocked_after(
+        test_level("", "0.0", &["P.."], tiles),
+        &[MovementDirection::East],
+        MovementDirection::West,
+    );
+}
+
+#[test]
+fn conveyors_do_not_push_objects_through_walls() {
+    let tiles = "[[layers.tiles]]\nq = 0\nr = 0\nheight = 0\nconveyor = \"E\"\nwalls = [\"E\"]";
+    let ((x, y, z), end) = move_east(test_level("", "0.0", &["P.."], tiles));
+    assert_eq!(end, (x + 1, y, z));
+}
+
+#[test]
+fn tiles_under_water_cannot_be_walked_on() {
+    let level = test_level("[water]\nheight = 1", "10", &["P."], "");
+    assert_blocked_after(level, &[], MovementDirection::East);
+}
+
+#[test]
+fn falling_water_uncovers_tiles() {
+    let water =
+        "[water]\nheight = 1\n[water.movement]\nshape = \"offsets\"\noffsets = [[0, -1, 0]]";
+    let ((x, _, z), end) = play_moves(
+        test_level(water, "110", &["P.."], ""),
+        &[MovementDirection::East; 2],
+    );
+    assert_eq!(end, (x + 2, 0, z));
+}
+
+#[test]
+fn rising_water_drowns_the_player() {
+    let water = "[water]\n[water.movement]\nshape = \"offsets\"\noffsets = [[0, 1, 0]]";
+    let level = test_level(water, "000", &["P.."], "");
+
+    let simulation = Simulation::from_level(&level).unwrap();
+    assert!(
+        simulation
+            .play_move(&simulation.initial_state, MovementDirection::East)
+            .is_none()
+    );
+
+    let mut app = headless_app();
+    load_level(&mut app, level);
+    assert!(play_move(&mut app, MovementDirection::East));
+    assert!(!play_move(&mut app, MovementDirection::East));
+
+    let world = app.world_mut();
+    assert!(
+        world
+            .query_filtered::<(), With<Drowned>>()
+            .single(world)
+            .is_ok()
+    );
+}
+
+fn red_key_and_gate(key_q: Option<isize>) -> String {
+    let key = key_q
+        .map(|q| format!("[[layers.tiles]]\nq = {q}\nr = 0\nheight = 0\nkey = \"red\"\n"))
+        .unwrap_or_default();
+
+    format!("{key}[[layers.tiles]]\nq = 0\nr = 0\nheight = 0\ngate = \"red\"")
+}
+
+#[test]
+fn gates_cannot_be_entered_without_their_key() {
+    let level = test_level("", "00.0", &["P..."], &red_key_and_gate(None));
+    assert_blocked_after(level, &[MovementDirection::East], MovementDirection::East);
+}
+
+#[test]
+fn keys_open_gates_and_are_used_up() {
+    let level = test_level("", "0..0.", &["P...."], &red_key_and_gate(Some(-1)));
+    let ((x, y, z), end) = play_moves(level, &[MovementDirection::East; 3]);
+    assert_eq!(end, (x + 3, y, z));
+
+    let level = test_level("", "0..0.", &["P...."], &red_key_and_gate(Some(-1)));
+    let mut app = headless_app();
+    load_level(&mut app, level);
+    for _ in 0..2 {
+        assert!(play_move(&mut app, MovementDirection::East));
+    }
+
+    let world = app.world_mut();
+    assert!(world.resource::<KeyInventory>().keys.is_empty());
+    assert!(
+        world
+            .query::<&TileEdges>()
+            .iter(world)
+            .all(|edges| edges.gate.is_none())
+    );
+}
+
+#[test]
+fn keys_are_used_while_sliding_or_being_pushed() {
+    // The player picks up the key on the ice and slides on through the gate.
+    let tiles = "[[layers.tiles]]\nq = -1\nr = 0\nheight = 0\nicy = true\nkey = \"red\"\n\
+        [[layers.tiles]]\nq = 0\nr = 0\nheight = 0\nicy = true\ngate = \"red\"";
+    let level = test_level("", "0..0.", &["P...."], tiles);
+    let ((x, y, z), end) = play_moves(level, &[MovementDirection::East]);
+    assert_eq!(end, (x + 3, y, z));
+
+    // The player picks up the key on the conveyor, which pushes them through the gate.
+    let tiles = "[[layers.tiles]]\nq = -1\nr = 0\nheight = 0\nconveyor = \"E\"\nkey = \"red\"\n\
+        [[layers.tiles]]\nq = 0\nr = 0\nheight = 0\ngate = \"red\"";
+    let level = test_level("", "0..0.", &["P...."], tiles);
+    let ((x, y, z), end) = play_moves(level, &[MovementDirection::East]);
+    assert_eq!(end, (x + 2, y, z));
+}
+
+#[test]
+fn levels_are_lost_when_they_run_out_of_moves() {
+    assert!(
+        Simulation::from_level(&test_level("max_moves = 1", "000", &["P.G"], ""))
+            .unwrap()
+            .solve(1000)
+            .is_none()
+    );
+    assert_eq!(
+        Simulation::from_level(&test_level("max_moves = 2", "000", &["P.G"], ""))
+            .unwrap()
+            .solve(1000),
+        Some(vec![MovementDirection::East; 2])
+    );
+
+    let settings: Settings =
+        toml::from_str(include_str!("../settings.toml")).expect("Could not parse settings");
+    let mut app = headless_app();
+    app.insert_resource(settings.input)
+        .add_systems(Update, show_lose_text);
+    load_level(&mut app, test_level("max_moves = 1", "000", &["P.G"], ""));
+    assert!(play_move(&mut app, MovementDirection::East));
+    assert!(!play_move(&mut app, MovementDirection::East));
+    assert_eq!(app.world().resource::<LevelStats>().moves, 1);
+
+    let lose_texts: Vec<String> = app
+        .world_mut()
+        .query_filtered::<&Text, With<LoseTextMarker>>()
+        .iter(app.world())
+        .map(|text| text.0.clone())
+        .collect();
+    assert_eq!(
+        lose_texts,
+        ["Out of moves!\n<R> to undo, <Backspace> to restart"]
+    );
+}
+
+#[test]
+fn the_move_that_collects_the_last_goal_does_not_run_out_of_moves() {
+    let settings: Settings =
+        toml::from_str(include_str!("../settings.toml")).expect("Could not parse settings");
+    let mut app = headless_app();
+    app.insert_resource(settings.input)
+        .add_systems(Update, (count_elapsed_time, show_lose_text));
+    load_level(&mut app, test_level("max_moves = 1", "00", &["PG"], ""));
+
+    app.world_mut()
+        .resource_mut::<PlannedMoves>()
+        .directions
+        .push_back(MovementDirection::East);
+
+    // The clock keeps running and no lose text is shown until the goal is collected.
+    let mut elapsed_secs = 0.0;
+    for _ in 0..100 {
+        if count_goals(&mut app) == 0 {
+            return;
+        }
+
+        app.update();
+
+        let world = app.world_mut();
+        assert_eq!(
+            world
+                .query_filtered::<(), With<LoseTextMarker>>()
+                .iter(world)
+                .count(),
+            0
+        );
+
+        let stats = world.resource::<LevelStats>();
+        assert!(stats.elapsed_secs > elapsed_secs);
+        elapsed_secs = stats.elapsed_secs;
+    }
+
+    panic!("The goal was not collected");
+}
+
+fn enemy_at(q: isize, enemy: &str) -> String {
+    format!("[[layers.tiles]]\nq = {q}\nr = 0\nheight = 0\n{enemy}")
+}
+
+#[test]
+fn patrolling_enemies_catch_the_player() {
+    let enemy = enemy_at(1, "enemy = \"patrol\"\nroute = [\"W\"]");
+    let level = test_level("", "0000", &[".P.."], &enemy);
+
+    let simulation = Simulation::from_level(&level).unwrap();
+    let state = simulation
+        .play_move(&simulation.initial_state, MovementDirection::West)
+        .unwrap();
+    assert!(
+        simulation
+            .play_move(&state, MovementDirection::East)
+            .is_none()
+    );
+
+    let mut app = headless_app();
+    load_level(&mut app, level);
+    assert!(play_move(&mut app, MovementDirection::West));
+    assert!(play_move(&mut app, MovementDirection::East));
+    assert!(!play_move(&mut app, MovementDirection::East));
+
+    let world = app.world_mut();
+    assert!(
+        world
+            .query_filtered::<(), With<Caught>>()
+            .single(world)
+            .is_ok()
+    );
+}
+
+#[test]
+fn chasing_enemies_step_towards_the_player() {
+    let level = test_level("", "00000", &["P...."], &enemy_at(2, "enemy = \"chase\""));
+
+    let simulation = Simulation::from_level(&level).unwrap();
+    let mut state = simulation.initial_state.clone();
+
+    let mut app = headless_app();
+    load_level(&mut app, level);
+
+    for direction in [MovementDirection::East, MovementDirection::West] {
+        state = simulation.play_move(&state, direction).unwrap();
+        assert!(play_move(&mut app, direction));
+    }
+
+    let world = app.world_mut();
+    let enemy = world
+        .query_filtered::<&TileCoordinates, With<Enemy>>()
+        .single(world)
+        .unwrap();
+    assert_eq!((enemy.x, enemy.y, enemy.z), (0, 0, 0));
+    assert_eq!(state.enemies, vec![((0, 0, 0), 0)]);
+}
+
+#[test]
+fn enemies_do_not_step_onto_the_same_hex() {
+    // Both enemies want to step onto the hex between them, but only the first one gets there.
+    let enemies = [
+        enemy_at(0, "enemy = \"patrol\"\nroute = [\"E\"]"),
+        enemy_at(2, "enemy = \"patrol\"\nroute = [\"W\"]"),
+    ]
+    .join("\n");
+    let level = test_level("", "0000000", &["P......"], &enemies);
+
+    let simulation = Simulation::from_level(&level).unwrap();
+    let state = simulation
+        .play_move(&simulation.initial_state, MovementDirection::East)
+        .unwrap();
+
+    let mut app = headless_app();
+    load_level(&mut app, level);
+    assert!(play_move(&mut app, MovementDirection::East));
+
+    let world = app.world_mut();
+    let mut enemies: Vec<(isize, isize, isize)> = world
+        .query_filtered::<&TileCoordinates, With<Enemy>>()
+        .iter(world)
+        .map(|enemy| (enemy.x, enemy.y, enemy.z))
+        .collect();
+    enemies.sort();
+
+    assert_eq!(enemies, [(1, 0, 0), (2, 0, 0)]);
+    assert_eq!(state.enemies, [((1, 0, 0), 0), ((2, 0, 0), 0)]);
+}
+
+#[test]
+fn objects_fall_once_the_tiles_have_finished_moving() {
+    // The conveyor pushes the player off its edge while the platform moves below it.
+    let level = test_level(
+        "[movements.a]\nshape = \"offsets\"\noffsets = [[-1, 0, 0], [1, 0, 0]]",
+        "11.0",
+        &["P..a"],
+        "[[layers.tiles]]\nq = -1\nr = 0\nheight = 1\nconveyor = \"E\"",
+    );
+
+    let ((x, _, z), end) = move_east(level);
+    assert_eq!(end, (x + 2, 0, z));
+}
+
+/// A bridge that hides itself as soon as something stands on it, with a tile in a lower layer below
+/// it. The bridge is given the extra properties.
+fn vanishing_bridge(bridge: &str) -> (String, String) {
+    (
+        "[switches.s]\nkind = \"plate\"".to_owned(),
+        format!(
+            "[[layers.tiles]]\nq = 0\nr = 0\nheight = 0\nswitch = \"s\"\nhidden_by = \"s\"\n{bridge}\n\
+            [[layers]]\nheight_map = \".B\""
+        ),
+    )
+}
+
+#[test]
+fn objects_fall_when_the_tile_below_them_disappears() {
+    let (switch, tiles) = vanishing_bridge("");
+    let ((x, _, z), end) = move_east(test_level(&switch, "0.", &["P."], &tiles));
+    assert_eq!(end, (x + 1, -2, z));
+}
+
+#[test]
+fn stacked_objects_fall_together() {
+    // The left player falls onto the right one, which cannot move, and both fall once the
+    // bridge below them is hidden.
+    let (switch, tiles) = vanishing_bridge("player = true");
+    let mut app = headless_app();
+    load_level(&mut app, test_level(&switch, "1.", &["P."], &tiles));
+    assert!(play_move(&mut app, MovementDirection::East));
+
+    let world = app.world_mut();
+    let players: Vec<(isize, isize, isize)> = world
+        .query_filtered::<&TileCoordinates, With<Player>>()
+        .iter(world)
+        .map(|player| (player.x, player.y, player.z))
+        .collect();
+    assert_eq!(players, [(0, -2, 0); 2]);
+}
@@ -0,0 +1,43 @@
+use std::{fs, path::Path};
+
+use crate::{
+    resources::{levels::LevelResource, replay::ReplayFile},
+    tests::{count_goals, headless_app, level_numbers, load_level, play_steps},
+};
+
+/// Every level must have a solution in `tests/solutions`, stored in the same format
+/// as exported replays. Changing a level invalidates its solution, so that the
+/// solution has to be re-recorded and the level is known to still be winnable.
+#[test]
+fn every_level_is_won_by_its_stored_solution() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+    for level_number in level_numbers() {
+        let levels = LevelResource {
+            current_level_number: level_number,
+            ..Default::default()
+        };
+
+        let solution_path = manifest_dir.join(format!("tests/solutions/{level_number}.toml"));
+        let solution: ReplayFile =
+            toml::from_str(&fs::read_to_string(&solution_path).unwrap_or_else(|_| {
+                panic!("Level {level_number} has no solution at {solution_path:?}")
+            }))
+            .expect("Could not parse solution");
+
+        let steps = solution
+            .parse_steps(level_number, levels.get_level_hash().unwrap())
+            .unwrap_or_else(|error| panic!("{error}"));
+
+        let mut app = headless_app();
+        load_level(&mut app, levels.get_level().unwrap());
+        play_steps(&mut app, &steps)
+            .unwrap_or_else(|error| panic!("Level {level_number}: {error}"));
+
+        assert_eq!(
+            count_goals(&mut app),
+            0,
+            "Level {level_number} was not won by its solution"
+        );
+    }
+}